@@ -0,0 +1,49 @@
+use criterion::black_box;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::Criterion;
+
+use hello_rust::interpreter::intr::Interpreter;
+use hello_rust::interpreter::lexer;
+use hello_rust::interpreter::parser;
+
+use std::io;
+
+const PROGRAMS: [(&str, &str); 3] = [
+    ("arithmetic", include_str!("programs/arithmetic.bas")),
+    ("nested_loops", include_str!("programs/nested_loops.bas")),
+    ("strings", include_str!("programs/strings.bas")),
+];
+
+fn lexing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lex");
+    for (name, source) in PROGRAMS.iter() {
+        group.bench_function(*name, |b| b.iter(|| lexer::lex_all(black_box(source)).unwrap()));
+    }
+    group.finish();
+}
+
+fn parsing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (name, source) in PROGRAMS.iter() {
+        group.bench_function(*name, |b| b.iter(|| parser::parse_str(black_box(source)).unwrap()));
+    }
+    group.finish();
+}
+
+fn running(c: &mut Criterion) {
+    let mut group = c.benchmark_group("run");
+    for (name, source) in PROGRAMS.iter() {
+        // Parse once up front so only execution is measured
+        let ast = parser::parse_str(source).unwrap();
+        group.bench_function(*name, |b| b.iter(|| {
+            let mut interpreter = Interpreter::standalone();
+            interpreter.set_output(Box::new(io::sink()));
+            interpreter.run(black_box(&ast)).unwrap();
+        }));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, lexing, parsing, running);
+criterion_main!(benches);
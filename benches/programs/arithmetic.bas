@@ -0,0 +1,7 @@
+let i = 0;
+let total = 0;
+while i < 10000 then
+  total = total + i * 2 - i / 4;
+  i = i + 1;
+end
+print total;
@@ -0,0 +1,18 @@
+let i = 0;
+let j = 0;
+let count = 0;
+while i < 100 then
+  j = 0;
+  while j < 100 then
+    if j > i then
+      count = count + 1;
+    elseif j == i then
+      count = count + 2;
+    else
+      count = count - 1;
+    end
+    j = j + 1;
+  end
+  i = i + 1;
+end
+print count;
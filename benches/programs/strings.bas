@@ -0,0 +1,13 @@
+let i = 0;
+let matches = 0;
+let name = "hello";
+while i < 5000 then
+  if name == "hello" then
+    matches = matches + 1;
+  end
+  if name != "world" then
+    print name;
+  end
+  i = i + 1;
+end
+print matches;
@@ -0,0 +1,25 @@
+// Run a script on a worker thread and follow it live from the main thread, the way a
+// GUI would update its view
+use hello_rust::interpreter::events::Event;
+use hello_rust::interpreter::program::Program;
+
+use std::sync::mpsc;
+use std::thread;
+
+fn main() {
+    let program = Program::parse("let x = 0;\nwhile x < 3 then\n  x = x + 1;\n  print \"x is\", x;\nend\n")
+        .expect("program should parse");
+
+    let (sender, receiver) = mpsc::channel();
+    let worker = thread::spawn(move || program.run_with_events(sender));
+
+    for event in receiver {
+        match event {
+            Event::StatementEntered { line } => println!("line {}", line),
+            Event::VariableChanged { name, value, type_name } => println!("  {} = {} ({})", name, value, type_name),
+            Event::OutputProduced { text, .. } => print!("  output: {}", text),
+        }
+    }
+
+    worker.join().unwrap().expect("program should run");
+}
@@ -0,0 +1,41 @@
+// Add a PLOT x, y; statement without touching the parser - the application decides both
+// how it's written and what it does
+use hello_rust::interpreter::ast::HostStatement;
+use hello_rust::interpreter::intr::Interpreter;
+use hello_rust::interpreter::lexer::Lexer;
+use hello_rust::interpreter::parser::Parser;
+use hello_rust::interpreter::token::TokenType;
+
+use std::cell::RefCell;
+
+fn main() {
+    let source = "let x = 0;\nwhile x < 4 then\n  plot x, x * x;\n  x = x + 1;\nend\n";
+    let mut lexer = Lexer::new(source.chars().peekable());
+    let mut parser = Parser::new(&mut lexer);
+
+    parser.register_statement("PLOT", |parser| {
+        let x = parser.parse_host_expression()?;
+        parser.expect_token(TokenType::COMMA)?;
+        let y = parser.parse_host_expression()?;
+        parser.expect_token(TokenType::SEMICOLON)?;
+        Ok(HostStatement::new("PLOT", vec![x, y]))
+    });
+
+    let ast = parser.parse().expect("program should parse");
+
+    let points = RefCell::new(Vec::new());
+    let mut interpreter = Interpreter::standalone();
+    interpreter.register_statement_handler("PLOT", Box::new(|_, arguments| {
+        let x = arguments[0].to_number()?;
+        let y = arguments[1].to_number()?;
+        points.borrow_mut().push((x, y));
+        Ok(())
+    }));
+
+    interpreter.run(&ast).expect("program should run");
+    drop(interpreter);
+
+    for (x, y) in points.into_inner() {
+        println!("({}, {})", x, y);
+    }
+}
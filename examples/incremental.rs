@@ -0,0 +1,37 @@
+// Keep a syntax tree up to date as a file is edited, the way an editor integration would,
+// reparsing only the statements each keystroke touches
+use hello_rust::interpreter::incremental::Document;
+use hello_rust::interpreter::incremental::TextEdit;
+use hello_rust::interpreter::parser;
+
+fn main() {
+    let mut document = Document::parse("let x = 1;\nlet y = 2;\nwhile x < 3 then\n  x = x + 1;\nend\nprint x, y;\n")
+        .expect("document should parse");
+
+    let edits = [
+        // Change the 2 on the second line to 20
+        TextEdit::new(19..20, "20"),
+        // Add a line to the loop body
+        TextEdit::new(53..53, "  print x;\n"),
+        // A new first line moves everything after it down
+        TextEdit::new(0..0, "let z = 0;\n"),
+    ];
+
+    for edit in edits.iter() {
+        let reparsed = document.apply(edit).expect("edit should parse");
+        println!("{} statement(s) reparsed", reparsed);
+
+        // Reparsed statements get fresh ids, so the trees match in structure, not in ids
+        let full = parser::parse_str(document.get_source()).expect("source should parse");
+        assert_eq!(document.get_ast(), full);
+    }
+
+    // An edit that doesn't parse is reported and leaves the document alone
+    let before = String::from(document.get_source());
+    if let Err(err) = document.apply(&TextEdit::new(0..3, "lett")) {
+        println!("{}", err);
+    }
+    assert_eq!(document.get_source(), before);
+
+    print!("{}", document.get_ast());
+}
@@ -0,0 +1,21 @@
+// Rewrite source without disturbing its layout - every keyword is upper-cased and all the
+// whitespace in between comes through untouched
+use hello_rust::interpreter::cst;
+use hello_rust::interpreter::token::KEYWORDS;
+
+fn main() {
+    let source = "let x = 1;\nwhile x <  3 then\n\n    print \"x is\",   x;\n  x = x + 1;\nend\n";
+    let (_, mut tree) = cst::parse_lossless(source).expect("source should parse");
+
+    // Nothing is lost on the way through
+    assert_eq!(tree.to_string(), source);
+
+    for token in tree.tokens_mut() {
+        let upper = token.get_text().to_uppercase();
+        if KEYWORDS.contains(&upper.as_str()) {
+            token.set_text(&upper);
+        }
+    }
+
+    print!("{}", tree);
+}
@@ -0,0 +1,20 @@
+// What an editor needs for go-to-definition and hover - each identifier's declaration
+// and type, looked up by the node id the parser gave it
+use hello_rust::interpreter::parser;
+use hello_rust::interpreter::symbol::SymbolTable;
+
+fn main() {
+    let mut ast = parser::parse_str("let total = 0;\nlet name = \"sum\";\nlet i = 1;\nwhile i <= 3 then\n  total = total + i;\n  i = i + 1;\nend\nprint name, total;\n")
+        .expect("program should parse");
+
+    let mut symbols = SymbolTable::new();
+    symbols.process_abstract_syntax_tree(&ast).expect("program should check");
+
+    ast.block.for_each_ident_mut(&mut |ident| {
+        match (symbols.get_resolution(ident.id), symbols.get_type(ident.id)) {
+            (Some(symbol), Some(known)) => println!("#{} {} - declared at #{}, {:?}", ident.id.0, ident.symbol, symbol.declaration.0, known),
+            (Some(symbol), None) => println!("#{} {} - declared at #{}", ident.id.0, ident.symbol, symbol.declaration.0),
+            (None, _) => println!("#{} {} - not a variable", ident.id.0, ident.symbol)
+        }
+    });
+}
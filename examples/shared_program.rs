@@ -0,0 +1,24 @@
+// Parse a script once and run it from several threads at the same time
+use hello_rust::interpreter::program::Program;
+
+use std::thread;
+
+fn main() {
+    let program = Program::parse("let x = 0;\nwhile x < 3 then\n  x = x + 1;\nend\nprint x * 10;\n")
+        .expect("program should parse");
+
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let program = program.clone();
+            thread::spawn(move || {
+                let mut output: Vec<u8> = Vec::new();
+                program.run_with_output(&mut output).expect("program should run");
+                String::from_utf8(output).unwrap()
+            })
+        })
+        .collect();
+
+    for (id, handle) in handles.into_iter().enumerate() {
+        print!("thread {}: {}", id, handle.join().unwrap());
+    }
+}
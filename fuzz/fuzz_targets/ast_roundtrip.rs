@@ -0,0 +1,11 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use hello_rust::interpreter::ast::AbstractSyntaxTree;
+
+// Any tree the fuzzer builds should survive a trip through JSON unchanged
+fuzz_target!(|ast: AbstractSyntaxTree| {
+    let json = ast.to_json();
+    let parsed = AbstractSyntaxTree::from_json(&json).expect("emitted JSON should load");
+    assert_eq!(ast, parsed);
+});
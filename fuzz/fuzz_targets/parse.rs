@@ -0,0 +1,8 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use hello_rust::interpreter::parser;
+
+fuzz_target!(|source: &str| {
+    let _ = parser::parse_str(source);
+});
@@ -0,0 +1,51 @@
+#ifndef HELLO_RUST_H
+#define HELLO_RUST_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+/* Embedding API - build the library with cargo build --release --features ffi */
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define HELLO_RUST_OK 0
+#define HELLO_RUST_ERROR 1
+/* The interpreter itself failed - hello_rust_error says how */
+#define HELLO_RUST_PANIC 2
+
+typedef struct Engine hello_rust_engine;
+
+hello_rust_engine *hello_rust_create(void);
+
+/* Copies the program text, replacing anything loaded before */
+int hello_rust_load(hello_rust_engine *engine, const char *source);
+
+/* Stops later runs with an error after this many statements and loop iterations */
+void hello_rust_set_statement_budget(hello_rust_engine *engine, uint64_t budget);
+
+/* How deeply blocks and expressions may nest, and how deep a run may go across calls. The
+   defaults fit the 2MB stack of a typical thread - lower them on a thread with less */
+void hello_rust_set_max_nesting(hello_rust_engine *engine, size_t nesting);
+void hello_rust_set_max_depth(hello_rust_engine *engine, size_t depth);
+
+/* Runs the loaded program from a clean scope, capturing everything it prints to either stream */
+int hello_rust_run(hello_rust_engine *engine);
+
+/* Owned by the engine, valid until the next hello_rust_run or hello_rust_destroy */
+const char *hello_rust_output(const hello_rust_engine *engine);
+
+/* What the program wrote with EPRINT, with the same lifetime as hello_rust_output */
+const char *hello_rust_error_output(const hello_rust_engine *engine);
+
+/* NULL if the last call succeeded */
+const char *hello_rust_error(const hello_rust_engine *engine);
+
+void hello_rust_destroy(hello_rust_engine *engine);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
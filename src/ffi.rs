@@ -0,0 +1,230 @@
+use crate::interpreter::intr::Interpreter;
+use crate::interpreter::lexer::Lexer;
+use crate::interpreter::parser::ParseLimits;
+use crate::interpreter::parser::Parser;
+
+use std::any::Any;
+use std::ffi::CStr;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::os::raw::c_int;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::ptr;
+
+// Opaque handle handed to C callers - everything it owns is freed by hello_rust_destroy
+pub struct Engine {
+    source: Option<String>,
+    output: CString,
+    error_output: CString,
+    error: Option<CString>,
+    statement_budget: Option<u64>,
+    limits: ParseLimits,
+    max_depth: Option<usize>,
+}
+
+pub const HELLO_RUST_OK: c_int = 0;
+pub const HELLO_RUST_ERROR: c_int = 1;
+// A bug in the interpreter rather than in the program - unwinding into C is undefined, so
+// the panic stops here
+pub const HELLO_RUST_PANIC: c_int = 2;
+
+#[no_mangle]
+pub extern "C" fn hello_rust_create() -> *mut Engine {
+    let engine = Engine {
+        source: None,
+        output: CString::default(),
+        error_output: CString::default(),
+        error: None,
+        statement_budget: None,
+        limits: ParseLimits::default(),
+        max_depth: None,
+    };
+
+    Box::into_raw(Box::new(engine))
+}
+
+/// # Safety
+/// `engine` must come from hello_rust_create and `source` must be a NUL terminated string
+#[no_mangle]
+pub unsafe extern "C" fn hello_rust_load(engine: *mut Engine, source: *const c_char) -> c_int {
+    let engine = match engine.as_mut() {
+        Some(engine) => engine,
+        None => return HELLO_RUST_ERROR
+    };
+
+    if source.is_null() {
+        engine.set_error(String::from("No source given"));
+        return HELLO_RUST_ERROR;
+    }
+
+    match CStr::from_ptr(source).to_str() {
+        Ok(source) => {
+            engine.source = Some(String::from(source));
+            engine.error = None;
+            HELLO_RUST_OK
+        },
+        Err(err) => {
+            engine.set_error(format!("Source is not valid UTF-8 - {}", err));
+            HELLO_RUST_ERROR
+        }
+    }
+}
+
+/// # Safety
+/// `engine` must come from hello_rust_create
+#[no_mangle]
+pub unsafe extern "C" fn hello_rust_set_statement_budget(engine: *mut Engine, budget: u64) {
+    if let Some(engine) = engine.as_mut() {
+        engine.statement_budget = Some(budget);
+    }
+}
+
+/// # Safety
+/// `engine` must come from hello_rust_create
+#[no_mangle]
+pub unsafe extern "C" fn hello_rust_set_max_nesting(engine: *mut Engine, nesting: usize) {
+    if let Some(engine) = engine.as_mut() {
+        engine.limits.max_nesting = nesting;
+    }
+}
+
+/// # Safety
+/// `engine` must come from hello_rust_create
+#[no_mangle]
+pub unsafe extern "C" fn hello_rust_set_max_depth(engine: *mut Engine, depth: usize) {
+    if let Some(engine) = engine.as_mut() {
+        engine.max_depth = Some(depth);
+    }
+}
+
+/// # Safety
+/// `engine` must come from hello_rust_create
+#[no_mangle]
+pub unsafe extern "C" fn hello_rust_run(engine: *mut Engine) -> c_int {
+    let engine = match engine.as_mut() {
+        Some(engine) => engine,
+        None => return HELLO_RUST_ERROR
+    };
+
+    match panic::catch_unwind(AssertUnwindSafe(|| engine.run())) {
+        Ok(code) => code,
+        Err(payload) => {
+            engine.output = CString::default();
+            engine.error_output = CString::default();
+            engine.set_error(format!("The interpreter failed unexpectedly - {}", panic_message(&payload)));
+            HELLO_RUST_PANIC
+        }
+    }
+}
+
+/// # Safety
+/// `engine` must come from hello_rust_create. The string is owned by the engine and is
+/// only valid until the next call to hello_rust_run or hello_rust_destroy
+#[no_mangle]
+pub unsafe extern "C" fn hello_rust_output(engine: *const Engine) -> *const c_char {
+    match engine.as_ref() {
+        Some(engine) => engine.output.as_ptr(),
+        None => ptr::null()
+    }
+}
+
+/// # Safety
+/// `engine` must come from hello_rust_create. What EPRINT wrote, with the same lifetime as
+/// hello_rust_output
+#[no_mangle]
+pub unsafe extern "C" fn hello_rust_error_output(engine: *const Engine) -> *const c_char {
+    match engine.as_ref() {
+        Some(engine) => engine.error_output.as_ptr(),
+        None => ptr::null()
+    }
+}
+
+/// # Safety
+/// `engine` must come from hello_rust_create. Returns NULL when the last call succeeded,
+/// otherwise a string owned by the engine with the same lifetime as hello_rust_output
+#[no_mangle]
+pub unsafe extern "C" fn hello_rust_error(engine: *const Engine) -> *const c_char {
+    match engine.as_ref().and_then(|engine| engine.error.as_ref()) {
+        Some(error) => error.as_ptr(),
+        None => ptr::null()
+    }
+}
+
+/// # Safety
+/// `engine` must come from hello_rust_create and must not be used afterwards
+#[no_mangle]
+pub unsafe extern "C" fn hello_rust_destroy(engine: *mut Engine) {
+    if !engine.is_null() {
+        drop(Box::from_raw(engine));
+    }
+}
+
+impl Engine {
+    fn run(&mut self) -> c_int {
+        let source = match &self.source {
+            Some(source) => source,
+            None => {
+                self.set_error(String::from("No source loaded - call hello_rust_load first"));
+                return HELLO_RUST_ERROR;
+            }
+        };
+
+        let mut lexer = Lexer::new(source.chars().peekable());
+        let mut parser = Parser::new(&mut lexer);
+        parser.set_limits(self.limits);
+        let ast = match parser.parse() {
+            Ok(ast) => ast,
+            Err(err) => {
+                self.set_error(err.to_string());
+                return HELLO_RUST_ERROR;
+            }
+        };
+
+        let mut output: Vec<u8> = Vec::new();
+        let mut error_output: Vec<u8> = Vec::new();
+        let result = {
+            let mut interpreter = Interpreter::standalone();
+            interpreter.set_output(Box::new(&mut output));
+            interpreter.set_error_output(Box::new(&mut error_output));
+            interpreter.set_parse_limits(self.limits);
+            if let Some(budget) = self.statement_budget {
+                interpreter.set_statement_budget(budget);
+            }
+            if let Some(depth) = self.max_depth {
+                interpreter.set_max_depth(depth);
+            }
+            interpreter.run(&ast)
+        };
+
+        self.output = to_c_string(String::from_utf8_lossy(&output).into_owned());
+        self.error_output = to_c_string(String::from_utf8_lossy(&error_output).into_owned());
+
+        match result {
+            Ok(_) => {
+                self.error = None;
+                HELLO_RUST_OK
+            },
+            Err(err) => {
+                self.set_error(err.to_string());
+                HELLO_RUST_ERROR
+            }
+        }
+    }
+
+    fn set_error(&mut self, message: String) {
+        self.error = Some(to_c_string(message));
+    }
+}
+
+// What a panic was raised with, when it's the usual message
+fn panic_message(payload: &Box<dyn Any + Send>) -> &str {
+    payload.downcast_ref::<&str>().copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("no message")
+}
+
+// C strings can't hold interior NULs - drop them rather than lose the whole message
+fn to_c_string(text: String) -> CString {
+    CString::new(text.replace('\0', "")).unwrap_or_default()
+}
@@ -0,0 +1,129 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Stdio;
+
+// Runs every .bas file under a directory and compares what it printed with the files next to it:
+//   foo.expected         stdout (required)
+//   foo.stderr.expected  stderr (empty when missing)
+//   foo.code.expected    exit code (0 when missing)
+//   foo.args             extra flags to run with, separated by whitespace
+//   foo.stdin            fed to the script's standard input (nothing when missing)
+pub fn run(dir: &str) -> bool {
+    let exe = env::current_exe().expect("Unable to find the interpreter executable");
+
+    let mut scripts = Vec::new();
+    if let Err(err) = collect_scripts(Path::new(dir), &mut scripts) {
+        eprintln!("Unable to read {} - {}", dir, err);
+        return false;
+    }
+
+    scripts.sort();
+
+    let mut passed = 0;
+    let mut failed = 0;
+    for script in &scripts {
+        match check_script(&exe, script) {
+            Ok(()) => {
+                passed += 1;
+                println!("PASS {}", script.display());
+            },
+            Err(reason) => {
+                failed += 1;
+                println!("FAIL {}", script.display());
+                println!("  {}", reason);
+            }
+        }
+    }
+
+    println!();
+    println!("{} passed, {} failed", passed, failed);
+    failed == 0
+}
+
+fn collect_scripts(dir: &Path, scripts: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_scripts(&path, scripts)?;
+        } else if path.extension().map(|ext| ext == "bas").unwrap_or(false) {
+            scripts.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+fn check_script(exe: &Path, script: &Path) -> Result<(), String> {
+    let expected_stdout = match fs::read_to_string(script.with_extension("expected")) {
+        Ok(expected) => expected,
+        Err(err) => return Err(format!("missing {} - {}", script.with_extension("expected").display(), err))
+    };
+
+    let expected_stderr = fs::read_to_string(script.with_extension("stderr.expected")).unwrap_or_default();
+    let expected_code: i32 = match fs::read_to_string(script.with_extension("code.expected")) {
+        Ok(code) => match code.trim().parse() {
+            Ok(code) => code,
+            Err(err) => return Err(format!("invalid exit code file - {}", err))
+        },
+        Err(_) => 0
+    };
+
+    let args = fs::read_to_string(script.with_extension("args")).unwrap_or_default();
+    let stdin = fs::read(script.with_extension("stdin")).unwrap_or_default();
+
+    // Run from the script's own directory, so paths in the output don't depend on where the runner was started
+    let dir = script.parent().unwrap_or_else(|| Path::new("."));
+    let name = script.file_name().unwrap_or_default();
+    // Without a home directory, a .rustinterp.toml there can't change the results
+    let mut command = Command::new(exe);
+    command.current_dir(dir).env_remove("HOME").env_remove("USERPROFILE");
+    command.arg("run").arg("--quiet").args(args.split_whitespace()).arg(name);
+    command.stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped());
+
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(err) => return Err(format!("unable to run - {}", err))
+    };
+
+    // Dropping the handle closes it, so a script reading past the input sees its end
+    if let Some(mut input) = child.stdin.take() {
+        if let Err(err) = input.write_all(&stdin) {
+            return Err(format!("unable to write stdin - {}", err));
+        }
+    }
+
+    let output = match child.wait_with_output() {
+        Ok(output) => output,
+        Err(err) => return Err(format!("unable to run - {}", err))
+    };
+
+    let code = output.status.code().unwrap_or(-1);
+    if code != expected_code {
+        return Err(format!("exit code {} but expected {}", code, expected_code));
+    }
+
+    compare("stdout", &expected_stdout, &String::from_utf8_lossy(&output.stdout))?;
+    compare("stderr", &expected_stderr, &String::from_utf8_lossy(&output.stderr))
+}
+
+fn compare(stream: &str, expected: &str, actual: &str) -> Result<(), String> {
+    if expected == actual {
+        return Ok(());
+    }
+
+    // Point at the first line that differs rather than dumping both outputs
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    let mut line = 1;
+    loop {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(e), Some(a)) if e == a => line += 1,
+            (e, a) => return Err(format!("{} differs at line {} - expected {:?} but got {:?}", stream, line, e.unwrap_or("<end>"), a.unwrap_or("<end>")))
+        }
+    }
+}
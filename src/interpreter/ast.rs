@@ -1,17 +1,35 @@
+use serde::Deserialize;
+use serde::Serialize;
+
 use std::fmt;
+use std::slice;
+
+pub mod build;
+mod macros;
 
 trait NodeOutput {
     fn output(&self, level: usize) -> String;
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 pub struct AbstractSyntaxTree {
     pub block: Block
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 pub struct Block {
-    statements: Vec<Statement>
+    statements: Vec<Statement>,
+    #[serde(default)]
+    lines: Vec<usize>,
+    // Which of a multi-file program's files each statement came from - empty for one file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    files: Vec<usize>,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 pub enum Comparator {
     Equal,
     GreaterThan,
@@ -21,58 +39,191 @@ pub enum Comparator {
     NotEqual,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 pub enum Operator {
     Plus,
     Minus,
     Times,
     Divides,
+    Power,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 pub enum Statement {
-    Print(Expression),
+    Print(Vec<Expression>),
+    EPrint(Vec<Expression>),
     Let(Ident, Expression),
-    Assignment(Ident, Expression),
+    Assignment(LValue, Expression),
     If(IfStatement),
     While(Condition, Block),
+    For(ForLoop),
+    Breakpoint,
+    Inspect(Option<Ident>),
+    // Only loops are labeled - `outer: WHILE ... END`
+    Labeled(Ident, Box<Statement>),
+    // Leave or restart the innermost loop, or the one with this label
+    Break(Option<Ident>),
+    Continue(Option<Ident>),
+    // A statement added by the embedding application - see Parser::register_statement
+    Host(HostStatement),
+    // Only at the top level of a program - every function is defined before the program runs
+    FunctionDef(FunctionDef),
+    // Leave the function, with its value if it has one
+    Return(Option<Expression>),
+    // A call made for what it does, with any value thrown away
+    Call(Call),
+    // Only in a function - which scope the function's assignments to these names go to
+    Global(Vec<Ident>),
+    Local(Vec<Ident>),
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 pub enum IfStatement {
     If(Condition, Block, Option<Box<IfStatement>>),
     ElseIf(Condition, Block, Option<Box<IfStatement>>),
     Else(Block),
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 pub enum Expression {
-    Literal(Literal),
+    // The id is how the analysis pass finds the literal's entry in the constant pool
+    Literal(Literal, NodeId),
     Ident(Ident),
     BinaryOp(Box<BinaryOp>),
-    UnaryOp(Box<UnaryOp>)
+    UnaryOp(Box<UnaryOp>),
+    Call(Call),
+    Index(Box<Index>),
+    // [1, 2, 3] - the elements in order, any of which can be any expression
+    Array(Vec<Expression>),
+    // { "a": 1, "b": 2 } - each key and its value, in the order written
+    Map(Vec<(Expression, Expression)>),
 }
 
+// Where an assignment stores its value - a variable, or an element of one, as in s[0] = "a"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
+pub enum LValue {
+    Ident(Ident),
+    Index(Box<LValue>, Expression),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 pub enum Literal {
     String(String),
     Number(String),
+    Decimal(String),
+    Boolean(bool),
+}
+
+// NOT binds tightest, then AND, then OR - the right side of AND and OR is only evaluated
+// when the left doesn't already settle the result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
+pub enum Condition {
+    Compare(Comparison),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+    Not(Box<Condition>),
 }
 
-pub struct Condition {
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
+pub struct Comparison {
     pub left_expression: Expression,
     pub comparator: Comparator,
     pub right_expression: Expression,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 pub struct BinaryOp {
     pub left_term: Expression,
     pub operator: Operator,
     pub right_term: Expression
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 pub struct UnaryOp {
     pub operator: Operator,
     pub term: Expression,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
+pub struct HostStatement {
+    pub keyword: String,
+    pub arguments: Vec<Expression>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
+pub struct Call {
+    pub function: Ident,
+    pub arguments: Vec<Expression>,
+    // The parameter each argument is given to by name - `plot(x = 1)` - or None to go by position
+    #[serde(default)]
+    pub names: Vec<Option<String>>,
+}
+
+// FOR i = 1 TO 10 STEP 2 - the step is 1 when left out
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
+pub struct ForLoop {
+    pub variable: Ident,
+    pub start: Expression,
+    pub end: Expression,
+    pub step: Option<Expression>,
+    pub body: Block,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
+pub struct FunctionDef {
+    pub name: Ident,
+    pub parameters: Vec<Ident>,
+    // What each parameter is given when a call leaves it out - worked out at the call, so it
+    // can use the parameters before it
+    #[serde(default)]
+    pub defaults: Vec<Option<Expression>>,
+    pub body: Block,
+    // MEMO FUNCTION - each call's value is kept, and a call with the same arguments gives it
+    // back without running the body again
+    #[serde(default)]
+    pub memo: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
+pub struct Index {
+    pub target: Expression,
+    pub index: Expression,
+}
+
+// Names a node so analyses can keep what they learn about it in a table of their own
+// rather than in the tree. Ids are unique within a parsed tree; 0 is a node that wasn't
+// built by the parser.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
+pub struct NodeId(pub u32);
+
+// A node with an id, as handed out by a walk of the tree
+enum Node<'a> {
+    Ident(&'a mut Ident),
+    Literal(&'a mut NodeId),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 pub struct Ident {
-    pub symbol: String
+    pub symbol: String,
+    #[serde(default)]
+    pub id: NodeId,
 }
 
 impl AbstractSyntaxTree {
@@ -81,12 +232,28 @@ impl AbstractSyntaxTree {
             block: block
         }
     }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Syntax trees always serialize")
+    }
+
+    pub fn from_json(json: &str) -> Result<AbstractSyntaxTree, serde_json::Error> {
+        serde_json::from_str(json)
+    }
 }
 
 impl Block {
     pub fn new(statements: Vec<Statement>) -> Block {
+        // Line numbers are unknown when a block isn't built from source
+        let lines = vec![0; statements.len()];
+        Block::with_lines(statements, lines)
+    }
+
+    pub fn with_lines(statements: Vec<Statement>, lines: Vec<usize>) -> Block {
         Block {
-            statements: statements
+            statements: statements,
+            lines: lines,
+            files: Vec::new(),
         }
     }
 
@@ -94,28 +261,300 @@ impl Block {
         &self.statements
     }
 
-    pub fn get_length(&self) -> usize {
-        self.statements.len()
+    pub fn iter(&self) -> slice::Iter<'_, Statement> {
+        self.statements.iter()
     }
 
-    pub fn get_statement(&self, idx: usize) -> &Statement {
-        match self.statements.get(idx) {
-            Some(s) => s,
-            None => panic!("Invalid index for statement")
-        }
+    // Pairs each statement with the source line it starts on (0 when unknown)
+    pub fn iter_with_lines(&self) -> impl Iterator<Item = (usize, &Statement)> {
+        // Deserialized blocks may carry fewer lines than statements
+        self.statements.iter()
+            .enumerate()
+            .map(move |(i, statement)| (self.lines.get(i).copied().unwrap_or(0), statement))
+    }
+
+    // Like iter_with_lines, with the index of the file each statement came from in front
+    pub fn iter_with_files(&self) -> impl Iterator<Item = (usize, usize, &Statement)> {
+        self.iter_with_lines()
+            .enumerate()
+            .map(move |(i, (line, statement))| (self.files.get(i).copied().unwrap_or(0), line, statement))
     }
 
     pub fn get_mut_statements(&mut self) -> &mut Vec<Statement> {
         &mut self.statements
     }
+
+    // Moves every known line, nested blocks included, by the same amount - for when the
+    // source above the block gains or loses lines
+    pub fn shift_lines(&mut self, delta: isize) {
+        for line in self.lines.iter_mut().filter(|line| **line > 0) {
+            *line = line.saturating_add_signed(delta);
+        }
+
+        for statement in self.statements.iter_mut() {
+            statement.shift_lines(delta);
+        }
+    }
+
+    // Marks every statement, nested blocks included, as coming from the given file
+    pub fn set_file(&mut self, file: usize) {
+        self.files = vec![file; self.statements.len()];
+
+        for statement in self.statements.iter_mut() {
+            statement.set_file(file);
+        }
+    }
+
+    // Every identifier in the block, nested ones included, in source order
+    pub fn for_each_ident_mut(&mut self, f: &mut dyn FnMut(&mut Ident)) {
+        self.for_each_node_mut(&mut |node| {
+            if let Node::Ident(ident) = node {
+                f(ident);
+            }
+        });
+    }
+
+    // The id of every identifier and literal in the block, in source order
+    pub fn for_each_id_mut(&mut self, f: &mut dyn FnMut(&mut NodeId)) {
+        self.for_each_node_mut(&mut |node| match node {
+            Node::Ident(ident) => f(&mut ident.id),
+            Node::Literal(id) => f(id)
+        });
+    }
+
+    fn for_each_node_mut(&mut self, f: &mut dyn FnMut(Node)) {
+        for statement in self.statements.iter_mut() {
+            statement.for_each_node_mut(f);
+        }
+    }
+
+    // Moves the statements of another block onto the end of this one
+    pub fn append(&mut self, mut other: Block) {
+        self.lines.resize(self.statements.len(), 0);
+        other.lines.resize(other.statements.len(), 0);
+        self.files.resize(self.statements.len(), 0);
+        other.files.resize(other.statements.len(), 0);
+        self.statements.append(&mut other.statements);
+        self.lines.append(&mut other.lines);
+        self.files.append(&mut other.files);
+    }
+}
+
+// Source lines are position info, not structure - trees built by hand should
+// compare equal to the same program parsed from text
+impl PartialEq for Block {
+    fn eq(&self, other: &Block) -> bool {
+        self.statements == other.statements
+    }
+}
+
+// Like lines, ids are bookkeeping - the same name is the same identifier
+impl PartialEq for Ident {
+    fn eq(&self, other: &Ident) -> bool {
+        self.symbol == other.symbol
+    }
+}
+
+// A literal's id is bookkeeping too
+impl PartialEq for Expression {
+    fn eq(&self, other: &Expression) -> bool {
+        match (self, other) {
+            (Expression::Literal(a, _), Expression::Literal(b, _)) => a == b,
+            (Expression::Ident(a), Expression::Ident(b)) => a == b,
+            (Expression::BinaryOp(a), Expression::BinaryOp(b)) => a == b,
+            (Expression::UnaryOp(a), Expression::UnaryOp(b)) => a == b,
+            (Expression::Call(a), Expression::Call(b)) => a == b,
+            (Expression::Index(a), Expression::Index(b)) => a == b,
+            (Expression::Array(a), Expression::Array(b)) => a == b,
+            (Expression::Map(a), Expression::Map(b)) => a == b,
+            _ => false
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Block {
+    type Item = &'a Statement;
+    type IntoIter = slice::Iter<'a, Statement>;
+
+    fn into_iter(self) -> slice::Iter<'a, Statement> {
+        self.statements.iter()
+    }
+}
+
+impl Statement {
+    pub fn shift_lines(&mut self, delta: isize) {
+        match self {
+            Statement::If(if_statement) => if_statement.shift_lines(delta),
+            Statement::While(_, block) => block.shift_lines(delta),
+            Statement::For(for_loop) => for_loop.body.shift_lines(delta),
+            Statement::Labeled(_, statement) => statement.shift_lines(delta),
+            Statement::FunctionDef(function) => function.body.shift_lines(delta),
+            _ => ()
+        }
+    }
+
+    fn set_file(&mut self, file: usize) {
+        match self {
+            Statement::If(if_statement) => if_statement.set_file(file),
+            Statement::While(_, block) => block.set_file(file),
+            Statement::For(for_loop) => for_loop.body.set_file(file),
+            Statement::Labeled(_, statement) => statement.set_file(file),
+            Statement::FunctionDef(function) => function.body.set_file(file),
+            _ => ()
+        }
+    }
+
+    fn for_each_node_mut(&mut self, f: &mut dyn FnMut(Node)) {
+        match self {
+            Statement::Print(expressions) | Statement::EPrint(expressions) => {
+                for expression in expressions.iter_mut() {
+                    expression.for_each_node_mut(f);
+                }
+            },
+            Statement::Let(ident, expression) => {
+                f(Node::Ident(ident));
+                expression.for_each_node_mut(f);
+            },
+            Statement::Assignment(target, expression) => {
+                target.for_each_node_mut(f);
+                expression.for_each_node_mut(f);
+            },
+            Statement::If(if_statement) => if_statement.for_each_node_mut(f),
+            Statement::While(condition, block) => {
+                condition.for_each_node_mut(f);
+                block.for_each_node_mut(f);
+            },
+            Statement::For(for_loop) => {
+                f(Node::Ident(&mut for_loop.variable));
+                for_loop.start.for_each_node_mut(f);
+                for_loop.end.for_each_node_mut(f);
+                if let Some(step) = &mut for_loop.step {
+                    step.for_each_node_mut(f);
+                }
+                for_loop.body.for_each_node_mut(f);
+            },
+            Statement::Labeled(label, statement) => {
+                f(Node::Ident(label));
+                statement.for_each_node_mut(f);
+            },
+            Statement::Inspect(Some(ident)) | Statement::Break(Some(ident)) | Statement::Continue(Some(ident)) => f(Node::Ident(ident)),
+            Statement::Host(host) => {
+                for expression in host.arguments.iter_mut() {
+                    expression.for_each_node_mut(f);
+                }
+            },
+            Statement::FunctionDef(function) => {
+                f(Node::Ident(&mut function.name));
+                for parameter in function.parameters.iter_mut() {
+                    f(Node::Ident(parameter));
+                }
+                for default in function.defaults.iter_mut().flatten() {
+                    default.for_each_node_mut(f);
+                }
+                function.body.for_each_node_mut(f);
+            },
+            Statement::Return(Some(expression)) => expression.for_each_node_mut(f),
+            Statement::Call(call) => call.for_each_node_mut(f),
+            Statement::Global(idents) | Statement::Local(idents) => {
+                for ident in idents.iter_mut() {
+                    f(Node::Ident(ident));
+                }
+            },
+            Statement::Inspect(None) | Statement::Break(None) | Statement::Continue(None) | Statement::Breakpoint | Statement::Return(None) => ()
+        }
+    }
+}
+
+impl IfStatement {
+    fn for_each_node_mut(&mut self, f: &mut dyn FnMut(Node)) {
+        match self {
+            IfStatement::If(condition, block, else_statement) | IfStatement::ElseIf(condition, block, else_statement) => {
+                condition.for_each_node_mut(f);
+                block.for_each_node_mut(f);
+                if let Some(else_statement) = else_statement {
+                    else_statement.for_each_node_mut(f);
+                }
+            },
+            IfStatement::Else(block) => block.for_each_node_mut(f)
+        }
+    }
+
+    fn shift_lines(&mut self, delta: isize) {
+        match self {
+            IfStatement::If(_, block, else_statement) | IfStatement::ElseIf(_, block, else_statement) => {
+                block.shift_lines(delta);
+                if let Some(else_statement) = else_statement {
+                    else_statement.shift_lines(delta);
+                }
+            },
+            IfStatement::Else(block) => block.shift_lines(delta)
+        }
+    }
+
+    fn set_file(&mut self, file: usize) {
+        match self {
+            IfStatement::If(_, block, else_statement) | IfStatement::ElseIf(_, block, else_statement) => {
+                block.set_file(file);
+                if let Some(else_statement) = else_statement {
+                    else_statement.set_file(file);
+                }
+            },
+            IfStatement::Else(block) => block.set_file(file)
+        }
+    }
 }
 
 impl Condition {
+    // A single comparison
     pub fn new(left_expression: Expression, comparator: Comparator, right_expression: Expression) -> Condition {
-        Condition {
+        Condition::Compare(Comparison {
             left_expression: left_expression,
             comparator: comparator,
             right_expression: right_expression
+        })
+    }
+
+    fn for_each_node_mut(&mut self, f: &mut dyn FnMut(Node)) {
+        match self {
+            Condition::Compare(comparison) => {
+                comparison.left_expression.for_each_node_mut(f);
+                comparison.right_expression.for_each_node_mut(f);
+            },
+            Condition::And(left, right) | Condition::Or(left, right) => {
+                left.for_each_node_mut(f);
+                right.for_each_node_mut(f);
+            },
+            Condition::Not(condition) => condition.for_each_node_mut(f)
+        }
+    }
+}
+
+impl Expression {
+    fn for_each_node_mut(&mut self, f: &mut dyn FnMut(Node)) {
+        match self {
+            Expression::Literal(_, id) => f(Node::Literal(id)),
+            Expression::Ident(ident) => f(Node::Ident(ident)),
+            Expression::BinaryOp(op) => {
+                op.left_term.for_each_node_mut(f);
+                op.right_term.for_each_node_mut(f);
+            },
+            Expression::UnaryOp(op) => op.term.for_each_node_mut(f),
+            Expression::Call(call) => call.for_each_node_mut(f),
+            Expression::Index(index) => {
+                index.target.for_each_node_mut(f);
+                index.index.for_each_node_mut(f);
+            },
+            Expression::Array(elements) => {
+                for element in elements {
+                    element.for_each_node_mut(f);
+                }
+            },
+            Expression::Map(entries) => {
+                for (key, value) in entries {
+                    key.for_each_node_mut(f);
+                    value.for_each_node_mut(f);
+                }
+            }
         }
     }
 }
@@ -139,9 +578,104 @@ impl UnaryOp {
     }
 }
 
+impl HostStatement {
+    pub fn new(keyword: &str, arguments: Vec<Expression>) -> HostStatement {
+        HostStatement {
+            keyword: keyword.to_uppercase(),
+            arguments: arguments,
+        }
+    }
+}
+
+impl Call {
+    pub fn new(function: Ident, arguments: Vec<Expression>) -> Call {
+        Call {
+            function: function,
+            names: vec![None; arguments.len()],
+            arguments: arguments,
+        }
+    }
+
+    // The name an argument is given by, if it has one
+    pub fn get_name(&self, index: usize) -> Option<&str> {
+        self.names.get(index).and_then(Option::as_deref)
+    }
+
+    pub fn has_names(&self) -> bool {
+        self.names.iter().any(Option::is_some)
+    }
+
+    fn for_each_node_mut(&mut self, f: &mut dyn FnMut(Node)) {
+        f(Node::Ident(&mut self.function));
+        for argument in self.arguments.iter_mut() {
+            argument.for_each_node_mut(f);
+        }
+    }
+}
+
+impl LValue {
+    // The variable that ends up changed
+    pub fn get_ident(&self) -> &Ident {
+        match self {
+            LValue::Ident(ident) => ident,
+            LValue::Index(target, _) => target.get_ident()
+        }
+    }
+
+    fn for_each_node_mut(&mut self, f: &mut dyn FnMut(Node)) {
+        match self {
+            LValue::Ident(ident) => f(Node::Ident(ident)),
+            LValue::Index(target, index) => {
+                target.for_each_node_mut(f);
+                index.for_each_node_mut(f);
+            }
+        }
+    }
+}
+
+impl ForLoop {
+    pub fn new(variable: Ident, start: Expression, end: Expression, step: Option<Expression>, body: Block) -> ForLoop {
+        ForLoop {
+            variable: variable,
+            start: start,
+            end: end,
+            step: step,
+            body: body,
+        }
+    }
+}
+
+impl FunctionDef {
+    pub fn new(name: Ident, parameters: Vec<Ident>, body: Block) -> FunctionDef {
+        FunctionDef {
+            name: name,
+            defaults: vec![None; parameters.len()],
+            parameters: parameters,
+            body: body,
+            memo: false,
+        }
+    }
+}
+
+impl Index {
+    pub fn new(target: Expression, index: Expression) -> Index {
+        Index {
+            target: target,
+            index: index,
+        }
+    }
+}
+
 impl Ident {
     pub fn new(symbol: String) -> Ident {
-        Ident { symbol: symbol }
+        Ident::with_id(symbol, NodeId::default())
+    }
+
+    pub fn with_id(symbol: String, id: NodeId) -> Ident {
+        Ident {
+            symbol: symbol,
+            id: id,
+        }
     }
 }
 
@@ -151,13 +685,19 @@ impl fmt::Display for AbstractSyntaxTree {
     }
 }
 
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.output(0))
+    }
+}
+
 impl NodeOutput for Block {
     fn output(&self, level: usize) -> String {
         let mut output = String::new();
         output.push_str(&"  ".repeat(level));
         output.push_str("block");
         for statement in &self.statements {
-            output.push_str("\n");
+            output.push('\n');
             output.push_str(&statement.output(level + 1));
         }
         output
@@ -172,7 +712,8 @@ impl NodeOutput for Operator {
             Operator::Plus => "+",
             Operator::Minus => "-",
             Operator::Times => "*",
-            Operator::Divides => "/"
+            Operator::Divides => "/",
+            Operator::Power => "^"
         });
         output
     }
@@ -199,24 +740,107 @@ impl NodeOutput for Statement {
         let mut output = String::new();
         output.push_str(&"  ".repeat(level));
         match self {
-            Statement::Print(expression) => {
-                output.push_str("print\n");
-                output.push_str(&expression.output(level + 1));
+            Statement::Print(expressions) => {
+                output.push_str("print");
+                for expression in expressions {
+                    output.push('\n');
+                    output.push_str(&expression.output(level + 1));
+                }
+            },
+            Statement::EPrint(expressions) => {
+                output.push_str("eprint");
+                for expression in expressions {
+                    output.push('\n');
+                    output.push_str(&expression.output(level + 1));
+                }
             },
             Statement::Let(ident, expression) => {
                 output.push_str("let\n");
                 output.push_str(&"  ".repeat(level + 1));
                 output.push_str(&ident.symbol);
-                output.push_str("\n");
+                output.push('\n');
                 output.push_str(&expression.output(level + 1));
             },
             Statement::If(if_statement) => output.push_str(&if_statement.output(level)),
             Statement::While(condition, block) => {
                 output.push_str("while\n");
                 output.push_str(&condition.output(level + 1));
-                output.push_str("\n");
+                output.push('\n');
                 output.push_str(&block.output(level + 1));
-            }
+            },
+            Statement::For(for_loop) => {
+                output.push_str("for ");
+                output.push_str(&for_loop.variable.symbol);
+                output.push('\n');
+                output.push_str(&for_loop.start.output(level + 1));
+                output.push('\n');
+                output.push_str(&for_loop.end.output(level + 1));
+                if let Some(step) = &for_loop.step {
+                    output.push('\n');
+                    output.push_str(&step.output(level + 1));
+                }
+                output.push('\n');
+                output.push_str(&for_loop.body.output(level + 1));
+            },
+            Statement::Breakpoint => output.push_str("breakpoint"),
+            Statement::Inspect(ident) => {
+                output.push_str("inspect");
+                if let Some(ident) = ident {
+                    output.push('\n');
+                    output.push_str(&"  ".repeat(level + 1));
+                    output.push_str(&ident.symbol);
+                }
+            },
+            Statement::Labeled(label, statement) => {
+                output.push_str(&label.symbol);
+                output.push_str(":\n");
+                output.push_str(&statement.output(level + 1));
+            },
+            Statement::Break(label) | Statement::Continue(label) => {
+                output.push_str(if matches!(self, Statement::Break(_)) { "break" } else { "continue" });
+                if let Some(label) = label {
+                    output.push(' ');
+                    output.push_str(&label.symbol);
+                }
+            },
+            Statement::Host(host) => {
+                output.push_str(&host.keyword.to_lowercase());
+                for argument in &host.arguments {
+                    output.push('\n');
+                    output.push_str(&argument.output(level + 1));
+                }
+            },
+            Statement::FunctionDef(function) => {
+                if function.memo {
+                    output.push_str("memo ");
+                }
+                output.push_str("function ");
+                output.push_str(&function.name.symbol);
+                let parameters: Vec<&str> = function.parameters.iter().map(|parameter| parameter.symbol.as_str()).collect();
+                output.push_str(&format!("({})\n", parameters.join(", ")));
+                for (parameter, default) in function.parameters.iter().zip(&function.defaults) {
+                    if let Some(default) = default {
+                        output.push_str(&"  ".repeat(level + 1));
+                        output.push_str(&format!("default {}\n", parameter.symbol));
+                        output.push_str(&default.output(level + 2));
+                        output.push('\n');
+                    }
+                }
+                output.push_str(&function.body.output(level + 1));
+            },
+            Statement::Return(expression) => {
+                output.push_str("return");
+                if let Some(expression) = expression {
+                    output.push('\n');
+                    output.push_str(&expression.output(level + 1));
+                }
+            },
+            Statement::Call(call) => output.push_str(call.output(level).trim_start()),
+            Statement::Global(idents) | Statement::Local(idents) => {
+                output.push_str(if matches!(self, Statement::Global(_)) { "global " } else { "local " });
+                let names: Vec<&str> = idents.iter().map(|ident| ident.symbol.as_str()).collect();
+                output.push_str(&names.join(", "));
+            },
             _ => output.push_str("")
         }
         output
@@ -230,29 +854,23 @@ impl NodeOutput for IfStatement {
             IfStatement::If(condition, block, other) => {
                 output.push_str("if\n");
                 output.push_str(&condition.output(level + 1));
-                output.push_str("\n");
+                output.push('\n');
                 output.push_str(&block.output(level + 1));
-                match other {
-                    Some(if_statement) => {
-                        output.push_str("\n");
-                        output.push_str(&if_statement.output(level + 1));
-                    },
-                    None => {}
-                };
+                if let Some(if_statement) = other {
+                    output.push('\n');
+                    output.push_str(&if_statement.output(level + 1));
+                }
             },
             IfStatement::ElseIf(condition, block, other) => {
                 output.push_str(&"  ".repeat(level));
                 output.push_str("elseif\n");
                 output.push_str(&condition.output(level + 1));
-                output.push_str("\n");
+                output.push('\n');
                 output.push_str(&block.output(level + 1));
-                match other {
-                    Some(if_statement) => {
-                        output.push_str("\n");
-                        output.push_str(&if_statement.output(level + 1));
-                    },
-                    None => {}
-                };
+                if let Some(if_statement) = other {
+                    output.push('\n');
+                    output.push_str(&if_statement.output(level + 1));
+                }
 
             },
             IfStatement::Else(block) => {
@@ -270,11 +888,61 @@ impl NodeOutput for Expression {
         let mut output = String::new();
         match self {
             Expression::BinaryOp(op) => output.push_str(&op.output(level)),
-            Expression::UnaryOp(op) => output.push_str(&op.term.output(level)),
-            Expression::Literal(literal) => output.push_str(&literal.output(level)),
+            Expression::UnaryOp(op) => {
+                output.push_str(&op.operator.output(level));
+                output.push('\n');
+                output.push_str(&op.term.output(level + 1));
+            },
+            Expression::Literal(literal, _) => output.push_str(&literal.output(level)),
             Expression::Ident(ident) => {
                 output.push_str(&"  ".repeat(level));
                 output.push_str(&ident.symbol);
+            },
+            Expression::Call(call) => output.push_str(&call.output(level)),
+            Expression::Index(index) => {
+                output.push_str(&"  ".repeat(level));
+                output.push_str("[]\n");
+                output.push_str(&index.target.output(level + 1));
+                output.push('\n');
+                output.push_str(&index.index.output(level + 1));
+            },
+            Expression::Array(elements) => {
+                output.push_str(&"  ".repeat(level));
+                output.push_str("array");
+                for element in elements {
+                    output.push('\n');
+                    output.push_str(&element.output(level + 1));
+                }
+            },
+            Expression::Map(entries) => {
+                output.push_str(&"  ".repeat(level));
+                output.push_str("map");
+                for (key, value) in entries {
+                    output.push('\n');
+                    output.push_str(&key.output(level + 1));
+                    output.push('\n');
+                    output.push_str(&value.output(level + 2));
+                }
+            }
+        }
+        output
+    }
+}
+
+impl NodeOutput for Call {
+    fn output(&self, level: usize) -> String {
+        let mut output = String::new();
+        output.push_str(&"  ".repeat(level));
+        output.push_str(&self.function.symbol);
+        output.push_str("()");
+        for (index, argument) in self.arguments.iter().enumerate() {
+            output.push('\n');
+            if let Some(name) = self.get_name(index) {
+                output.push_str(&"  ".repeat(level + 1));
+                output.push_str(&format!("{} =\n", name));
+                output.push_str(&argument.output(level + 2));
+            } else {
+                output.push_str(&argument.output(level + 1));
             }
         }
         output
@@ -282,12 +950,34 @@ impl NodeOutput for Expression {
 }
 
 impl NodeOutput for Condition {
+    fn output(&self, level: usize) -> String {
+        let mut output = String::new();
+        match self {
+            Condition::Compare(comparison) => return comparison.output(level),
+            Condition::And(left, right) | Condition::Or(left, right) => {
+                output.push_str(&"  ".repeat(level));
+                output.push_str(if matches!(self, Condition::And(_, _)) { "and\n" } else { "or\n" });
+                output.push_str(&left.output(level + 1));
+                output.push('\n');
+                output.push_str(&right.output(level + 1));
+            },
+            Condition::Not(condition) => {
+                output.push_str(&"  ".repeat(level));
+                output.push_str("not\n");
+                output.push_str(&condition.output(level + 1));
+            }
+        }
+        output
+    }
+}
+
+impl NodeOutput for Comparison {
     fn output(&self, level: usize) -> String {
         let mut output = String::new();
         output.push_str(&self.comparator.output(level));
-        output.push_str("\n");
+        output.push('\n');
         output.push_str(&self.left_expression.output(level + 1));
-        output.push_str("\n");
+        output.push('\n');
         output.push_str(&self.right_expression.output(level + 1));
         output
     }
@@ -297,9 +987,9 @@ impl NodeOutput for BinaryOp {
     fn output(&self, level: usize) -> String {
         let mut output = String::new();
         output.push_str(&self.operator.output(level));
-        output.push_str("\n");
+        output.push('\n');
         output.push_str(&self.left_term.output(level + 1));
-        output.push_str("\n");
+        output.push('\n');
         output.push_str(&self.right_term.output(level + 1));
         output
     }
@@ -311,7 +1001,12 @@ impl NodeOutput for Literal {
         output.push_str(&"  ".repeat(level));
         match self {
             Literal::String(s) => output.push_str(s),
-            Literal::Number(s) => output.push_str(s)
+            Literal::Number(s) => output.push_str(s),
+            Literal::Decimal(s) => {
+                output.push_str(s);
+                output.push('d');
+            },
+            Literal::Boolean(b) => output.push_str(if *b { "true" } else { "false" })
         }
         output
     }
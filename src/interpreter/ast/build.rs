@@ -0,0 +1,301 @@
+// Helpers for constructing syntax trees in code rather than from source text, e.g.
+//
+//     program(vec![
+//         let_("x", num(5)),
+//         while_(lt(ident("x"), num(10)), vec![
+//             assign("x", add(ident("x"), num(1))),
+//         ]),
+//         print(ident("x")),
+//     ])
+
+use super::AbstractSyntaxTree;
+use super::BinaryOp;
+use super::Block;
+use super::Call;
+use super::Comparator;
+use super::Condition;
+use super::Expression;
+use super::ForLoop;
+use super::HostStatement;
+use super::Ident;
+use super::Index;
+use super::IfStatement;
+use super::LValue;
+use super::Literal;
+use super::NodeId;
+use super::Operator;
+use super::Statement;
+use super::UnaryOp;
+
+use std::ops;
+
+// Wraps an Expression so trees can be combined with Rust's own arithmetic
+// operators, which share this language's precedence - used by prog!
+pub struct Expr(pub Expression);
+
+pub struct IfBuilder {
+    branches: Vec<(Condition, Vec<Statement>)>,
+    otherwise: Option<Vec<Statement>>,
+}
+
+impl IfBuilder {
+    pub fn else_if(mut self, condition: Condition, statements: Vec<Statement>) -> IfBuilder {
+        self.branches.push((condition, statements));
+        self
+    }
+
+    pub fn else_(mut self, statements: Vec<Statement>) -> IfBuilder {
+        self.otherwise = Some(statements);
+        self
+    }
+
+    pub fn build(self) -> Statement {
+        // Chain from the last branch back to the first, the way the parser nests them
+        let mut other = self.otherwise.map(|statements| Box::new(IfStatement::Else(block(statements))));
+        let mut branches = self.branches;
+        let (first_condition, first_statements) = branches.remove(0);
+
+        while let Some((condition, statements)) = branches.pop() {
+            other = Some(Box::new(IfStatement::ElseIf(condition, block(statements), other)));
+        }
+
+        Statement::If(IfStatement::If(first_condition, block(first_statements), other))
+    }
+}
+
+impl From<IfBuilder> for Statement {
+    fn from(builder: IfBuilder) -> Statement {
+        builder.build()
+    }
+}
+
+pub fn program(statements: Vec<Statement>) -> AbstractSyntaxTree {
+    AbstractSyntaxTree::new(block(statements))
+}
+
+pub fn block(statements: Vec<Statement>) -> Block {
+    Block::new(statements)
+}
+
+// Statements
+
+pub fn print(expression: Expression) -> Statement {
+    Statement::Print(vec![expression])
+}
+
+pub fn print_all(expressions: Vec<Expression>) -> Statement {
+    Statement::Print(expressions)
+}
+
+pub fn eprint(expression: Expression) -> Statement {
+    Statement::EPrint(vec![expression])
+}
+
+pub fn let_(name: &str, expression: Expression) -> Statement {
+    Statement::Let(Ident::new(String::from(name)), expression)
+}
+
+pub fn assign(name: &str, expression: Expression) -> Statement {
+    Statement::Assignment(LValue::Ident(Ident::new(String::from(name))), expression)
+}
+
+pub fn if_(condition: Condition, statements: Vec<Statement>) -> IfBuilder {
+    IfBuilder {
+        branches: vec![(condition, statements)],
+        otherwise: None,
+    }
+}
+
+pub fn while_(condition: Condition, statements: Vec<Statement>) -> Statement {
+    Statement::While(condition, block(statements))
+}
+
+pub fn for_(variable: &str, start: Expression, end: Expression, step: Option<Expression>, statements: Vec<Statement>) -> Statement {
+    Statement::For(ForLoop::new(Ident::new(String::from(variable)), start, end, step, block(statements)))
+}
+
+pub fn labeled(label: &str, statement: Statement) -> Statement {
+    Statement::Labeled(Ident::new(String::from(label)), Box::new(statement))
+}
+
+pub fn break_(label: Option<&str>) -> Statement {
+    Statement::Break(label.map(|label| Ident::new(String::from(label))))
+}
+
+pub fn continue_(label: Option<&str>) -> Statement {
+    Statement::Continue(label.map(|label| Ident::new(String::from(label))))
+}
+
+pub fn breakpoint() -> Statement {
+    Statement::Breakpoint
+}
+
+pub fn inspect(name: Option<&str>) -> Statement {
+    Statement::Inspect(name.map(|name| Ident::new(String::from(name))))
+}
+
+pub fn host(keyword: &str, arguments: Vec<Expression>) -> Statement {
+    Statement::Host(HostStatement::new(keyword, arguments))
+}
+
+// Conditions
+
+pub fn eq(left: Expression, right: Expression) -> Condition {
+    Condition::new(left, Comparator::Equal, right)
+}
+
+pub fn ne(left: Expression, right: Expression) -> Condition {
+    Condition::new(left, Comparator::NotEqual, right)
+}
+
+pub fn lt(left: Expression, right: Expression) -> Condition {
+    Condition::new(left, Comparator::LessThan, right)
+}
+
+pub fn le(left: Expression, right: Expression) -> Condition {
+    Condition::new(left, Comparator::LessThanOrEqual, right)
+}
+
+pub fn gt(left: Expression, right: Expression) -> Condition {
+    Condition::new(left, Comparator::GreaterThan, right)
+}
+
+pub fn ge(left: Expression, right: Expression) -> Condition {
+    Condition::new(left, Comparator::GreaterThanOrEqual, right)
+}
+
+pub fn and(left: Condition, right: Condition) -> Condition {
+    Condition::And(Box::new(left), Box::new(right))
+}
+
+pub fn or(left: Condition, right: Condition) -> Condition {
+    Condition::Or(Box::new(left), Box::new(right))
+}
+
+pub fn not(condition: Condition) -> Condition {
+    Condition::Not(Box::new(condition))
+}
+
+// Expressions
+
+pub fn num<T: ToString>(number: T) -> Expression {
+    Expression::Literal(Literal::Number(number.to_string()), NodeId::default())
+}
+
+pub fn decimal(number: &str) -> Expression {
+    Expression::Literal(Literal::Decimal(String::from(number)), NodeId::default())
+}
+
+pub fn string(text: &str) -> Expression {
+    Expression::Literal(Literal::String(String::from(text)), NodeId::default())
+}
+
+pub fn boolean(value: bool) -> Expression {
+    Expression::Literal(Literal::Boolean(value), NodeId::default())
+}
+
+pub fn call(function: &str, arguments: Vec<Expression>) -> Expression {
+    Expression::Call(Call::new(Ident::new(String::from(function)), arguments))
+}
+
+pub fn index(target: Expression, index: Expression) -> Expression {
+    Expression::Index(Box::new(Index::new(target, index)))
+}
+
+pub fn array(elements: Vec<Expression>) -> Expression {
+    Expression::Array(elements)
+}
+
+pub fn map(entries: Vec<(Expression, Expression)>) -> Expression {
+    Expression::Map(entries)
+}
+
+pub fn ident(name: &str) -> Expression {
+    Expression::Ident(Ident::new(String::from(name)))
+}
+
+pub fn add(left: Expression, right: Expression) -> Expression {
+    binary(left, Operator::Plus, right)
+}
+
+pub fn sub(left: Expression, right: Expression) -> Expression {
+    binary(left, Operator::Minus, right)
+}
+
+pub fn mul(left: Expression, right: Expression) -> Expression {
+    binary(left, Operator::Times, right)
+}
+
+pub fn div(left: Expression, right: Expression) -> Expression {
+    binary(left, Operator::Divides, right)
+}
+
+pub fn pow(left: Expression, right: Expression) -> Expression {
+    binary(left, Operator::Power, right)
+}
+
+pub fn neg(term: Expression) -> Expression {
+    Expression::UnaryOp(Box::new(UnaryOp::new(Operator::Minus, term)))
+}
+
+pub fn binary(left: Expression, operator: Operator, right: Expression) -> Expression {
+    Expression::BinaryOp(Box::new(BinaryOp::new(left, operator, right)))
+}
+
+impl From<i32> for Expr {
+    fn from(number: i32) -> Expr {
+        Expr(num(number))
+    }
+}
+
+impl From<f64> for Expr {
+    fn from(number: f64) -> Expr {
+        Expr(num(number))
+    }
+}
+
+impl From<&str> for Expr {
+    fn from(text: &str) -> Expr {
+        Expr(string(text))
+    }
+}
+
+impl ops::Add for Expr {
+    type Output = Expr;
+
+    fn add(self, right: Expr) -> Expr {
+        Expr(add(self.0, right.0))
+    }
+}
+
+impl ops::Sub for Expr {
+    type Output = Expr;
+
+    fn sub(self, right: Expr) -> Expr {
+        Expr(sub(self.0, right.0))
+    }
+}
+
+impl ops::Mul for Expr {
+    type Output = Expr;
+
+    fn mul(self, right: Expr) -> Expr {
+        Expr(mul(self.0, right.0))
+    }
+}
+
+impl ops::Div for Expr {
+    type Output = Expr;
+
+    fn div(self, right: Expr) -> Expr {
+        Expr(div(self.0, right.0))
+    }
+}
+
+impl ops::Neg for Expr {
+    type Output = Expr;
+
+    fn neg(self) -> Expr {
+        Expr(neg(self.0))
+    }
+}
@@ -0,0 +1,142 @@
+// prog! builds a syntax tree from source-like tokens at compile time, e.g.
+//
+//     let ast = prog! {
+//         let x = 1;
+//         while x < 10 {
+//             x = x + 1;
+//         }
+//         if x == 10 {
+//             print "done";
+//         } elseif x > 10 {
+//             print x;
+//         } else {
+//             print 0;
+//         }
+//     };
+//
+// Blocks use braces instead of THEN ... END so the macro can find where they stop.
+
+#[macro_export]
+macro_rules! prog {
+    // Statements - accumulated into [..] until the input runs out
+    (@stmts [$($out:expr,)*]) => {
+        vec![$($out,)*]
+    };
+    (@stmts [$($out:expr,)*] let $name:ident = $($rest:tt)*) => {
+        $crate::prog!(@let [$($out,)*] $name [] $($rest)*)
+    };
+    (@stmts [$($out:expr,)*] print $($rest:tt)*) => {
+        $crate::prog!(@print [$($out,)*] [] $($rest)*)
+    };
+    (@stmts [$($out:expr,)*] breakpoint ; $($rest:tt)*) => {
+        $crate::prog!(@stmts [$($out,)* $crate::interpreter::ast::build::breakpoint(),] $($rest)*)
+    };
+    (@stmts [$($out:expr,)*] while $($rest:tt)*) => {
+        $crate::prog!(@while [$($out,)*] [] $($rest)*)
+    };
+    (@stmts [$($out:expr,)*] if $($rest:tt)*) => {
+        $crate::prog!(@if [$($out,)*] [] $($rest)*)
+    };
+    (@stmts [$($out:expr,)*] $name:ident = $($rest:tt)*) => {
+        $crate::prog!(@assign [$($out,)*] $name [] $($rest)*)
+    };
+
+    (@let [$($out:expr,)*] $name:ident [$($e:tt)*] ; $($rest:tt)*) => {
+        $crate::prog!(@stmts [$($out,)* $crate::interpreter::ast::build::let_(stringify!($name), $crate::prog!(@expr [] $($e)*)),] $($rest)*)
+    };
+    (@let [$($out:expr,)*] $name:ident [$($e:tt)*] $t:tt $($rest:tt)*) => {
+        $crate::prog!(@let [$($out,)*] $name [$($e)* $t] $($rest)*)
+    };
+
+    (@assign [$($out:expr,)*] $name:ident [$($e:tt)*] ; $($rest:tt)*) => {
+        $crate::prog!(@stmts [$($out,)* $crate::interpreter::ast::build::assign(stringify!($name), $crate::prog!(@expr [] $($e)*)),] $($rest)*)
+    };
+    (@assign [$($out:expr,)*] $name:ident [$($e:tt)*] $t:tt $($rest:tt)*) => {
+        $crate::prog!(@assign [$($out,)*] $name [$($e)* $t] $($rest)*)
+    };
+
+    (@print [$($out:expr,)*] [$($e:tt)*] ; $($rest:tt)*) => {
+        $crate::prog!(@stmts [$($out,)* $crate::interpreter::ast::build::print($crate::prog!(@expr [] $($e)*)),] $($rest)*)
+    };
+    (@print [$($out:expr,)*] [$($e:tt)*] $t:tt $($rest:tt)*) => {
+        $crate::prog!(@print [$($out,)*] [$($e)* $t] $($rest)*)
+    };
+
+    (@while [$($out:expr,)*] [$($c:tt)*] { $($body:tt)* } $($rest:tt)*) => {
+        $crate::prog!(@stmts [$($out,)* $crate::interpreter::ast::build::while_($crate::prog!(@cond [] $($c)*), $crate::prog!(@stmts [] $($body)*)),] $($rest)*)
+    };
+    (@while [$($out:expr,)*] [$($c:tt)*] $t:tt $($rest:tt)*) => {
+        $crate::prog!(@while [$($out,)*] [$($c)* $t] $($rest)*)
+    };
+
+    // IF chains collect the first branch, then fold elseif/else branches into the builder
+    (@if [$($out:expr,)*] [$($c:tt)*] { $($body:tt)* } $($rest:tt)*) => {
+        $crate::prog!(@else [$($out,)*] ($crate::interpreter::ast::build::if_($crate::prog!(@cond [] $($c)*), $crate::prog!(@stmts [] $($body)*))) $($rest)*)
+    };
+    (@if [$($out:expr,)*] [$($c:tt)*] $t:tt $($rest:tt)*) => {
+        $crate::prog!(@if [$($out,)*] [$($c)* $t] $($rest)*)
+    };
+    (@else [$($out:expr,)*] ($builder:expr) elseif $($rest:tt)*) => {
+        $crate::prog!(@elseif [$($out,)*] ($builder) [] $($rest)*)
+    };
+    (@else [$($out:expr,)*] ($builder:expr) else { $($body:tt)* } $($rest:tt)*) => {
+        $crate::prog!(@stmts [$($out,)* $builder.else_($crate::prog!(@stmts [] $($body)*)).build(),] $($rest)*)
+    };
+    (@else [$($out:expr,)*] ($builder:expr) $($rest:tt)*) => {
+        $crate::prog!(@stmts [$($out,)* $builder.build(),] $($rest)*)
+    };
+    (@elseif [$($out:expr,)*] ($builder:expr) [$($c:tt)*] { $($body:tt)* } $($rest:tt)*) => {
+        $crate::prog!(@else [$($out,)*] ($builder.else_if($crate::prog!(@cond [] $($c)*), $crate::prog!(@stmts [] $($body)*))) $($rest)*)
+    };
+    (@elseif [$($out:expr,)*] ($builder:expr) [$($c:tt)*] $t:tt $($rest:tt)*) => {
+        $crate::prog!(@elseif [$($out,)*] ($builder) [$($c)* $t] $($rest)*)
+    };
+
+    // Conditions - split the tokens on the comparison operator
+    (@cond [$($l:tt)*] == $($r:tt)*) => {
+        $crate::interpreter::ast::build::eq($crate::prog!(@expr [] $($l)*), $crate::prog!(@expr [] $($r)*))
+    };
+    (@cond [$($l:tt)*] != $($r:tt)*) => {
+        $crate::interpreter::ast::build::ne($crate::prog!(@expr [] $($l)*), $crate::prog!(@expr [] $($r)*))
+    };
+    (@cond [$($l:tt)*] <= $($r:tt)*) => {
+        $crate::interpreter::ast::build::le($crate::prog!(@expr [] $($l)*), $crate::prog!(@expr [] $($r)*))
+    };
+    (@cond [$($l:tt)*] >= $($r:tt)*) => {
+        $crate::interpreter::ast::build::ge($crate::prog!(@expr [] $($l)*), $crate::prog!(@expr [] $($r)*))
+    };
+    (@cond [$($l:tt)*] < $($r:tt)*) => {
+        $crate::interpreter::ast::build::lt($crate::prog!(@expr [] $($l)*), $crate::prog!(@expr [] $($r)*))
+    };
+    (@cond [$($l:tt)*] > $($r:tt)*) => {
+        $crate::interpreter::ast::build::gt($crate::prog!(@expr [] $($l)*), $crate::prog!(@expr [] $($r)*))
+    };
+    (@cond [$($l:tt)*] $t:tt $($rest:tt)*) => {
+        $crate::prog!(@cond [$($l)* $t] $($rest)*)
+    };
+
+    // Expressions - swap identifiers and literals for Expr values and let Rust apply precedence
+    (@expr [$($out:tt)*]) => {
+        ($($out)*).0
+    };
+    (@expr [$($out:tt)*] $name:ident $($rest:tt)*) => {
+        $crate::prog!(@expr [$($out)* $crate::interpreter::ast::build::Expr($crate::interpreter::ast::build::ident(stringify!($name)))] $($rest)*)
+    };
+    // Keep '-' as an operator, otherwise `-2` would match as one negative literal
+    (@expr [$($out:tt)*] - $($rest:tt)*) => {
+        $crate::prog!(@expr [$($out)* -] $($rest)*)
+    };
+    (@expr [$($out:tt)*] $value:literal $($rest:tt)*) => {
+        $crate::prog!(@expr [$($out)* $crate::interpreter::ast::build::Expr::from($value)] $($rest)*)
+    };
+    (@expr [$($out:tt)*] ($($inner:tt)*) $($rest:tt)*) => {
+        $crate::prog!(@expr [$($out)* $crate::interpreter::ast::build::Expr($crate::prog!(@expr [] $($inner)*))] $($rest)*)
+    };
+    (@expr [$($out:tt)*] $op:tt $($rest:tt)*) => {
+        $crate::prog!(@expr [$($out)* $op] $($rest)*)
+    };
+
+    ($($body:tt)*) => {
+        $crate::interpreter::ast::build::program($crate::prog!(@stmts [] $($body)*))
+    };
+}
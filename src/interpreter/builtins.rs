@@ -0,0 +1,174 @@
+use super::value::Value;
+
+#[cfg(feature = "stdlib-collections")]
+mod collections;
+#[cfg(feature = "stdlib-io")]
+mod io;
+#[cfg(feature = "stdlib-math")]
+mod math;
+#[cfg(feature = "stdlib-string")]
+mod string;
+#[cfg(feature = "stdlib-time")]
+mod time;
+
+// Builtins are grouped by what they give a script access to, so a run can be limited to
+// some of them and a build can leave whole groups out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Module {
+    Io,
+    Math,
+    String,
+    Net,
+    Time,
+    Collections,
+    // EVAL - running text as code is worth switching off on its own
+    Eval,
+}
+
+// Builtins that need the running program rather than only their arguments, so the
+// interpreter carries them out itself
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Intrinsic {
+    Eval,
+}
+
+// A build that leaves every module out has no builtins to make
+#[allow(dead_code)]
+enum Function {
+    Pure(fn(&[Value]) -> Result<Value, String>),
+    Intrinsic(Intrinsic),
+}
+
+// A build that leaves out every module with a predicate never calls this
+#[allow(dead_code)]
+fn truth(value: bool) -> Value {
+    Value::Boolean(value)
+}
+
+// A function every program can call without defining it
+pub struct Builtin {
+    pub name: &'static str,
+    pub module: Module,
+    pub min_arity: usize,
+    pub max_arity: usize,
+    function: Function,
+}
+
+static BUILTINS: &[Builtin] = &[
+    #[cfg(feature = "stdlib-io")]
+    Builtin { name: "READBYTES", module: Module::Io, min_arity: 1, max_arity: 1, function: Function::Pure(io::read_bytes) },
+    #[cfg(feature = "stdlib-io")]
+    Builtin { name: "WRITEBYTES", module: Module::Io, min_arity: 2, max_arity: 2, function: Function::Pure(io::write_bytes) },
+    #[cfg(feature = "stdlib-math")]
+    Builtin { name: "ISNAN", module: Module::Math, min_arity: 1, max_arity: 1, function: Function::Pure(math::is_nan) },
+    #[cfg(feature = "stdlib-math")]
+    Builtin { name: "ISINF", module: Module::Math, min_arity: 1, max_arity: 1, function: Function::Pure(math::is_inf) },
+    #[cfg(feature = "stdlib-string")]
+    Builtin { name: "FORMATNUM", module: Module::String, min_arity: 2, max_arity: 3, function: Function::Pure(string::format_num) },
+    #[cfg(feature = "stdlib-string")]
+    Builtin { name: "CHR", module: Module::String, min_arity: 1, max_arity: 1, function: Function::Pure(string::chr) },
+    #[cfg(feature = "stdlib-string")]
+    Builtin { name: "ORD", module: Module::String, min_arity: 1, max_arity: 1, function: Function::Pure(string::ord) },
+    #[cfg(feature = "stdlib-string")]
+    Builtin { name: "PAD", module: Module::String, min_arity: 2, max_arity: 3, function: Function::Pure(string::pad) },
+    #[cfg(feature = "stdlib-string")]
+    Builtin { name: "STARTSWITH", module: Module::String, min_arity: 2, max_arity: 2, function: Function::Pure(string::starts_with) },
+    #[cfg(feature = "stdlib-string")]
+    Builtin { name: "ENDSWITH", module: Module::String, min_arity: 2, max_arity: 2, function: Function::Pure(string::ends_with) },
+    #[cfg(feature = "stdlib-string")]
+    Builtin { name: "REPLACE", module: Module::String, min_arity: 3, max_arity: 3, function: Function::Pure(string::replace) },
+    #[cfg(feature = "stdlib-string")]
+    Builtin { name: "HEX", module: Module::String, min_arity: 1, max_arity: 1, function: Function::Pure(string::hex) },
+    #[cfg(feature = "stdlib-string")]
+    Builtin { name: "UNHEX", module: Module::String, min_arity: 1, max_arity: 1, function: Function::Pure(string::unhex) },
+    #[cfg(feature = "stdlib-time")]
+    Builtin { name: "DATE", module: Module::Time, min_arity: 3, max_arity: 6, function: Function::Pure(time::date) },
+    #[cfg(feature = "stdlib-time")]
+    Builtin { name: "DIFFDAYS", module: Module::Time, min_arity: 2, max_arity: 2, function: Function::Pure(time::diff_days) },
+    #[cfg(feature = "stdlib-time")]
+    Builtin { name: "FORMATDATE", module: Module::Time, min_arity: 2, max_arity: 2, function: Function::Pure(time::format_date) },
+    #[cfg(feature = "stdlib-collections")]
+    Builtin { name: "KEYS", module: Module::Collections, min_arity: 1, max_arity: 1, function: Function::Pure(collections::keys) },
+    #[cfg(feature = "stdlib-collections")]
+    Builtin { name: "EQUALS", module: Module::Collections, min_arity: 2, max_arity: 2, function: Function::Pure(collections::equals) },
+    #[cfg(feature = "stdlib-collections")]
+    Builtin { name: "COPY", module: Module::Collections, min_arity: 1, max_arity: 1, function: Function::Pure(collections::copy) },
+    #[cfg(feature = "stdlib-eval")]
+    Builtin { name: "EVAL", module: Module::Eval, min_arity: 1, max_arity: 1, function: Function::Intrinsic(Intrinsic::Eval) },
+];
+
+// Like keywords, builtin names are case-insensitive
+pub fn lookup(name: &str) -> Option<&'static Builtin> {
+    BUILTINS.iter().find(|builtin| builtin.name.eq_ignore_ascii_case(name))
+}
+
+pub fn names() -> impl Iterator<Item = &'static str> {
+    BUILTINS.iter().map(|builtin| builtin.name)
+}
+
+impl Module {
+    // net has no builtins yet - they're here so runs can already be
+    // configured for them
+    pub const ALL: [Module; 7] = [Module::Io, Module::Math, Module::String, Module::Net, Module::Time, Module::Collections, Module::Eval];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Module::Io => "io",
+            Module::Math => "math",
+            Module::String => "string",
+            Module::Net => "net",
+            Module::Time => "time",
+            Module::Collections => "collections",
+            Module::Eval => "eval",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Module> {
+        Module::ALL.iter().copied().find(|module| module.name().eq_ignore_ascii_case(name))
+    }
+
+    // Whether this build includes the module's builtins at all
+    pub fn is_compiled(&self) -> bool {
+        match self {
+            Module::Io => cfg!(feature = "stdlib-io"),
+            Module::Math => cfg!(feature = "stdlib-math"),
+            Module::String => cfg!(feature = "stdlib-string"),
+            Module::Net => cfg!(feature = "stdlib-net"),
+            Module::Time => cfg!(feature = "stdlib-time"),
+            Module::Collections => cfg!(feature = "stdlib-collections"),
+            Module::Eval => cfg!(feature = "stdlib-eval"),
+        }
+    }
+}
+
+impl Builtin {
+    pub fn call(&self, arguments: &[Value]) -> Result<Value, String> {
+        self.check_arity(arguments.len())?;
+
+        match self.function {
+            Function::Pure(function) => function(arguments),
+            Function::Intrinsic(_) => Err(format!("{} can only be called by a running program", self.name))
+        }
+    }
+
+    pub fn check_arity(&self, count: usize) -> Result<(), String> {
+        if count < self.min_arity || count > self.max_arity {
+            let expected = if self.min_arity == self.max_arity {
+                self.min_arity.to_string()
+            } else {
+                format!("{} to {}", self.min_arity, self.max_arity)
+            };
+
+            return Err(format!("{} expects {} argument(s) but was given {}", self.name, expected, count));
+        }
+
+        Ok(())
+    }
+
+    pub fn get_intrinsic(&self) -> Option<Intrinsic> {
+        match self.function {
+            Function::Pure(_) => None,
+            Function::Intrinsic(intrinsic) => Some(intrinsic)
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use crate::interpreter::value::Value;
+
+use super::truth;
+
+use std::rc::Rc;
+
+// KEYS(map) is an array of the map's keys, in the order they were first added
+pub(super) fn keys(arguments: &[Value]) -> Result<Value, String> {
+    match &arguments[0] {
+        Value::Map(entries) => Ok(Value::Array(entries.iter().map(|(key, _)| Value::String(Rc::clone(key))).collect())),
+        other => Err(format!("KEYS expects a map - found a {}", other.type_name()))
+    }
+}
+
+// EQUALS(a, b) is the == of a condition as a value - arrays and maps are equal when their
+// elements are, and 1 equals 1d
+pub(super) fn equals(arguments: &[Value]) -> Result<Value, String> {
+    Ok(truth(arguments[0].equals(&arguments[1])))
+}
+
+// COPY(value) is the value with arrays and maps copied all the way down. Assigning one
+// variable to another already copies - changing an element of one never shows in the other -
+// so a copy only matters to an embedder holding on to the value.
+pub(super) fn copy(arguments: &[Value]) -> Result<Value, String> {
+    Ok(arguments[0].deep_copy())
+}
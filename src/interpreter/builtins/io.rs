@@ -0,0 +1,34 @@
+use crate::interpreter::value::Value;
+
+use std::fs;
+use std::rc::Rc;
+
+// READBYTES(path) is the whole file, exactly as it is on disk
+pub(super) fn read_bytes(arguments: &[Value]) -> Result<Value, String> {
+    let path = path_argument("READBYTES", &arguments[0])?;
+    match fs::read(path) {
+        Ok(bytes) => Ok(Value::Bytes(Rc::from(bytes))),
+        Err(err) => Err(format!("Unable to read {} - {}", path, err))
+    }
+}
+
+// WRITEBYTES(path, bytes) replaces the file, giving back how many bytes were written
+pub(super) fn write_bytes(arguments: &[Value]) -> Result<Value, String> {
+    let path = path_argument("WRITEBYTES", &arguments[0])?;
+    let bytes = match &arguments[1] {
+        Value::Bytes(bytes) => bytes,
+        other => return Err(format!("WRITEBYTES expects bytes to write - found a {}", other.type_name()))
+    };
+
+    match fs::write(path, bytes) {
+        Ok(()) => Ok(Value::Number(bytes.len() as f32)),
+        Err(err) => Err(format!("Unable to write {} - {}", path, err))
+    }
+}
+
+fn path_argument<'a>(name: &str, value: &'a Value) -> Result<&'a str, String> {
+    match value {
+        Value::String(path) => Ok(path),
+        other => Err(format!("{} expects a file path - found a {}", name, other.type_name()))
+    }
+}
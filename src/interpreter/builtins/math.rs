@@ -0,0 +1,18 @@
+use crate::interpreter::value::Value;
+
+use super::truth;
+
+pub(super) fn is_nan(arguments: &[Value]) -> Result<Value, String> {
+    match &arguments[0] {
+        // Decimals are always finite
+        Value::Decimal(_) => Ok(truth(false)),
+        value => Ok(truth(value.to_number()?.is_nan()))
+    }
+}
+
+pub(super) fn is_inf(arguments: &[Value]) -> Result<Value, String> {
+    match &arguments[0] {
+        Value::Decimal(_) => Ok(truth(false)),
+        value => Ok(truth(value.to_number()?.is_infinite()))
+    }
+}
@@ -0,0 +1,158 @@
+use crate::interpreter::value::encode_hex;
+use crate::interpreter::value::MAX_DECIMAL_PLACES;
+use crate::interpreter::value::NumberFormat;
+use crate::interpreter::value::Value;
+
+use super::truth;
+
+use std::rc::Rc;
+
+// Padding is allocated up front, and a failed allocation aborts rather than erroring - so
+// keep it to something a script could actually mean
+const MAX_PAD_WIDTH: usize = 1_000_000;
+
+// FORMATNUM(x, decimals) rounds to a fixed number of places, and an optional third
+// argument groups thousands - FORMATNUM(1234.5, 2, ",") gives "1,234.50"
+pub(super) fn format_num(arguments: &[Value]) -> Result<Value, String> {
+    let decimals = arguments[1].to_number()?;
+    if !(0.0..=MAX_DECIMAL_PLACES as f32).contains(&decimals) || decimals.fract() != 0.0 {
+        return Err(format!("FORMATNUM decimals must be a whole number from 0 to {} - found {}", MAX_DECIMAL_PLACES, arguments[1]));
+    }
+
+    let thousands_separator = match arguments.get(2) {
+        Some(Value::String(separator)) => {
+            let mut chars = separator.chars();
+            match (chars.next(), chars.next()) {
+                (Some(separator), None) => Some(separator),
+                _ => return Err(format!("FORMATNUM separator must be a single character - found {:?}", separator))
+            }
+        },
+        Some(other) => return Err(format!("FORMATNUM separator must be a string - found a {}", other.type_name())),
+        None => None
+    };
+
+    let format = NumberFormat {
+        precision: Some(decimals as usize),
+        thousands_separator: thousands_separator,
+    };
+
+    let number = match &arguments[0] {
+        Value::String(_) => Value::Number(arguments[0].to_number()?),
+        value => value.clone()
+    };
+
+    Ok(Value::String(Rc::from(number.format(&format))))
+}
+
+// CHR(code) is the one-character string for a Unicode code point
+pub(super) fn chr(arguments: &[Value]) -> Result<Value, String> {
+    let code = arguments[0].to_number()?;
+    if code < 0.0 || code.fract() != 0.0 {
+        return Err(format!("CHR expects a whole number of 0 or more - found {}", arguments[0]));
+    }
+
+    match char::from_u32(code as u32) {
+        Some(c) => Ok(Value::String(Rc::from(c.to_string()))),
+        None => Err(format!("{} is not a valid character code", arguments[0]))
+    }
+}
+
+// ORD(c) is the Unicode code point of a one-character string
+pub(super) fn ord(arguments: &[Value]) -> Result<Value, String> {
+    let text = match &arguments[0] {
+        Value::String(text) => text,
+        other => return Err(format!("ORD expects a string - found a {}", other.type_name()))
+    };
+
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(Value::Number(c as u32 as f32)),
+        _ => Err(format!("ORD expects a single character - found {:?}", text))
+    }
+}
+
+// PAD(value, width) fills with spaces up to width characters, keeping the value on the
+// left. A third argument of "right" or "center" moves it - PAD(42, 5, "right") gives
+// "   42". Anything already width characters or longer comes back unchanged.
+pub(super) fn pad(arguments: &[Value]) -> Result<Value, String> {
+    let text = arguments[0].to_string();
+    let width = arguments[1].to_number()?;
+    if !(0.0..=MAX_PAD_WIDTH as f32).contains(&width) || width.fract() != 0.0 {
+        return Err(format!("PAD width must be a whole number from 0 to {} - found {}", MAX_PAD_WIDTH, arguments[1]));
+    }
+
+    let alignment = match arguments.get(2) {
+        Some(alignment) => string_argument("PAD", alignment)?,
+        None => "left"
+    };
+
+    let fill = (width as usize).saturating_sub(text.chars().count());
+    let (before, after) = match alignment.to_ascii_lowercase().as_str() {
+        "left" => (0, fill),
+        "right" => (fill, 0),
+        "center" => (fill / 2, fill - fill / 2),
+        _ => return Err(format!("PAD alignment must be \"left\", \"right\" or \"center\" - found {:?}", alignment))
+    };
+
+    Ok(Value::String(Rc::from(format!("{}{}{}", " ".repeat(before), text, " ".repeat(after)))))
+}
+
+// STARTSWITH(text, prefix) is 1 when text begins with prefix, otherwise 0
+pub(super) fn starts_with(arguments: &[Value]) -> Result<Value, String> {
+    let text = string_argument("STARTSWITH", &arguments[0])?;
+    let prefix = string_argument("STARTSWITH", &arguments[1])?;
+    Ok(truth(text.starts_with(prefix)))
+}
+
+// ENDSWITH(text, suffix) is 1 when text ends with suffix, otherwise 0
+pub(super) fn ends_with(arguments: &[Value]) -> Result<Value, String> {
+    let text = string_argument("ENDSWITH", &arguments[0])?;
+    let suffix = string_argument("ENDSWITH", &arguments[1])?;
+    Ok(truth(text.ends_with(suffix)))
+}
+
+// REPLACE(text, from, to) swaps every occurrence of from for to, matching the text
+// exactly rather than as a pattern
+pub(super) fn replace(arguments: &[Value]) -> Result<Value, String> {
+    let text = string_argument("REPLACE", &arguments[0])?;
+    let from = string_argument("REPLACE", &arguments[1])?;
+    let to = string_argument("REPLACE", &arguments[2])?;
+    if from.is_empty() {
+        return Err(String::from("REPLACE can't search for an empty string"));
+    }
+
+    Ok(Value::String(Rc::from(text.replace(from, to))))
+}
+
+fn string_argument<'a>(name: &str, value: &'a Value) -> Result<&'a str, String> {
+    match value {
+        Value::String(text) => Ok(text),
+        other => Err(format!("{} expects a string - found a {}", name, other.type_name()))
+    }
+}
+
+// HEX(bytes) is two lowercase hex digits per byte - UNHEX turns them back
+pub(super) fn hex(arguments: &[Value]) -> Result<Value, String> {
+    match &arguments[0] {
+        Value::Bytes(bytes) => Ok(Value::String(Rc::from(encode_hex(bytes)))),
+        other => Err(format!("HEX expects bytes - found a {}", other.type_name()))
+    }
+}
+
+// UNHEX("48690a") gives the three bytes those digits spell out, in either case
+pub(super) fn unhex(arguments: &[Value]) -> Result<Value, String> {
+    let text = string_argument("UNHEX", &arguments[0])?;
+    if text.len() % 2 != 0 {
+        return Err(format!("UNHEX expects two digits per byte - found {} digits", text.len()));
+    }
+
+    let bytes: Result<Vec<u8>, String> = (0..text.len())
+        .step_by(2)
+        .map(|i| {
+            let pair = text.get(i..i + 2).unwrap_or("");
+            u8::from_str_radix(pair, 16).map_err(|_| format!("UNHEX found {:?}, which isn't a hex byte", pair))
+        })
+        .collect();
+
+    Ok(Value::Bytes(Rc::from(bytes?)))
+}
@@ -0,0 +1,47 @@
+use crate::interpreter::date::DateTime;
+use crate::interpreter::value::Value;
+
+use std::rc::Rc;
+
+// DATE(year, month, day) is midnight on that day - an hour, minute and second can follow
+pub(super) fn date(arguments: &[Value]) -> Result<Value, String> {
+    let mut parts = [0; 6];
+    for (part, argument) in parts.iter_mut().zip(arguments) {
+        let number = argument.to_number()?;
+        if number.fract() != 0.0 {
+            return Err(format!("DATE expects whole numbers - found {}", argument));
+        }
+        *part = number as i64;
+    }
+
+    let [year, month, day, hour, minute, second] = parts;
+    match DateTime::new(year, month, day, hour, minute, second) {
+        Some(date) => Ok(Value::DateTime(date)),
+        None => Err(format!("There is no date {:04}-{:02}-{:02} {:02}:{:02}:{:02}", year, month, day, hour, minute, second))
+    }
+}
+
+// DIFFDAYS(from, to) is how many days to is after from, with any part day as a fraction
+pub(super) fn diff_days(arguments: &[Value]) -> Result<Value, String> {
+    let from = date_argument("DIFFDAYS", &arguments[0])?;
+    let to = date_argument("DIFFDAYS", &arguments[1])?;
+    Ok(Value::Number(from.days_until(&to) as f32))
+}
+
+// FORMATDATE(date, "%d/%m/%Y") lays a date out as text
+pub(super) fn format_date(arguments: &[Value]) -> Result<Value, String> {
+    let date = date_argument("FORMATDATE", &arguments[0])?;
+    let pattern = match &arguments[1] {
+        Value::String(pattern) => pattern,
+        other => return Err(format!("FORMATDATE pattern must be a string - found a {}", other.type_name()))
+    };
+
+    Ok(Value::String(Rc::from(date.format(pattern)?)))
+}
+
+fn date_argument(name: &str, value: &Value) -> Result<DateTime, String> {
+    match value {
+        Value::DateTime(date) => Ok(*date),
+        other => Err(format!("{} expects a date - found a {}", name, other.type_name()))
+    }
+}
@@ -0,0 +1,90 @@
+use super::ast;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+
+#[derive(Default)]
+pub struct Coverage {
+    // Keyed by file, then line, so a multi-file run keeps each file's lines apart
+    hits: BTreeMap<(usize, usize), usize>,
+}
+
+impl Coverage {
+    pub fn new() -> Coverage {
+        Coverage { hits: BTreeMap::new() }
+    }
+
+    pub fn register_abstract_syntax_tree(&mut self, ast: &ast::AbstractSyntaxTree) {
+        self.register_block(&ast.block);
+    }
+
+    pub fn record(&mut self, file: usize, line: usize) {
+        if line > 0 {
+            *self.hits.entry((file, line)).or_insert(0) += 1;
+        }
+    }
+
+    // One record per source file, in the order the files were given
+    pub fn to_lcov(&self, source_files: &[String]) -> String {
+        let mut output = String::new();
+
+        for (file, source_file) in source_files.iter().enumerate() {
+            let hits: Vec<(usize, usize)> = self.hits.range((file, 0)..=(file, usize::MAX))
+                .map(|((_, line), count)| (*line, *count))
+                .collect();
+
+            output.push_str("TN:\n");
+            output.push_str(&format!("SF:{}\n", source_file));
+
+            for (line, count) in &hits {
+                output.push_str(&format!("DA:{},{}\n", line, count));
+            }
+
+            let lines_hit = hits.iter().filter(|(_, count)| *count > 0).count();
+            output.push_str(&format!("LF:{}\n", hits.len()));
+            output.push_str(&format!("LH:{}\n", lines_hit));
+            output.push_str("end_of_record\n");
+        }
+
+        output
+    }
+
+    pub fn write_lcov(&self, source_files: &[String], path: &str) -> io::Result<()> {
+        fs::write(path, self.to_lcov(source_files))
+    }
+
+    fn register_block(&mut self, block: &ast::Block) {
+        // Every statement starts out unexecuted so lcov can report what never ran
+        for (file, line, statement) in block.iter_with_files() {
+            if line > 0 {
+                self.hits.entry((file, line)).or_insert(0);
+            }
+
+            self.register_statement(statement);
+        }
+    }
+
+    fn register_statement(&mut self, statement: &ast::Statement) {
+        match statement {
+            ast::Statement::If(if_statement) => self.register_if(if_statement),
+            ast::Statement::While(_, block) => self.register_block(block),
+            ast::Statement::For(for_loop) => self.register_block(&for_loop.body),
+            ast::Statement::Labeled(_, statement) => self.register_statement(statement),
+            ast::Statement::FunctionDef(function) => self.register_block(&function.body),
+            _ => {}
+        }
+    }
+
+    fn register_if(&mut self, if_statement: &ast::IfStatement) {
+        match if_statement {
+            ast::IfStatement::If(_, block, other) | ast::IfStatement::ElseIf(_, block, other) => {
+                self.register_block(block);
+                if let Some(other) = other {
+                    self.register_if(other);
+                }
+            },
+            ast::IfStatement::Else(block) => self.register_block(block)
+        }
+    }
+}
@@ -0,0 +1,185 @@
+// A concrete syntax tree keeps everything the syntax tree throws away - the exact text of
+// every token and the whitespace between them - so tools that rewrite source can put it
+// back together byte for byte. Whitespace is the only trivia the language has; it belongs
+// to the token after it, and whatever trails the last statement belongs to the EOF token.
+
+use super::ast::AbstractSyntaxTree;
+use super::error::ParseError;
+use super::error::Span;
+use super::lexer;
+use super::lexer::Lexer;
+use super::parser::Parser;
+use super::token::Token;
+use super::token::TokenType;
+
+use std::fmt;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SyntaxKind {
+    Program,
+    Statement,
+    MacroDefinition,
+    OperatorDefinition,
+    // A macro call, standing in for the statements it expanded to
+    MacroUse,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxToken {
+    token_type: TokenType,
+    // Whitespace between the previous token and this one
+    leading: String,
+    // As written - keywords keep their case and strings their quotes
+    text: String,
+    span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxNode {
+    kind: SyntaxKind,
+    children: Vec<SyntaxElement>,
+}
+
+// Parses the source into its syntax tree along with the concrete tree, whose text is the
+// source exactly
+pub fn parse_lossless(source: &str) -> Result<(AbstractSyntaxTree, SyntaxNode), ParseError> {
+    let mut lexer = Lexer::new(source.chars().peekable());
+    let mut parser = Parser::new(&mut lexer);
+    parser.record_syntax();
+    let ast = parser.parse()?;
+
+    // A declared operator can't be written before its declaration without failing the parse,
+    // so lexing the whole source with every one of them gives the parser's tokens
+    let tokens = lexer::lex_all_with_operators(source, &parser.get_operator_symbols())?;
+
+    Ok((ast, build_tree(source, tokens, parser.take_syntax())))
+}
+
+impl SyntaxToken {
+    pub fn get_token_type(&self) -> &TokenType {
+        &self.token_type
+    }
+
+    pub fn get_leading(&self) -> &str {
+        &self.leading
+    }
+
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn get_span(&self) -> Span {
+        self.span
+    }
+
+    // Rewriting a token keeps the whitespace around it
+    pub fn set_text(&mut self, text: &str) {
+        self.text = String::from(text);
+    }
+}
+
+impl SyntaxNode {
+    fn new(kind: SyntaxKind) -> SyntaxNode {
+        SyntaxNode {
+            kind: kind,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn get_kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    pub fn get_children(&self) -> &Vec<SyntaxElement> {
+        &self.children
+    }
+
+    pub fn get_mut_children(&mut self) -> &mut Vec<SyntaxElement> {
+        &mut self.children
+    }
+
+    // Every token under this node, in source order
+    pub fn tokens(&self) -> Vec<&SyntaxToken> {
+        let mut tokens = Vec::new();
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => tokens.append(&mut node.tokens()),
+                SyntaxElement::Token(token) => tokens.push(token)
+            }
+        }
+        tokens
+    }
+
+    pub fn tokens_mut(&mut self) -> Vec<&mut SyntaxToken> {
+        let mut tokens = Vec::new();
+        for child in self.children.iter_mut() {
+            match child {
+                SyntaxElement::Node(node) => tokens.append(&mut node.tokens_mut()),
+                SyntaxElement::Token(token) => tokens.push(token)
+            }
+        }
+        tokens
+    }
+}
+
+// The node's source, whitespace before its first token included
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for token in self.tokens() {
+            write!(f, "{}{}", token.leading, token.text)?;
+        }
+        Ok(())
+    }
+}
+
+// Hangs the tokens under the nodes the parser recorded. A node runs from its first token
+// up to where the token after it starts, so ranges either nest or don't touch.
+fn build_tree(source: &str, tokens: Vec<Token>, mut nodes: Vec<(SyntaxKind, Range<usize>)>) -> SyntaxNode {
+    nodes.sort_by_key(|(_, range)| (range.start, std::cmp::Reverse(range.end)));
+    let mut nodes = nodes.into_iter().peekable();
+
+    let mut stack = vec![(SyntaxNode::new(SyntaxKind::Program), source.len() + 1)];
+    let mut previous_end = 0;
+
+    for (index, token) in tokens.iter().enumerate() {
+        let start = token.get_span().offset;
+        let next_start = tokens.get(index + 1).map(|next| next.get_span().offset).unwrap_or(source.len());
+        // Tokens never end in whitespace, so what follows one up to the next token is trivia
+        let end = start + source[start..next_start].trim_end().len();
+
+        while stack.len() > 1 && stack[stack.len() - 1].1 <= start {
+            close_node(&mut stack);
+        }
+
+        while let Some((kind, range)) = nodes.next_if(|(_, range)| range.start == start) {
+            stack.push((SyntaxNode::new(kind), range.end));
+        }
+
+        let syntax_token = SyntaxToken {
+            token_type: token.get_token_type().clone(),
+            leading: String::from(&source[previous_end..start]),
+            text: String::from(&source[start..end]),
+            span: token.get_span(),
+        };
+        stack.last_mut().unwrap().0.children.push(SyntaxElement::Token(syntax_token));
+        previous_end = end;
+    }
+
+    while stack.len() > 1 {
+        close_node(&mut stack);
+    }
+
+    stack.pop().unwrap().0
+}
+
+fn close_node(stack: &mut Vec<(SyntaxNode, usize)>) {
+    let (node, _) = stack.pop().unwrap();
+    stack.last_mut().unwrap().0.children.push(SyntaxElement::Node(node));
+}
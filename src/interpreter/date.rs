@@ -0,0 +1,106 @@
+use std::fmt;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+// A moment on the calendar, kept as seconds from 1970-01-01 00:00:00. There are no time
+// zones - a date means what it says wherever the program runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateTime {
+    seconds: i64,
+}
+
+impl DateTime {
+    // None when there is no such date or time, like February 30th or 24:00
+    pub fn new(year: i64, month: i64, day: i64, hour: i64, minute: i64, second: i64) -> Option<DateTime> {
+        if !(1..=9999).contains(&year) || !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return None;
+        }
+
+        if !(0..24).contains(&hour) || !(0..60).contains(&minute) || !(0..60).contains(&second) {
+            return None;
+        }
+
+        Some(DateTime {
+            seconds: days_from_civil(year, month, day) * SECONDS_PER_DAY + hour * 3600 + minute * 60 + second,
+        })
+    }
+
+    // Fractional when the times of day differ, negative when other comes first
+    pub fn days_until(&self, other: &DateTime) -> f64 {
+        (other.seconds - self.seconds) as f64 / SECONDS_PER_DAY as f64
+    }
+
+    // strftime-style - %Y, %m, %d, %H, %M and %S, with %% for a literal %
+    pub fn format(&self, pattern: &str) -> Result<String, String> {
+        let (year, month, day) = civil_from_days(self.seconds.div_euclid(SECONDS_PER_DAY));
+        let time = self.seconds.rem_euclid(SECONDS_PER_DAY);
+
+        let mut output = String::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                output.push(c);
+                continue;
+            }
+
+            match chars.next() {
+                Some('Y') => output.push_str(&format!("{:04}", year)),
+                Some('m') => output.push_str(&format!("{:02}", month)),
+                Some('d') => output.push_str(&format!("{:02}", day)),
+                Some('H') => output.push_str(&format!("{:02}", time / 3600)),
+                Some('M') => output.push_str(&format!("{:02}", time % 3600 / 60)),
+                Some('S') => output.push_str(&format!("{:02}", time % 60)),
+                Some('%') => output.push('%'),
+                Some(other) => return Err(format!("Unknown date format code %{}", other)),
+                None => return Err(String::from("Date format ends with a lone %"))
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+// 2024-03-01, with the time only when there is one - 2024-03-01 13:45:00
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pattern = if self.seconds.rem_euclid(SECONDS_PER_DAY) == 0 { "%Y-%m-%d" } else { "%Y-%m-%d %H:%M:%S" };
+        write!(f, "{}", self.format(pattern).expect("The display patterns are always valid"))
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31
+    }
+}
+
+// Days since 1970-01-01 in the proleptic Gregorian calendar, counting years from March so
+// the leap day falls at the end
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 { month_from_march + 3 } else { month_from_march - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
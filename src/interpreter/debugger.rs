@@ -0,0 +1,160 @@
+use super::ast;
+use super::error::RuntimeError;
+use super::error::RuntimeErrorKind;
+use super::intr::Interpreter;
+use super::postmortem;
+use super::value::Value;
+
+use std::collections::HashSet;
+use std::io;
+use std::io::Write;
+
+pub struct Debugger {
+    stepping: bool,
+    watches: HashSet<String>,
+}
+
+impl Default for Debugger {
+    fn default() -> Debugger {
+        Debugger::new()
+    }
+}
+
+impl Debugger {
+    pub fn new() -> Debugger {
+        // Start paused so watches can be set before anything runs
+        Debugger {
+            stepping: true,
+            watches: HashSet::new(),
+        }
+    }
+
+    pub fn watch(&mut self, symbol: &str) {
+        self.watches.insert(String::from(symbol));
+    }
+
+    // Each of these fails with a Stopped error when the user quits at the prompt, which ends
+    // the run the way any error would
+    pub fn before_statement(&mut self, statement: &ast::Statement, interpreter: &mut Interpreter) -> Result<(), RuntimeError> {
+        if self.stepping {
+            println!("-> {}", describe_statement(statement));
+            return self.prompt(interpreter);
+        }
+
+        Ok(())
+    }
+
+    pub fn on_breakpoint(&mut self, interpreter: &mut Interpreter) -> Result<(), RuntimeError> {
+        println!("Breakpoint reached");
+        self.prompt(interpreter)
+    }
+
+    pub fn on_assignment(&mut self, ident: &str, previous: Option<&Value>, current: &Value, interpreter: &mut Interpreter) -> Result<(), RuntimeError> {
+        if !self.watches.contains(ident) {
+            return Ok(());
+        }
+
+        // Only pause when the value actually changed
+        if previous == Some(current) {
+            return Ok(());
+        }
+
+        match previous {
+            Some(previous) => println!("Watchpoint {}: {} -> {}", ident, previous, current),
+            None => println!("Watchpoint {}: set to {}", ident, current),
+        }
+
+        self.prompt(interpreter)
+    }
+
+    // Anything that isn't a command is evaluated as an expression in the paused scope
+    fn prompt(&mut self, interpreter: &mut Interpreter) -> Result<(), RuntimeError> {
+        loop {
+            print!("(debug) ");
+            let _ = io::stdout().flush();
+
+            let mut line = String::new();
+            match io::stdin().read_line(&mut line) {
+                Ok(0) | Err(_) => {
+                    // Input closed, nothing more to ask - run to completion
+                    self.stepping = false;
+                    return Ok(());
+                },
+                Ok(_) => {}
+            }
+
+            let mut parts = line.split_whitespace();
+            let scope = interpreter.get_current_scope();
+            match parts.next() {
+                Some("s") | Some("step") => {
+                    self.stepping = true;
+                    return Ok(());
+                },
+                Some("c") | Some("continue") => {
+                    self.stepping = false;
+                    return Ok(());
+                },
+                Some("w") | Some("watch") => match parts.next() {
+                    Some(symbol) => {
+                        self.watch(symbol);
+                        println!("Watching {}", symbol);
+                    },
+                    None => println!("Usage: watch <variable>")
+                },
+                Some("unwatch") => match parts.next() {
+                    Some(symbol) => {
+                        self.watches.remove(symbol);
+                        println!("No longer watching {}", symbol);
+                    },
+                    None => println!("Usage: unwatch <variable>")
+                },
+                Some("p") | Some("print") => match line.trim().split_once(char::is_whitespace) {
+                    Some((_, source)) => postmortem::print_evaluated(interpreter, source.trim()),
+                    None => println!("Usage: print <expression>")
+                },
+                Some("vars") => {
+                    for (name, value) in scope {
+                        println!("{} = {}", name, value);
+                    }
+                },
+                Some("q") | Some("quit") => return Err(RuntimeError::with_kind(RuntimeErrorKind::Stopped, String::from("Stopped from the debugger"))),
+                Some("h") | Some("help") => {
+                    println!("step (s)             run the next statement and pause");
+                    println!("continue (c)         run until a watchpoint is hit");
+                    println!("watch (w) <var>      pause whenever <var> is assigned a new value");
+                    println!("unwatch <var>        stop watching <var>");
+                    println!("print (p) <expr>     show the value of <expr>, e.g. p n * 2");
+                    println!("vars                 show all assigned variables");
+                    println!("quit (q)             stop the program");
+                    println!("<expr>               anything else is evaluated as an expression");
+                },
+                Some(_) => postmortem::print_evaluated(interpreter, line.trim()),
+                None => {}
+            }
+        }
+    }
+}
+
+fn describe_statement(statement: &ast::Statement) -> String {
+    match statement {
+        ast::Statement::Print(_) => String::from("print"),
+        ast::Statement::EPrint(_) => String::from("eprint"),
+        ast::Statement::Let(ident, _) => format!("let {}", ident.symbol),
+        ast::Statement::Assignment(ast::LValue::Ident(ident), _) => format!("{} =", ident.symbol),
+        ast::Statement::Assignment(target, _) => format!("{}[...] =", target.get_ident().symbol),
+        ast::Statement::If(_) => String::from("if"),
+        ast::Statement::While(_, _) => String::from("while"),
+        ast::Statement::For(for_loop) => format!("for {}", for_loop.variable.symbol),
+        ast::Statement::Labeled(label, statement) => format!("{}: {}", label.symbol, describe_statement(statement)),
+        ast::Statement::Break(_) => String::from("break"),
+        ast::Statement::Continue(_) => String::from("continue"),
+        ast::Statement::Breakpoint => String::from("breakpoint"),
+        ast::Statement::Inspect(_) => String::from("inspect"),
+        ast::Statement::Host(host) => host.keyword.to_lowercase(),
+        ast::Statement::FunctionDef(function) => format!("function {}", function.name.symbol),
+        ast::Statement::Return(_) => String::from("return"),
+        ast::Statement::Call(call) => format!("{}()", call.function.symbol),
+        ast::Statement::Global(_) => String::from("global"),
+        ast::Statement::Local(_) => String::from("local"),
+    }
+}
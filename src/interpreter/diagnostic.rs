@@ -0,0 +1,124 @@
+use super::error::LexErrorKind;
+use super::error::ParseError;
+use super::error::ParseErrorKind;
+use super::error::RuntimeError;
+use super::error::RuntimeErrorKind;
+use super::warning::Warning;
+use super::warning::WarningKind;
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DiagnosticSpan {
+    pub line: usize,
+    // 0 when only the line is known, as for runtime errors
+    pub column: usize,
+}
+
+// Any problem found in a program, in one shape for tools to consume. The code names the
+// kind of problem and stays the same when the wording of the message changes.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+    pub file: Option<String>,
+    pub span: Option<DiagnosticSpan>,
+    pub suggestions: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, code: &'static str, message: String) -> Diagnostic {
+        Diagnostic {
+            severity: severity,
+            code: code,
+            message: message,
+            file: None,
+            span: None,
+            suggestions: Vec::new(),
+        }
+    }
+
+    pub fn from_parse_error(err: &ParseError) -> Diagnostic {
+        let mut diagnostic = Diagnostic::new(Severity::Error, parse_code(err.get_kind()), String::from(err.get_message()));
+        diagnostic.span = span(err.get_span().line, err.get_span().column);
+        diagnostic
+    }
+
+    pub fn from_runtime_error(err: &RuntimeError) -> Diagnostic {
+        let mut diagnostic = Diagnostic::new(Severity::Error, runtime_code(err.get_kind()), String::from(err.get_message()));
+        diagnostic.span = span(err.get_line(), 0);
+        if err.get_kind() == RuntimeErrorKind::UndefinedVariable {
+            diagnostic.suggestions.push(String::from("Declare the variable with LET before using it"));
+        }
+        diagnostic
+    }
+
+    pub fn from_warning(warning: &Warning) -> Diagnostic {
+        let mut diagnostic = Diagnostic::new(Severity::Warning, warning.get_kind().name(), String::from(warning.get_message()));
+        diagnostic.span = span(warning.get_line(), warning.get_span().column);
+        if warning.get_kind() == WarningKind::AssignmentInCondition {
+            diagnostic.suggestions.push(String::from("Use == to compare"));
+        }
+        diagnostic
+    }
+
+    pub fn in_file(mut self, file: &str) -> Diagnostic {
+        self.file = Some(String::from(file));
+        self
+    }
+
+    // A single line, so a stream of diagnostics can be read one per line
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Diagnostics always serialize")
+    }
+}
+
+fn span(line: usize, column: usize) -> Option<DiagnosticSpan> {
+    match line {
+        0 => None,
+        _ => Some(DiagnosticSpan { line: line, column: column })
+    }
+}
+
+fn parse_code(kind: ParseErrorKind) -> &'static str {
+    match kind {
+        ParseErrorKind::Lex(LexErrorKind::UnexpectedCharacter) => "unexpected-character",
+        ParseErrorKind::Lex(LexErrorKind::UnclosedString) => "unclosed-string",
+        ParseErrorKind::Lex(LexErrorKind::InvalidNumber) => "invalid-number",
+        ParseErrorKind::UnexpectedToken => "unexpected-token",
+        ParseErrorKind::UnexpectedEnd => "unexpected-end",
+        ParseErrorKind::InvalidStatement => "invalid-statement",
+        ParseErrorKind::Directive => "invalid-directive",
+        ParseErrorKind::TooDeep => "nested-too-deep",
+        ParseErrorKind::TooLong => "too-many-tokens",
+    }
+}
+
+fn runtime_code(kind: RuntimeErrorKind) -> &'static str {
+    match kind {
+        RuntimeErrorKind::Failed => "runtime-error",
+        RuntimeErrorKind::TimedOut => "timed-out",
+        RuntimeErrorKind::BudgetExceeded => "budget-exceeded",
+        RuntimeErrorKind::Syntax => "syntax-error",
+        RuntimeErrorKind::Type => "type-error",
+        RuntimeErrorKind::UndefinedVariable => "undefined-variable",
+        RuntimeErrorKind::UnknownFunction => "unknown-function",
+        RuntimeErrorKind::ModuleDisabled => "module-disabled",
+        RuntimeErrorKind::Host => "host-statement",
+        RuntimeErrorKind::InvalidArgument => "invalid-argument",
+        RuntimeErrorKind::IndexOutOfRange => "index-out-of-range",
+        RuntimeErrorKind::KeyNotFound => "key-not-found",
+        RuntimeErrorKind::DivisionByZero => "division-by-zero",
+        RuntimeErrorKind::Overflow => "overflow",
+        RuntimeErrorKind::Output => "output-failed",
+        RuntimeErrorKind::Stopped => "stopped",
+    }
+}
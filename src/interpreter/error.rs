@@ -0,0 +1,303 @@
+use std::error::Error;
+use std::fmt;
+
+// Where in the source something happened - columns count characters from 1, and a
+// line of 0 means the position isn't known
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    // Bytes from the start of the source, for tools that slice the text - only set by the lexer
+    pub offset: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LexErrorKind {
+    UnexpectedCharacter,
+    UnclosedString,
+    InvalidNumber,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexError {
+    kind: LexErrorKind,
+    message: String,
+    span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParseErrorKind {
+    // The source couldn't be split into tokens - the LexError is the error's source()
+    Lex(LexErrorKind),
+    UnexpectedToken,
+    // The source stopped part way through a statement or expression - more input could fix it
+    UnexpectedEnd,
+    InvalidStatement,
+    // A preprocessor line like #IF or #DEFINE is malformed
+    Directive,
+    // Blocks or an expression nested deeper than the parser and interpreter can recurse
+    TooDeep,
+    // More tokens than ParseLimits allows
+    TooLong,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    message: String,
+    span: Span,
+    source: Option<LexError>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub name: String,
+    pub line: usize,
+}
+
+#[derive(Debug)]
+pub struct RuntimeError {
+    kind: RuntimeErrorKind,
+    message: String,
+    span: Span,
+    // Which file of a multi-file program the line is in - 0 for a program from one file
+    file: usize,
+    trace: Vec<Frame>,
+    source: Option<Box<dyn Error + Send + Sync>>,
+}
+
+// Lets embedders tell why a script stopped without matching on messages
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RuntimeErrorKind {
+    Failed,
+    TimedOut,
+    BudgetExceeded,
+    Syntax,
+    Type,
+    UndefinedVariable,
+    UnknownFunction,
+    // The builtin exists but its module wasn't enabled for this run
+    ModuleDisabled,
+    // A statement added by the embedder has no handler, or its handler failed
+    Host,
+    InvalidArgument,
+    IndexOutOfRange,
+    // A map was read at a key it doesn't have
+    KeyNotFound,
+    DivisionByZero,
+    Overflow,
+    Output,
+    // Quit from the debugger - the program was stopped, it didn't fail
+    Stopped,
+}
+
+impl Span {
+    pub fn new(line: usize, column: usize) -> Span {
+        Span {
+            line: line,
+            column: column,
+            offset: 0,
+        }
+    }
+}
+
+impl LexError {
+    pub fn new(kind: LexErrorKind, message: String, span: Span) -> LexError {
+        LexError {
+            kind: kind,
+            message: message,
+            span: span,
+        }
+    }
+
+    pub fn get_kind(&self) -> LexErrorKind {
+        self.kind
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn get_span(&self) -> Span {
+        self.span
+    }
+
+    pub fn get_line(&self) -> usize {
+        self.span.line
+    }
+}
+
+impl ParseError {
+    pub fn new(kind: ParseErrorKind, message: String, span: Span) -> ParseError {
+        ParseError {
+            kind: kind,
+            message: message,
+            span: span,
+            source: None,
+        }
+    }
+
+    pub fn get_kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn get_span(&self) -> Span {
+        self.span
+    }
+
+    pub fn get_line(&self) -> usize {
+        self.span.line
+    }
+}
+
+// The parser pulls tokens as it goes, so lexing problems surface as parse failures
+impl From<LexError> for ParseError {
+    fn from(err: LexError) -> ParseError {
+        ParseError {
+            kind: ParseErrorKind::Lex(err.kind),
+            message: err.message.clone(),
+            span: err.span,
+            source: Some(err),
+        }
+    }
+}
+
+impl Frame {
+    pub fn new(name: String) -> Frame {
+        Frame { name: name, line: 0 }
+    }
+}
+
+impl RuntimeError {
+    pub fn new(message: String) -> RuntimeError {
+        RuntimeError::with_kind(RuntimeErrorKind::Failed, message)
+    }
+
+    pub fn with_kind(kind: RuntimeErrorKind, message: String) -> RuntimeError {
+        RuntimeError {
+            kind: kind,
+            message: message,
+            span: Span::default(),
+            file: 0,
+            trace: Vec::new(),
+            source: None,
+        }
+    }
+
+    // Keeps the underlying error reachable through source()
+    pub fn with_source<E: Error + Send + Sync + 'static>(kind: RuntimeErrorKind, message: String, source: E) -> RuntimeError {
+        let mut err = RuntimeError::with_kind(kind, message);
+        err.source = Some(Box::new(source));
+        err
+    }
+
+    pub fn get_kind(&self) -> RuntimeErrorKind {
+        self.kind
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+
+    // Position of the statement that failed - only the line is tracked at runtime
+    pub fn get_span(&self) -> Span {
+        self.span
+    }
+
+    pub fn get_line(&self) -> usize {
+        self.span.line
+    }
+
+    pub fn set_line(&mut self, line: usize) {
+        self.span.line = line;
+    }
+
+    pub fn get_file(&self) -> usize {
+        self.file
+    }
+
+    pub fn set_file(&mut self, file: usize) {
+        self.file = file;
+    }
+
+    pub fn get_trace(&self) -> &Vec<Frame> {
+        &self.trace
+    }
+
+    pub fn set_trace(&mut self, trace: Vec<Frame>) {
+        self.trace = trace;
+    }
+}
+
+fn write_position(f: &mut fmt::Formatter, span: &Span) -> fmt::Result {
+    match (span.line, span.column) {
+        (0, _) => Ok(()),
+        (line, 0) => write!(f, " at line {}", line),
+        (line, column) => write!(f, " at line {}, column {}", line, column)
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Syntax error")?;
+        write_position(f, &self.span)?;
+        write!(f, " - {}", self.message)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Syntax error")?;
+        write_position(f, &self.span)?;
+        write!(f, " - {}", self.message)
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Runtime error")?;
+        write_position(f, &self.span)?;
+        write!(f, " - {}", self.message)?;
+
+        // Innermost frame first, the way the error unwound. Deep recursion repeats the same
+        // frame over and over, so a run of them is shown once.
+        let mut frames = self.trace.iter().rev().peekable();
+        while let Some(frame) = frames.next() {
+            if frame.line > 0 {
+                write!(f, "\n  at {} (line {})", frame.name, frame.line)?;
+            } else {
+                write!(f, "\n  at {}", frame.name)?;
+            }
+
+            let mut repeats = 0;
+            while frames.next_if(|next| next.name == frame.name && next.line == frame.line).is_some() {
+                repeats += 1;
+            }
+
+            if repeats > 0 {
+                write!(f, "\n  ... the same {} more time(s)", repeats)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for LexError {}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source.as_ref().map(|err| err as &(dyn Error + 'static))
+    }
+}
+
+impl Error for RuntimeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source.as_ref().map(|err| err.as_ref() as &(dyn Error + 'static))
+    }
+}
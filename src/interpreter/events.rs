@@ -0,0 +1,28 @@
+use std::sync::mpsc::Sender;
+
+// What a running program is doing, for front ends that show execution as it happens
+// rather than reading printed text. Values arrive already formatted, so events can be
+// sent to another thread.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    StatementEntered { line: usize },
+    OutputProduced { stream: Stream, text: String },
+    VariableChanged { name: String, value: String, type_name: &'static str },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stream {
+    // PRINT and INSPECT
+    Output,
+    // EPRINT
+    ErrorOutput,
+}
+
+pub type EventHandler<'a> = Box<dyn FnMut(Event) + 'a>;
+
+// Forwards every event down a channel - a front end that has gone away just stops receiving
+pub fn channel_handler<'a>(sender: Sender<Event>) -> EventHandler<'a> {
+    Box::new(move |event| {
+        let _ = sender.send(event);
+    })
+}
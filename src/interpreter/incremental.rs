@@ -0,0 +1,210 @@
+use super::ast::AbstractSyntaxTree;
+use super::ast::Block;
+use super::ast::Statement;
+use super::error::ParseError;
+use super::error::Span;
+use super::lexer::Lexer;
+use super::parser::Parser;
+
+use std::ops::Range;
+
+// A change to the source the way editors report one - the bytes in range give way to the text
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextEdit {
+    range: Range<usize>,
+    text: String,
+}
+
+// A top-level statement and the bytes it came from - its first token up to where the
+// next statement starts
+#[derive(Debug, Clone)]
+struct Entry {
+    statement: Statement,
+    line: usize,
+    range: Range<usize>,
+}
+
+// Source kept alongside its syntax tree so that an edit only reparses the top-level
+// statements it touches. Parsing restarts at the statement before the edit and stops as
+// soon as a statement ends where an old one began past the edit - from there on the old
+// statements are reused, moved to their new lines. The tree is equal to a full parse of the
+// source, node ids aside - those of reparsed statements are fresh rather than renumbered.
+pub struct Document {
+    source: String,
+    entries: Vec<Entry>,
+    // A macro or operator can be used far from its declaration, so a source declaring any is
+    // always parsed whole
+    whole: bool,
+    // Reparsed statements get fresh ids, so the ones kept from before stay valid
+    next_id: u32,
+}
+
+impl TextEdit {
+    pub fn new(range: Range<usize>, text: &str) -> TextEdit {
+        TextEdit {
+            range: range,
+            text: String::from(text),
+        }
+    }
+
+    pub fn get_range(&self) -> &Range<usize> {
+        &self.range
+    }
+
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl Document {
+    pub fn parse(source: &str) -> Result<Document, ParseError> {
+        let (entries, whole, next_id) = parse_entries(source)?;
+        Ok(Document {
+            source: String::from(source),
+            entries: entries,
+            whole: whole,
+            next_id: next_id,
+        })
+    }
+
+    pub fn get_source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn get_ast(&self) -> AbstractSyntaxTree {
+        let statements = self.entries.iter().map(|entry| entry.statement.clone()).collect();
+        let lines = self.entries.iter().map(|entry| entry.line).collect();
+        AbstractSyntaxTree::new(Block::with_lines(statements, lines))
+    }
+
+    // Applies the edit, returning how many top-level statements had to be parsed again. On
+    // a syntax error the document is left as it was. Panics if the range isn't within the
+    // source on character boundaries, like String::replace_range.
+    pub fn apply(&mut self, edit: &TextEdit) -> Result<usize, ParseError> {
+        let mut source = self.source.clone();
+        source.replace_range(edit.range.clone(), &edit.text);
+
+        if self.whole || self.entries.is_empty() {
+            return self.replace(source);
+        }
+
+        // A statement ending right where the edit starts is reparsed too - the edit may extend it
+        let first = self.entries.iter()
+            .position(|entry| entry.range.end >= edit.range.start)
+            .unwrap_or(self.entries.len() - 1);
+        let start = if first == 0 { 0 } else { self.entries[first].range.start };
+
+        let shift = edit.text.len() as isize - edit.range.len() as isize;
+        let moved = |entry: &Entry| entry.range.start.wrapping_add_signed(shift);
+
+        let mut lexer = Lexer::starting_at(source[start..].chars().peekable(), span_at(&source, start));
+        let mut parser = Parser::new(&mut lexer);
+        parser.set_next_id(self.next_id);
+        parser.begin()?;
+
+        let mut parsed = Vec::new();
+        let mut next = first + 1;
+        let mut reused = None;
+
+        loop {
+            let begin = parser.get_current_token().get_span().offset;
+            let (statement, line) = match parser.parse_next_statement()? {
+                Some(parsed) => parsed,
+                None => break
+            };
+            let end = parser.get_current_token().get_span().offset;
+
+            parsed.push(Entry {
+                statement: statement,
+                line: line,
+                range: begin..end,
+            });
+
+            if parser.defines_syntax() {
+                return self.replace(source);
+            }
+
+            // Only text past the edit is the same as before, so only an old statement
+            // starting there can be picked up again
+            while next < self.entries.len() &&
+                (self.entries[next].range.start < edit.range.end || moved(&self.entries[next]) < end) {
+                next += 1;
+            }
+
+            if next < self.entries.len() && moved(&self.entries[next]) == end {
+                reused = Some(next);
+                break;
+            }
+        }
+
+        let reparsed = parsed.len();
+        self.next_id = parser.get_next_id();
+        let mut tail = match reused {
+            Some(index) => self.entries.split_off(index),
+            None => Vec::new()
+        };
+
+        let lines = count_lines(&edit.text) - count_lines(&self.source[edit.range.clone()]);
+        for entry in tail.iter_mut() {
+            entry.range = moved(entry)..entry.range.end.wrapping_add_signed(shift);
+            entry.line = entry.line.saturating_add_signed(lines);
+            entry.statement.shift_lines(lines);
+        }
+
+        self.entries.truncate(first);
+        self.entries.append(&mut parsed);
+        self.entries.append(&mut tail);
+        self.source = source;
+
+        Ok(reparsed)
+    }
+
+    fn replace(&mut self, source: String) -> Result<usize, ParseError> {
+        let (entries, whole, next_id) = parse_entries(&source)?;
+        let reparsed = entries.len();
+
+        self.source = source;
+        self.entries = entries;
+        self.whole = whole;
+        self.next_id = next_id;
+        Ok(reparsed)
+    }
+}
+
+fn parse_entries(source: &str) -> Result<(Vec<Entry>, bool, u32), ParseError> {
+    let mut lexer = Lexer::new(source.chars().peekable());
+    let mut parser = Parser::new(&mut lexer);
+    parser.begin()?;
+
+    let mut entries = Vec::new();
+    loop {
+        let begin = parser.get_current_token().get_span().offset;
+        let (statement, line) = match parser.parse_next_statement()? {
+            Some(parsed) => parsed,
+            None => break
+        };
+
+        entries.push(Entry {
+            statement: statement,
+            line: line,
+            range: begin..parser.get_current_token().get_span().offset,
+        });
+    }
+
+    Ok((entries, parser.defines_syntax(), parser.get_next_id()))
+}
+
+fn span_at(source: &str, offset: usize) -> Span {
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map(|index| index + 1).unwrap_or(0);
+
+    Span {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+        offset: offset,
+    }
+}
+
+fn count_lines(text: &str) -> isize {
+    text.matches('\n').count() as isize
+}
@@ -1,151 +1,1472 @@
 use super::ast;
+use super::ast::NodeId;
 
+use super::builtins;
+use super::builtins::Intrinsic;
+use super::builtins::Module;
+use super::coverage::Coverage;
+use super::debugger::Debugger;
+use super::error::Frame;
+use super::error::RuntimeError;
+use super::error::RuntimeErrorKind;
+use super::error::Span;
+use super::events::Event;
+use super::events::EventHandler;
+use super::events::Stream;
+use super::lexer::Lexer;
+use super::memory::MemoryStats;
+use super::profile::Profiler;
+use super::parser::ParseLimits;
 use super::parser::Parser;
-use super::symbol::Symbol;
+use super::symbol::Guard;
+use super::symbol::Scope;
 use super::symbol::SymbolTable;
+use super::trace::TraceFormat;
+use super::trace::Tracer;
+use super::value::MAX_DECIMAL_PLACES;
+use super::value::NumberFormat;
+use super::value::StringPool;
+use super::value::Value;
+use super::warning::Warning;
+use super::warning::WarningKind;
+use super::warning::WarningSet;
 
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io;
+use std::io::Write;
+use std::rc::Rc;
+use std::time::Duration;
+use std::time::Instant;
+
+// EVAL("EVAL(s)") with s holding that same text would otherwise recurse until the stack ran out
+const MAX_EVAL_DEPTH: usize = 64;
+
+// Each call goes several Rust frames deep, so unbounded recursion would overflow the stack
+const MAX_CALL_DEPTH: usize = 200;
+
+// Blocks and expressions are run by recursing into them, and the parser's limits on each
+// multiply across calls. This bounds the total - a debug build takes up to about 10KB of stack
+// a level, so this fits within the 2MB a spawned thread gets, calls included since each is a
+// few levels. On a thread with more, raise it with set_max_depth.
+const MAX_DEPTH: usize = 128;
+
+// How a block finished - BREAK and CONTINUE pass up through the blocks until they reach
+// the loop they're for, which is the innermost one unless they name a label
+enum Flow {
+    Normal,
+    Break(Option<String>),
+    Continue(Option<String>),
+    // Passes up through the blocks of a function to the call, with the value returned
+    Return(Option<Value>),
+}
+
+// What a finished run hands back to the embedder
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunResult {
+    exit_code: i32,
+    warnings: Vec<Warning>,
+    stats: RunStats,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RunStats {
+    // Counting every time a loop runs its body again
+    pub statements: u64,
+    // What the statement budget is measured in - statements plus loop iterations
+    pub steps: u64,
+    // Variables the analysis pass found, and how long it took - part of elapsed
+    pub symbols: usize,
+    pub analysis: Duration,
+    pub elapsed: Duration,
+}
+
+impl RunResult {
+    // 0 unless the program ended with a top-level RETURN that gave a code
+    pub fn get_exit_code(&self) -> i32 {
+        self.exit_code
+    }
+
+    // Found while parsing, analysing and running - parse warnings only come with interpret
+    pub fn get_warnings(&self) -> &Vec<Warning> {
+        &self.warnings
+    }
+
+    pub fn get_stats(&self) -> RunStats {
+        self.stats
+    }
+}
+
+// Runs an embedder's statement with its arguments already evaluated
+pub type HostHandler<'a> = Box<dyn FnMut(&ast::HostStatement, &[Value]) -> Result<(), String> + 'a>;
 
 pub struct Interpreter<'a> {
-    parser: &'a mut Parser<'a>,
+    parser: Option<&'a mut Parser<'a>>,
     symbol_table: SymbolTable,
-    global_scope: HashMap<String, String>
+    // Indexed by the slots the symbol table gives each variable - None until it's assigned
+    globals: Vec<Option<Value>>,
+    // The variables of each function call in progress, innermost last
+    locals: Vec<Vec<Option<Value>>>,
+    functions: HashMap<String, Rc<ast::FunctionDef>>,
+    // What each MEMO FUNCTION has given back so far, by the arguments it was called with
+    memos: HashMap<String, HashMap<Vec<Value>, Option<Value>>>,
+    strings: StringPool,
+    debugger: Option<Debugger>,
+    call_stack: Vec<Frame>,
+    coverage: Option<Coverage>,
+    memory_stats: Option<MemoryStats>,
+    profiler: Option<Profiler>,
+    tracer: Option<Tracer<'a>>,
+    output: Box<dyn Write + 'a>,
+    error_output: Box<dyn Write + 'a>,
+    events: Option<EventHandler<'a>>,
+    modules: Vec<Module>,
+    host_handlers: HashMap<String, HostHandler<'a>>,
+    print_format: NumberFormat,
+    print_separator: String,
+    print_terminator: String,
+    timeout: Option<Duration>,
+    deadline: Option<Instant>,
+    statement_budget: Option<u64>,
+    steps: u64,
+    statements: u64,
+    eval_depth: usize,
+    // The value of each literal in the symbol table's constant pool, worked out once per
+    // run. None for a literal that doesn't make a valid value - it fails when evaluated.
+    constants: Vec<Option<Value>>,
+    depth: usize,
+    max_depth: usize,
+    // What EVAL's parser takes on, the same as the program's unless changed
+    parse_limits: ParseLimits,
+    enabled_warnings: WarningSet,
+    // Found during the run so far - handed over in its RunResult, or by take_warnings when
+    // it fails
+    warnings: Vec<Warning>,
+    // Coercion is warned about once per line, however often the line runs
+    coerced_lines: HashSet<usize>,
 }
 
 impl<'a> Interpreter<'a> {
 
     pub fn new(parser: &'a mut Parser<'a>) -> Interpreter<'a> {
+        let mut interpreter = Interpreter::standalone();
+        interpreter.parse_limits = parser.get_limits();
+        interpreter.parser = Some(parser);
+        interpreter
+    }
+
+    // An interpreter with no source attached - programs are handed to run directly
+    pub fn standalone() -> Interpreter<'a> {
         Interpreter {
-            parser: parser,
+            parser: None,
             symbol_table: SymbolTable::new(),
-            global_scope: HashMap::new()
+            globals: Vec::new(),
+            locals: Vec::new(),
+            functions: HashMap::new(),
+            memos: HashMap::new(),
+            strings: StringPool::new(),
+            debugger: None,
+            call_stack: vec![Frame::new(String::from("<main>"))],
+            coverage: None,
+            memory_stats: None,
+            profiler: None,
+            tracer: None,
+            output: Box::new(io::stdout()),
+            error_output: Box::new(io::stderr()),
+            events: None,
+            modules: Module::ALL.to_vec(),
+            host_handlers: HashMap::new(),
+            print_format: NumberFormat::default(),
+            print_separator: String::from(" "),
+            print_terminator: String::from("\n"),
+            timeout: None,
+            deadline: None,
+            statement_budget: None,
+            steps: 0,
+            statements: 0,
+            eval_depth: 0,
+            constants: Vec::new(),
+            depth: 0,
+            max_depth: MAX_DEPTH,
+            parse_limits: ParseLimits::default(),
+            enabled_warnings: WarningSet::default(),
+            warnings: Vec::new(),
+            coerced_lines: HashSet::new(),
+        }
+    }
+
+    // Where PRINT writes to - stdout unless redirected, e.g. to io::sink() when benchmarking
+    pub fn set_output(&mut self, output: Box<dyn Write + 'a>) {
+        self.output = output;
+    }
+
+    // How many blocks and expressions can be running inside one another before the run
+    // stops with a BudgetExceeded error
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    // The limits on the text EVAL parses, which should match those the program was parsed with
+    pub fn set_parse_limits(&mut self, parse_limits: ParseLimits) {
+        self.parse_limits = parse_limits;
+    }
+
+    // Stops each later run with a TimedOut error once it has taken longer than this
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
+
+    // Stops each later run with a BudgetExceeded error after this many steps - every
+    // statement and every loop iteration is one step, so the cut off point is the same on
+    // every machine
+    pub fn set_statement_budget(&mut self, budget: u64) {
+        self.statement_budget = Some(budget);
+    }
+
+    // Lets a plain assignment declare a variable LET never did, the way classic BASIC does
+    pub fn set_implicit_vars(&mut self, implicit_vars: bool) {
+        self.symbol_table.set_implicit_declarations(implicit_vars);
+    }
+
+    // Which warnings to look for while parsing, analysing and running
+    pub fn set_enabled_warnings(&mut self, enabled_warnings: WarningSet) {
+        if let Some(parser) = &mut self.parser {
+            parser.set_enabled_warnings(enabled_warnings.clone());
         }
+        self.symbol_table.set_enabled_warnings(enabled_warnings.clone());
+        self.enabled_warnings = enabled_warnings;
+    }
+
+    // Warnings from a run that failed - one that finishes hands them over in its RunResult
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    // Where EPRINT writes to - stderr unless redirected
+    pub fn set_error_output(&mut self, error_output: Box<dyn Write + 'a>) {
+        self.error_output = error_output;
+    }
+
+    // How PRINT and EPRINT show numbers, e.g. a fixed number of decimal places - no more than
+    // MAX_DECIMAL_PLACES, as with FORMATNUM
+    pub fn set_print_format(&mut self, mut print_format: NumberFormat) {
+        print_format.precision = print_format.precision.map(|precision| precision.min(MAX_DECIMAL_PLACES));
+        self.print_format = print_format;
+    }
+
+    // What goes between the values of a PRINT a, b, c - a space unless changed
+    pub fn set_print_separator(&mut self, separator: &str) {
+        self.print_separator = String::from(separator);
+    }
+
+    // What ends each PRINT - a newline unless changed
+    pub fn set_print_terminator(&mut self, terminator: &str) {
+        self.print_terminator = String::from(terminator);
+    }
+
+    // Reports execution as events instead of printing - output goes to the handler in
+    // place of the output streams
+    pub fn set_event_handler(&mut self, handler: EventHandler<'a>) {
+        self.events = Some(handler);
+    }
+
+    // The builtin modules scripts may call into - all of them unless limited
+    pub fn set_modules(&mut self, modules: &[Module]) {
+        self.modules = modules.to_vec();
+    }
+
+    // Runs the statements a parser plugin registered under this keyword
+    pub fn register_statement_handler(&mut self, keyword: &str, handler: HostHandler<'a>) {
+        self.host_handlers.insert(keyword.to_uppercase(), handler);
+    }
+
+    pub fn enable_debugger(&mut self) {
+        self.debugger = Some(Debugger::new());
+    }
+
+    pub fn enable_coverage(&mut self) {
+        self.coverage = Some(Coverage::new());
+    }
+
+    pub fn get_coverage(&self) -> Option<&Coverage> {
+        self.coverage.as_ref()
+    }
+
+    pub fn enable_memory_stats(&mut self) {
+        self.memory_stats = Some(MemoryStats::new());
+    }
+
+    pub fn enable_profiler(&mut self) {
+        self.profiler = Some(Profiler::new());
     }
 
-    pub fn interpret(&mut self) {
-        let mut ast = self.parser.parse();
-        // println!("{}", ast);
+    // Writes a line to stderr for every statement executed, with the variable it changed
+    pub fn enable_trace(&mut self, format: TraceFormat) {
+        self.tracer = Some(Tracer::new(format, Box::new(io::stderr())));
+    }
+
+    pub fn memory_report(&self) -> Option<String> {
+        let values: Vec<&Value> = self.globals.iter()
+            .chain(self.locals.iter().flatten())
+            .flatten()
+            .collect();
+        self.memory_stats.as_ref().map(|stats| stats.report(&values))
+    }
+
+    pub fn get_profiler(&self) -> Option<&Profiler> {
+        self.profiler.as_ref()
+    }
+
+    pub fn interpret(&mut self) -> Result<RunResult, RuntimeError> {
+        let (ast, warnings) = match &mut self.parser {
+            Some(parser) => match parser.parse() {
+                Ok(ast) => (ast, parser.take_warnings()),
+                Err(err) => return Err(RuntimeError::with_source(RuntimeErrorKind::Syntax, err.to_string(), err))
+            },
+            None => return Err(RuntimeError::new(String::from("No source to interpret - use run with a syntax tree")))
+        };
+
+        let mut result = self.run(&ast)?;
+        result.warnings.splice(0..0, warnings);
+        Ok(result)
+    }
+
+    pub fn run(&mut self, ast: &ast::AbstractSyntaxTree) -> Result<RunResult, RuntimeError> {
+        let started = start_timing();
 
         // Build a symbol table
-        self.symbol_table.process_abstract_syntax_tree(&mut ast);
+        let analysed = self.symbol_table.process_abstract_syntax_tree(ast);
+        self.warnings.append(&mut self.symbol_table.take_warnings());
+        analysed?;
         // symbol_table.output();
+        let analysis = elapsed_since(started);
+
+        let literals = self.symbol_table.get_constants().to_vec();
+        self.constants = literals.iter().map(|literal| self.literal_value(literal).ok()).collect();
+
+        if let Some(coverage) = &mut self.coverage {
+            coverage.register_abstract_syntax_tree(ast);
+        }
+
+        self.define_functions(&ast.block)?;
+
+        // A timeout too long to reach is no deadline at all
+        self.deadline = self.timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+        self.coerced_lines.clear();
+        self.steps = 0;
+        self.statements = 0;
+
+        // The top level is profiled as a call of its own, so time outside functions shows up
+        if let Some(profiler) = &mut self.profiler {
+            profiler.enter("<main>");
+        }
 
         // Process root level code block
-        self.process_block(&ast.block);
+        let result = self.process_block(&ast.block);
+
+        if let Some(profiler) = &mut self.profiler {
+            profiler.exit();
+        }
 
+        // The last statement's trace line is still waiting for a statement after it
+        if let Some(tracer) = &mut self.tracer {
+            if let Err(err) = tracer.flush() {
+                return Err(RuntimeError::with_source(RuntimeErrorKind::Output, format!("Unable to write trace - {}", err), err));
+            }
+        }
+
+        let exit_code = match result? {
+            Flow::Normal => 0,
+            // RETURN outside of a function ends the program, optionally with an exit code
+            Flow::Return(None) => 0,
+            Flow::Return(Some(Value::Number(code))) if code.fract() == 0.0 && code >= i32::MIN as f32 && code <= i32::MAX as f32 => code as i32,
+            Flow::Return(Some(value)) => return Err(RuntimeError::with_kind(RuntimeErrorKind::Type, format!("RETURN outside of a function ends the program - its exit code must be a whole number, not {}", value))),
+            // The parser rejects these, but a syntax tree can come from elsewhere
+            _ => return Err(RuntimeError::new(String::from("BREAK or CONTINUE used outside of a loop it could leave")))
+        };
+
+        Ok(RunResult {
+            exit_code: exit_code,
+            warnings: self.take_warnings(),
+            stats: RunStats {
+                statements: self.statements,
+                steps: self.steps,
+                symbols: self.symbol_table.get_names().len(),
+                analysis: analysis,
+                elapsed: elapsed_since(started),
+            },
+        })
+    }
+
+    pub fn evaluate(&mut self, expression: &ast::Expression) -> Result<Value, RuntimeError> {
+        self.process_expression(expression)
+    }
+
+    // Each assigned variable by name, in name order so anything listing them comes out the
+    // same every run - put together when asked for, since values are kept by slot
+    pub fn get_global_scope(&self) -> BTreeMap<&str, &Value> {
+        self.name_scope(&self.globals)
+    }
+
+    // The variables of the function running now, or the globals outside of one
+    pub fn get_current_scope(&self) -> BTreeMap<&str, &Value> {
+        self.name_scope(self.locals.last().unwrap_or(&self.globals))
+    }
+
+    fn name_scope<'s>(&'s self, scope: &'s [Option<Value>]) -> BTreeMap<&'s str, &'s Value> {
+        scope.iter()
+            .enumerate()
+            .filter_map(|(slot, value)| value.as_ref().map(|value| (self.symbol_table.get_slot_name(slot), value)))
+            .collect()
     }
 
-    fn process_block(&mut self, block: &ast::Block) {
-        for i in 0..block.get_length() {
-            self.process_statement(block.get_statement(i));
+    pub fn get_symbol_table(&self) -> &SymbolTable {
+        &self.symbol_table
+    }
+
+    // Every FUNCTION defined so far, by the name it was defined with
+    pub fn get_function_names(&self) -> Vec<&str> {
+        self.functions.keys().map(|name| name.as_str()).collect()
+    }
+
+    // Functions are defined before anything runs, so a call can come before the definition.
+    // A later run can define a function again - the REPL relies on that.
+    fn define_functions(&mut self, block: &ast::Block) -> Result<(), RuntimeError> {
+        let mut defined = HashSet::new();
+        for (file, line, statement) in block.iter_with_files() {
+            if let ast::Statement::FunctionDef(function) = statement {
+                let name = &function.name.symbol;
+                let problem = if builtins::lookup(name).is_some() {
+                    Some(format!("FUNCTION {} has the name of a builtin", name))
+                } else if !defined.insert(name.clone()) {
+                    Some(format!("FUNCTION {} is defined more than once", name))
+                } else {
+                    None
+                };
+
+                if let Some(problem) = problem {
+                    let mut err = RuntimeError::new(problem);
+                    err.set_line(line);
+                    err.set_file(file);
+                    return Err(err);
+                }
+
+                // A function defined again may give something else for the same arguments
+                self.memos.remove(name);
+                self.functions.insert(name.clone(), Rc::new(function.clone()));
+            }
+        }
+
+        Ok(())
+    }
+
+    // A function's own variables hide the program's, unless it declared the name GLOBAL or LOCAL
+    fn lookup_variable(&self, ident: &ast::Ident) -> Option<&Value> {
+        let slot = self.symbol_table.get_slot(ident)?;
+        match self.symbol_table.get_scope(ident) {
+            Some(Scope::Global) => self.globals.get(slot).and_then(Option::as_ref),
+            Some(Scope::Local) => self.locals.last().and_then(|locals| locals.get(slot)).and_then(Option::as_ref),
+            None => self.lookup_slot(slot)
         }
     }
 
-    fn process_statement(&mut self, statement: &ast::Statement) {
+    fn lookup_slot(&self, slot: usize) -> Option<&Value> {
+        self.locals.last()
+            .and_then(|locals| locals.get(slot))
+            .and_then(Option::as_ref)
+            .or_else(|| self.globals.get(slot).and_then(Option::as_ref))
+    }
+
+    fn process_block(&mut self, block: &ast::Block) -> Result<Flow, RuntimeError> {
+        self.descend()?;
+        let flow = self.process_statements(block);
+        self.depth -= 1;
+        flow
+    }
+
+    // Counts one more level of blocks and expressions being run, failing once past the limit
+    fn descend(&mut self) -> Result<(), RuntimeError> {
+        if self.depth >= self.max_depth {
+            return Err(RuntimeError::with_kind(RuntimeErrorKind::BudgetExceeded, format!("Program nested more than {} deep", self.max_depth)));
+        }
+
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn process_statements(&mut self, block: &ast::Block) -> Result<Flow, RuntimeError> {
+        for (file, line, statement) in block.iter_with_files() {
+            self.set_current_line(line);
+
+            if let Err(mut err) = self.step() {
+                err.set_line(line);
+                err.set_file(file);
+                err.set_trace(self.call_stack.clone());
+                return Err(err);
+            }
+
+            self.statements += 1;
+
+            if let Some(coverage) = &mut self.coverage {
+                coverage.record(file, line);
+            }
+
+            self.emit(Event::StatementEntered { line: line });
+
+            if let Some(tracer) = &mut self.tracer {
+                if let Err(err) = tracer.enter(line, statement) {
+                    return Err(RuntimeError::with_source(RuntimeErrorKind::Output, format!("Unable to write trace - {}", err), err));
+                }
+            }
+
+            match self.process_statement(statement) {
+                Ok(Flow::Normal) => {},
+                Ok(flow) => return Ok(flow),
+                Err(mut err) => {
+                    // Capture the position and stack where the error was raised, before frames unwind
+                    if err.get_trace().is_empty() {
+                        err.set_line(line);
+                        err.set_file(file);
+                        err.set_trace(self.call_stack.clone());
+                    }
+
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(Flow::Normal)
+    }
+
+    fn step(&mut self) -> Result<(), RuntimeError> {
+        self.steps += 1;
+
+        if let Some(budget) = self.statement_budget {
+            if self.steps > budget {
+                return Err(RuntimeError::with_kind(RuntimeErrorKind::BudgetExceeded, format!("Statement budget of {} exceeded", budget)));
+            }
+        }
+
+        match (self.deadline, self.timeout) {
+            (Some(deadline), Some(timeout)) if Instant::now() >= deadline => {
+                Err(RuntimeError::with_kind(RuntimeErrorKind::TimedOut, format!("Timed out after {:?}", timeout)))
+            },
+            _ => Ok(())
+        }
+    }
+
+    fn set_current_line(&mut self, line: usize) {
+        if let Some(frame) = self.call_stack.last_mut() {
+            frame.line = line;
+        }
+    }
+
+    fn process_statement(&mut self, statement: &ast::Statement) -> Result<Flow, RuntimeError> {
+        // Taken out while it has control, so its prompt can evaluate expressions
+        if let Some(mut debugger) = self.debugger.take() {
+            let paused = debugger.before_statement(statement, self);
+            self.debugger = Some(debugger);
+            paused?;
+        }
+
         match statement {
-            ast::Statement::Print(expression) => println!("{}", self.process_expression(&expression)),
-            ast::Statement::Let(ident, expression) => self.process_assignment(&ident.symbol, &expression),
-            ast::Statement::Assignment(ident, expression) => self.process_assignment(&ident.symbol, &expression),
-            ast::Statement::If(if_statement) => match if_statement {
-                ast::IfStatement::If(condition, block, other) => self.process_if(condition, block, other),
-                ast::IfStatement::ElseIf(condition, block, other) => self.process_if(condition, block, other),
-                ast::IfStatement::Else(block) => self.process_block(block)
-            },
-            ast::Statement::While(condition, block) => {
-                while self.process_condition(&condition) {
-                    self.process_block(&block);
+            ast::Statement::Print(expressions) => {
+                let line = self.format_print(expressions)?;
+                self.write(Stream::Output, line)?;
+            },
+            ast::Statement::EPrint(expressions) => {
+                let line = self.format_print(expressions)?;
+                self.write(Stream::ErrorOutput, line)?;
+            },
+            ast::Statement::Let(ident, expression) => self.process_assignment(ident, expression, true)?,
+            ast::Statement::Assignment(ast::LValue::Ident(ident), expression) => self.process_assignment(ident, expression, false)?,
+            ast::Statement::Assignment(target, expression) => {
+                let value = self.process_expression(expression)?;
+                let updated = self.replace_element(target, value)?;
+                self.store(target.get_ident(), updated, false)?;
+            },
+            ast::Statement::If(if_statement) => return self.process_else_if(if_statement),
+            ast::Statement::While(condition, block) => return self.process_while(condition, block, None),
+            ast::Statement::For(for_loop) => return self.process_for(for_loop, None),
+            ast::Statement::Labeled(label, statement) => match statement.as_ref() {
+                ast::Statement::While(condition, block) => return self.process_while(condition, block, Some(&label.symbol)),
+                ast::Statement::For(for_loop) => return self.process_for(for_loop, Some(&label.symbol)),
+                // Only loops can be labeled, so anything else just runs
+                statement => return self.process_statement(statement)
+            },
+            ast::Statement::Break(label) => return Ok(Flow::Break(label.as_ref().map(|label| label.symbol.clone()))),
+            ast::Statement::Continue(label) => return Ok(Flow::Continue(label.as_ref().map(|label| label.symbol.clone()))),
+            ast::Statement::Breakpoint => {
+                match self.debugger.take() {
+                    Some(mut debugger) => {
+                        let paused = debugger.on_breakpoint(self);
+                        self.debugger = Some(debugger);
+                        paused?;
+                    },
+                    None => {
+                        // Not debugging - dump the scope so the script author can still see the state
+                        let mut text = String::from("Breakpoint reached\n");
+                        for (name, value) in self.get_current_scope() {
+                            text.push_str(&format!("  {} = {}\n", name, value));
+                        }
+                        self.write(Stream::Output, text)?;
+                    }
                 }
+            },
+            ast::Statement::Inspect(ident) => self.process_inspect(ident.as_ref())?,
+            ast::Statement::Host(host) => self.process_host(host)?,
+            // Defined before the run started
+            ast::Statement::FunctionDef(_) => {},
+            ast::Statement::Return(expression) => {
+                let value = match expression {
+                    Some(expression) => Some(self.process_expression(expression)?),
+                    None => None
+                };
+                return Ok(Flow::Return(value));
+            },
+            // Settled by the analysis pass, which gave every use of the name its scope
+            ast::Statement::Global(_) | ast::Statement::Local(_) => {},
+            ast::Statement::Call(call) => {
+                self.invoke(call)?;
+            }
+        }
+
+        Ok(Flow::Normal)
+    }
+
+    fn process_while(&mut self, condition: &ast::Condition, block: &ast::Block, label: Option<&str>) -> Result<Flow, RuntimeError> {
+        let guard = self.symbol_table.get_guard(condition);
+        while self.process_guard(guard.as_ref(), condition)? {
+            match self.process_block(block)? {
+                Flow::Break(None) => break,
+                Flow::Break(Some(target)) if Some(target.as_str()) == label => break,
+                Flow::Normal | Flow::Continue(None) => {},
+                Flow::Continue(Some(target)) if Some(target.as_str()) == label => {},
+                // Aimed at a loop further out
+                flow => return Ok(flow)
+            }
+
+            // An empty body never reaches a statement to check the limits
+            self.step()?;
+        }
+
+        Ok(Flow::Normal)
+    }
+
+    fn process_guard(&mut self, guard: Option<&Guard>, condition: &ast::Condition) -> Result<bool, RuntimeError> {
+        if let Some(guard) = guard {
+            if let Some(Value::Number(number)) = self.lookup_slot(guard.slot) {
+                return Ok(compare(&guard.comparator, *number, guard.limit));
+            }
+        }
+
+        self.process_condition(condition)
+    }
+
+    // The bounds and step are worked out once, before the first pass. The body may change
+    // the loop variable - the next value follows on from whatever it was left at.
+    fn process_for(&mut self, for_loop: &ast::ForLoop, label: Option<&str>) -> Result<Flow, RuntimeError> {
+        let start = self.process_bound(&for_loop.start)?;
+        let end = self.process_bound(&for_loop.end)?;
+        let step = match &for_loop.step {
+            Some(step) => self.process_bound(step)?,
+            None => 1.0
+        };
+
+        if step == 0.0 {
+            return Err(RuntimeError::with_kind(RuntimeErrorKind::InvalidArgument, format!("FOR {} has a STEP of 0 and would never finish", for_loop.variable.symbol)));
+        }
+
+        let variable = &for_loop.variable;
+        let name = &variable.symbol;
+        self.store(variable, Value::Number(start), true)?;
+        let mut current = start;
+
+        while (step > 0.0 && current <= end) || (step < 0.0 && current >= end) {
+            match self.process_block(&for_loop.body)? {
+                Flow::Break(None) => break,
+                Flow::Break(Some(target)) if Some(target.as_str()) == label => break,
+                Flow::Normal | Flow::Continue(None) => {},
+                Flow::Continue(Some(target)) if Some(target.as_str()) == label => {},
+                // Aimed at a loop further out
+                flow => return Ok(flow)
+            }
+
+            current = match self.lookup_variable(variable).map(|value| value.to_number()) {
+                Some(Ok(number)) => number + step,
+                Some(Err(err)) => return Err(RuntimeError::with_kind(RuntimeErrorKind::Type, format!("FOR {} no longer holds a number - {}", name, err))),
+                None => return Err(RuntimeError::with_kind(RuntimeErrorKind::UndefinedVariable, format!("FOR {} lost its loop variable", name)))
+            };
+            self.store(variable, Value::Number(current), false)?;
+
+            // An empty body never reaches a statement to check the limits
+            self.step()?;
+        }
+
+        Ok(Flow::Normal)
+    }
+
+    fn process_bound(&mut self, expression: &ast::Expression) -> Result<f32, RuntimeError> {
+        match self.process_expression(expression)?.to_number() {
+            Ok(number) => Ok(number),
+            Err(err) => Err(RuntimeError::with_kind(RuntimeErrorKind::Type, format!("Invalid number used in FOR loop - {}", err)))
+        }
+    }
+
+    fn process_host(&mut self, host: &ast::HostStatement) -> Result<(), RuntimeError> {
+        let mut arguments = Vec::with_capacity(host.arguments.len());
+        for argument in &host.arguments {
+            arguments.push(self.process_expression(argument)?);
+        }
+
+        let handler = match self.host_handlers.get_mut(&host.keyword) {
+            Some(handler) => handler,
+            None => return Err(RuntimeError::with_kind(RuntimeErrorKind::Host, format!("No handler registered for {} statements", host.keyword)))
+        };
+
+        handler(host, &arguments).map_err(|err| RuntimeError::with_kind(RuntimeErrorKind::Host, format!("{} failed - {}", host.keyword, err)))
+    }
+
+    fn format_print(&mut self, expressions: &[ast::Expression]) -> Result<String, RuntimeError> {
+        let mut line = String::new();
+        for (i, expression) in expressions.iter().enumerate() {
+            if i > 0 {
+                line.push_str(&self.print_separator);
+            }
+
+            line.push_str(&self.process_expression(expression)?.format(&self.print_format));
+        }
+
+        line.push_str(&self.print_terminator);
+        Ok(line)
+    }
+
+    // One `name: type = value` line per variable, sorted so the output is stable
+    fn process_inspect(&mut self, ident: Option<&ast::Ident>) -> Result<(), RuntimeError> {
+        let mut lines = Vec::new();
+        match ident {
+            Some(ident) => match self.lookup_variable(ident) {
+                Some(value) => lines.push(describe_variable(&ident.symbol, value)),
+                None => lines.push(format!("{} is not assigned", ident.symbol))
+            },
+            None => {
+                for (name, value) in self.get_current_scope() {
+                    lines.push(describe_variable(name, value));
+                }
+
+                if lines.is_empty() {
+                    lines.push(String::from("no variables assigned"));
+                }
+            }
+        }
+
+        let mut text = lines.join("\n");
+        text.push('\n');
+        self.write(Stream::Output, text)
+    }
+
+    fn write(&mut self, stream: Stream, text: String) -> Result<(), RuntimeError> {
+        if let Some(events) = &mut self.events {
+            events(Event::OutputProduced { stream: stream, text: text });
+            return Ok(());
+        }
+
+        let (output, name) = match stream {
+            Stream::Output => (&mut self.output, "output"),
+            Stream::ErrorOutput => (&mut self.error_output, "error output")
+        };
+
+        match output.write_all(text.as_bytes()) {
+            Ok(()) => Ok(()),
+            Err(err) => Err(RuntimeError::with_source(RuntimeErrorKind::Output, format!("Unable to write {} - {}", name, err), err))
+        }
+    }
+
+    fn emit(&mut self, event: Event) {
+        if let Some(events) = &mut self.events {
+            events(event);
+        }
+    }
+
+    // Inside a function, LET makes a variable of the call's own. A plain assignment changes
+    // the function's variable if it has one, then the program's, and otherwise makes one
+    // for the call.
+    fn process_assignment(&mut self, ident: &ast::Ident, expression: &ast::Expression, declare: bool) -> Result<(), RuntimeError> {
+        let value = self.process_expression(expression)?;
+        self.store(ident, value, declare)
+    }
+
+    // The target's variable as it is with the element swapped for value. Values are
+    // never changed in place - the whole variable is stored again.
+    fn replace_element(&mut self, target: &ast::LValue, value: Value) -> Result<Value, RuntimeError> {
+        match target {
+            ast::LValue::Ident(_) => Ok(value),
+            ast::LValue::Index(container, index) => {
+                let current = self.read_lvalue(container)?;
+                let updated = self.write_element(current, index, value)?;
+                self.replace_element(container, updated)
             }
         }
     }
 
-    fn process_assignment(&mut self, ident: &str, expression: &ast::Expression) {
-        let expression = self.process_expression(expression);
-        let symbol = self.symbol_table.lookup(ident);
+    fn read_lvalue(&mut self, target: &ast::LValue) -> Result<Value, RuntimeError> {
+        match target {
+            ast::LValue::Ident(ident) => match self.lookup_variable(ident) {
+                Some(value) => Ok(value.clone()),
+                None => Err(RuntimeError::with_kind(RuntimeErrorKind::UndefinedVariable, format!("Attempted to use a variable before assignment - {}", &ident.symbol)))
+            },
+            ast::LValue::Index(container, index) => {
+                let container = self.read_lvalue(container)?;
+                self.read_element(&container, index)
+            }
+        }
+    }
+
+    fn store(&mut self, ident: &ast::Ident, expression: Value, declare: bool) -> Result<(), RuntimeError> {
+        let name = ident.symbol.as_str();
+        let slot = match self.symbol_table.get_slot(ident) {
+            Some(slot) => slot,
+            None => return Err(RuntimeError::with_kind(RuntimeErrorKind::UndefinedVariable, format!("Attempted to assign to an unidentified variable - {}", name)))
+        };
+
+        if let Some(events) = &mut self.events {
+            events(Event::VariableChanged {
+                name: String::from(name),
+                value: expression.format(&self.print_format),
+                type_name: expression.type_name(),
+            });
+        }
+
+        if let Some(tracer) = &mut self.tracer {
+            tracer.assigned(name, expression.format(&self.print_format));
+        }
 
-        if symbol.is_none() {
-            panic!("Attempted to assign to an unidentified variable - {}", ident);
+        let is_global = matches!(self.globals.get(slot), Some(Some(_)));
+        let scope = match (self.symbol_table.get_scope(ident), self.locals.last_mut()) {
+            (Some(Scope::Global), _) | (_, None) => &mut self.globals,
+            (Some(Scope::Local), Some(locals)) => locals,
+            (None, Some(locals)) if declare || matches!(locals.get(slot), Some(Some(_))) || !is_global => locals,
+            (None, Some(_)) => &mut self.globals
+        };
+        if slot >= scope.len() {
+            scope.resize(self.symbol_table.slot_count().max(slot + 1), None);
+        }
+        // The debugger is shown what was stored, since it may not be in the current scope
+        let stored = self.debugger.as_ref().map(|_| expression.clone());
+        let previous = scope[slot].replace(expression);
+
+        if let Some(mut memory_stats) = self.memory_stats.take() {
+            // Everything in reach - the globals, and the locals of the call being run
+            let mut scope = self.get_global_scope();
+            if let Some(locals) = self.locals.last() {
+                scope.extend(self.name_scope(locals));
+            }
+            memory_stats.observe_scope(&scope);
+            self.memory_stats = Some(memory_stats);
         }
 
-        self.global_scope.insert(String::from(&symbol.unwrap().name), expression);
+        // Give the debugger a chance to pause on watched variables
+        if let (Some(mut debugger), Some(stored)) = (self.debugger.take(), stored) {
+            let paused = debugger.on_assignment(name, previous.as_ref(), &stored, self);
+            self.debugger = Some(debugger);
+            paused?;
+        }
+
+        Ok(())
+    }
+
+    fn process_expression(&mut self, expression: &ast::Expression) -> Result<Value, RuntimeError> {
+        self.descend()?;
+        let value = self.process_expression_node(expression);
+        self.depth -= 1;
+        value
     }
 
-    fn process_expression(&mut self, expression: &ast::Expression) -> String {
+    fn process_expression_node(&mut self, expression: &ast::Expression) -> Result<Value, RuntimeError> {
         match expression {
-            ast::Expression::Literal(literal) => self.process_literal(&literal),
-            ast::Expression::BinaryOp(bin_op) => self.process_binary_op(&bin_op),
+            ast::Expression::Literal(literal, id) => self.process_literal(literal, *id),
+            ast::Expression::BinaryOp(bin_op) => self.process_binary_op(bin_op),
             ast::Expression::UnaryOp(un_op) => {
-                match self.process_expression(&un_op.term).parse() {
+                let value = self.process_expression(&un_op.term)?;
+                if let Value::Decimal(term) = value {
+                    return match un_op.operator {
+                        ast::Operator::Minus => Ok(Value::Decimal(-term)),
+                        _ => Ok(Value::Decimal(term))
+                    };
+                }
+
+                self.note_coercion(&value, &value);
+                let term = match value.to_number() {
                     Ok(number) => number,
-                    Err(err) => panic!("Invalid number used in binary op - {}", err)
+                    Err(err) => return Err(RuntimeError::with_kind(RuntimeErrorKind::Type, format!("Invalid number used in unary op - {}", err)))
+                };
+
+                match un_op.operator {
+                    ast::Operator::Minus => Ok(Value::Number(-term)),
+                    _ => Ok(Value::Number(term))
                 }
             }
+            ast::Expression::Call(call) => match self.invoke(call)? {
+                Some(value) => Ok(value),
+                None => Err(RuntimeError::with_kind(RuntimeErrorKind::Type, format!("{} ended without returning a value", call.function.symbol)))
+            },
+            ast::Expression::Index(index) => self.process_index(index),
+            ast::Expression::Array(elements) => {
+                let values = elements.iter()
+                    .map(|element| self.process_expression(element))
+                    .collect::<Result<Vec<Value>, RuntimeError>>()?;
+                Ok(Value::Array(Rc::from(values)))
+            },
+            ast::Expression::Map(entries) => {
+                let mut map = Vec::with_capacity(entries.len());
+                for (key, value) in entries {
+                    let key = self.process_key(key)?;
+                    let value = self.process_expression(value)?;
+                    set_entry(&mut map, key, value);
+                }
+                Ok(Value::Map(Rc::from(map)))
+            },
             ast::Expression::Ident(ident) => {
-                match self.global_scope.get(&ident.symbol) {
-                    Some(val) => String::from(val),
-                    None => panic!("Attempted to use a variable before assignment - {}", &ident.symbol)
+                match self.lookup_variable(ident) {
+                    Some(val) => Ok(val.clone()),
+                    None => Err(RuntimeError::with_kind(RuntimeErrorKind::UndefinedVariable, format!("Attempted to use a variable before assignment - {}", &ident.symbol)))
+                }
+            }
+        }
+    }
+
+    // Runs a function of the program's own or a builtin. Only a function that ends without
+    // RETURN, or with a RETURN that gives no value, comes back with None.
+    fn invoke(&mut self, call: &ast::Call) -> Result<Option<Value>, RuntimeError> {
+        match self.functions.get(&call.function.symbol) {
+            Some(function) => {
+                let function = Rc::clone(function);
+                self.call_function(&function, call)
+            },
+            None => self.process_call(call).map(Some)
+        }
+    }
+
+    fn call_function(&mut self, function: &ast::FunctionDef, call: &ast::Call) -> Result<Option<Value>, RuntimeError> {
+        let name = &function.name.symbol;
+        // Every parameter up to the last one without a default has to be given
+        let required = (0..function.parameters.len())
+            .rfind(|index| !matches!(function.defaults.get(*index), Some(Some(_))))
+            .map_or(0, |index| index + 1);
+        let given = (0..call.arguments.len()).filter(|index| call.get_name(*index).is_none()).count();
+        if given > function.parameters.len() || (!call.has_names() && given < required) {
+            let expected = if required == function.parameters.len() {
+                required.to_string()
+            } else {
+                format!("{} to {}", required, function.parameters.len())
+            };
+            return Err(RuntimeError::with_kind(RuntimeErrorKind::InvalidArgument, format!("{} expects {} argument(s) but was given {}", name, expected, given)));
+        }
+
+        if self.locals.len() >= MAX_CALL_DEPTH {
+            return Err(RuntimeError::with_kind(RuntimeErrorKind::BudgetExceeded, format!("Calls nested more than {} deep", MAX_CALL_DEPTH)));
+        }
+
+        // Arguments given by position fill the first parameters, and those given by name fill
+        // theirs - all checked before any of them is worked out
+        let mut positions = Vec::with_capacity(call.arguments.len());
+        let mut next = 0;
+        for index in 0..call.arguments.len() {
+            let position = match call.get_name(index) {
+                Some(parameter) => match function.parameters.iter().position(|existing| existing.symbol == parameter) {
+                    Some(position) => position,
+                    None => return Err(RuntimeError::with_kind(RuntimeErrorKind::InvalidArgument, format!("{} has no parameter named {}", name, parameter)))
+                },
+                None => {
+                    next += 1;
+                    next - 1
+                }
+            };
+
+            if positions.contains(&position) {
+                return Err(RuntimeError::with_kind(RuntimeErrorKind::InvalidArgument, format!("{} is given {} more than once", name, function.parameters[position].symbol)));
+            }
+            positions.push(position);
+        }
+
+        // Arguments are worked out in the caller's scope, before the call has one
+        let mut values: Vec<Option<Value>> = vec![None; function.parameters.len()];
+        for (argument, position) in call.arguments.iter().zip(positions) {
+            values[position] = Some(self.process_expression(argument)?);
+        }
+
+        let mut locals = vec![None; self.symbol_table.slot_count()];
+        for (parameter, value) in function.parameters.iter().zip(&values) {
+            if let (Some(slot), Some(value)) = (self.symbol_table.get_slot(parameter), value) {
+                locals[slot] = Some(value.clone());
+            }
+        }
+
+        // The rest are worked out in the call's scope, where the parameters given are already set
+        self.locals.push(locals);
+        let arguments = match self.bind_defaults(function, values) {
+            Ok(arguments) => arguments,
+            Err(err) => {
+                self.locals.pop();
+                return Err(err);
+            }
+        };
+
+        if function.memo {
+            if let Some(value) = self.memos.get(name).and_then(|memo| memo.get(&arguments)) {
+                let value = value.clone();
+                self.locals.pop();
+                return Ok(value);
+            }
+        }
+
+        if let Some(profiler) = &mut self.profiler {
+            profiler.enter(name);
+        }
+
+        self.call_stack.push(Frame::new(name.clone()));
+        let result = self.process_block(&function.body);
+        self.call_stack.pop();
+        self.locals.pop();
+
+        if let Some(profiler) = &mut self.profiler {
+            profiler.exit();
+        }
+
+        let value = match result? {
+            Flow::Normal => None,
+            Flow::Return(value) => value,
+            // The parser rejects these, but a syntax tree can come from elsewhere
+            _ => return Err(RuntimeError::new(format!("BREAK or CONTINUE in {} used outside of a loop it could leave", name)))
+        };
+
+        if function.memo {
+            self.memos.entry(name.clone()).or_default().insert(arguments, value.clone());
+        }
+
+        Ok(value)
+    }
+
+    fn bind_defaults(&mut self, function: &ast::FunctionDef, values: Vec<Option<Value>>) -> Result<Vec<Value>, RuntimeError> {
+        let mut arguments = Vec::with_capacity(values.len());
+        for (index, (parameter, value)) in function.parameters.iter().zip(values).enumerate() {
+            let value = match (value, function.defaults.get(index)) {
+                (Some(value), _) => value,
+                (None, Some(Some(default))) => {
+                    let value = self.process_expression(default)?;
+                    if let (Some(slot), Some(locals)) = (self.symbol_table.get_slot(parameter), self.locals.last_mut()) {
+                        locals[slot] = Some(value.clone());
+                    }
+                    value
+                },
+                _ => return Err(RuntimeError::with_kind(RuntimeErrorKind::InvalidArgument, format!("{} was not given a value for {}", function.name.symbol, parameter.symbol)))
+            };
+            arguments.push(value);
+        }
+
+        Ok(arguments)
+    }
+
+    fn process_call(&mut self, call: &ast::Call) -> Result<Value, RuntimeError> {
+        let builtin = match builtins::lookup(&call.function.symbol) {
+            Some(builtin) => builtin,
+            None => return Err(RuntimeError::with_kind(RuntimeErrorKind::UnknownFunction, format!("Call to unknown function - {}", call.function.symbol)))
+        };
+
+        if !self.modules.contains(&builtin.module) {
+            return Err(RuntimeError::with_kind(RuntimeErrorKind::ModuleDisabled, format!("{} is in the {} module, which is not enabled for this run", builtin.name, builtin.module.name())));
+        }
+
+        if call.has_names() {
+            return Err(RuntimeError::with_kind(RuntimeErrorKind::InvalidArgument, format!("{} is a builtin, so its arguments can't be given by name", builtin.name)));
+        }
+
+        let mut arguments = Vec::with_capacity(call.arguments.len());
+        for argument in &call.arguments {
+            arguments.push(self.process_expression(argument)?);
+        }
+
+        match builtin.get_intrinsic() {
+            Some(intrinsic) => {
+                builtin.check_arity(arguments.len()).map_err(|err| RuntimeError::with_kind(RuntimeErrorKind::InvalidArgument, err))?;
+                match intrinsic {
+                    Intrinsic::Eval => self.process_eval(&arguments[0])
                 }
             },
-            _ => panic!("Expression not defined")
+            None => builtin.call(&arguments).map_err(|err| RuntimeError::with_kind(RuntimeErrorKind::InvalidArgument, err))
+        }
+    }
+
+    // Parses the text as one expression and evaluates it against the program's variables.
+    // It runs under the same limits as everything else - its calls are checked against the
+    // enabled modules, and each EVAL is a step towards the budget and timeout.
+    fn process_eval(&mut self, source: &Value) -> Result<Value, RuntimeError> {
+        let source = match source {
+            Value::String(source) => source.clone(),
+            other => return Err(RuntimeError::with_kind(RuntimeErrorKind::InvalidArgument, format!("EVAL expects a string - found a {}", other.type_name())))
+        };
+
+        self.step()?;
+
+        if self.eval_depth >= MAX_EVAL_DEPTH {
+            return Err(RuntimeError::with_kind(RuntimeErrorKind::BudgetExceeded, format!("EVAL nested more than {} deep", MAX_EVAL_DEPTH)));
+        }
+
+        let mut lexer = Lexer::new(source.chars().peekable());
+        let mut parser = Parser::new(&mut lexer);
+        parser.set_limits(self.parse_limits);
+        let expression = match parser.parse_single_expression() {
+            Ok(expression) => expression,
+            Err(err) => return Err(RuntimeError::with_source(RuntimeErrorKind::Syntax, format!("EVAL of {:?} failed - {}", source, err), err))
+        };
+
+        self.eval_depth += 1;
+        let result = self.process_expression(&expression);
+        self.eval_depth -= 1;
+        result
+    }
+
+    fn process_index(&mut self, index: &ast::Index) -> Result<Value, RuntimeError> {
+        let target = self.process_expression(&index.target)?;
+        self.read_element(&target, &index.index)
+    }
+
+    // Maps are looked up by key, everything else by position
+    fn read_element(&mut self, target: &Value, index: &ast::Expression) -> Result<Value, RuntimeError> {
+        if let Value::Map(entries) = target {
+            let key = self.process_key(index)?;
+            return match entries.iter().find(|(existing, _)| *existing == key) {
+                Some((_, value)) => Ok(value.clone()),
+                None => Err(RuntimeError::with_kind(RuntimeErrorKind::KeyNotFound, format!("Map has no key {:?}", key)))
+            };
+        }
+
+        let position = self.process_position(index)?;
+        self.get_element(target, position)
+    }
+
+    // Setting a key a map doesn't have yet adds it after the others
+    fn write_element(&mut self, target: Value, index: &ast::Expression, value: Value) -> Result<Value, RuntimeError> {
+        if let Value::Map(entries) = &target {
+            let key = self.process_key(index)?;
+            let mut updated = entries.to_vec();
+            set_entry(&mut updated, key, value);
+            return Ok(Value::Map(Rc::from(updated)));
+        }
+
+        let position = self.process_position(index)?;
+        self.set_element(target, position, value)
+    }
+
+    fn process_key(&mut self, key: &ast::Expression) -> Result<Rc<str>, RuntimeError> {
+        match self.process_expression(key)? {
+            Value::String(key) => Ok(key),
+            other => Err(RuntimeError::with_kind(RuntimeErrorKind::Type, format!("Map keys must be strings - found a {}", other.type_name())))
+        }
+    }
+
+    fn process_position(&mut self, index: &ast::Expression) -> Result<usize, RuntimeError> {
+        match self.process_expression(index)?.to_number() {
+            Ok(position) if position >= 0.0 && position.fract() == 0.0 => Ok(position as usize),
+            Ok(position) => Err(RuntimeError::with_kind(RuntimeErrorKind::IndexOutOfRange, format!("Index must be a whole number of 0 or more - found {}", Value::Number(position)))),
+            Err(err) => Err(RuntimeError::with_kind(RuntimeErrorKind::Type, format!("Invalid index - {}", err)))
+        }
+    }
+
+    // Strings are indexed by character (Unicode scalar value), not by byte, starting at 0.
+    // Bytes give each byte as a number from 0 to 255.
+    fn get_element(&mut self, target: &Value, position: usize) -> Result<Value, RuntimeError> {
+        match target {
+            Value::Array(elements) => match elements.get(position) {
+                Some(element) => Ok(element.clone()),
+                None => Err(RuntimeError::with_kind(RuntimeErrorKind::IndexOutOfRange, format!("Index {} is out of range for an array of {} elements", position, elements.len())))
+            },
+            Value::Bytes(bytes) => match bytes.get(position) {
+                Some(byte) => Ok(Value::Number(*byte as f32)),
+                None => Err(RuntimeError::with_kind(RuntimeErrorKind::IndexOutOfRange, format!("Index {} is out of range for {} bytes", position, bytes.len())))
+            },
+            Value::String(s) => match s.chars().nth(position) {
+                Some(c) => Ok(Value::String(Rc::from(c.encode_utf8(&mut [0; 4])))),
+                None => Err(RuntimeError::with_kind(RuntimeErrorKind::IndexOutOfRange, format!("Index {} is out of range for a string of {} characters", position, s.chars().count())))
+            },
+            _ => Err(RuntimeError::with_kind(RuntimeErrorKind::Type, format!("Only strings, bytes, arrays and maps can be indexed - found a {}", target.type_name())))
         }
     }
 
-    fn process_binary_op(&mut self, binary_op: &ast::BinaryOp) -> String {
-        let left_expression: f32 = match self.process_expression(&binary_op.left_term).parse() {
+    // A string element holds exactly one character, and a byte a whole number from 0 to 255.
+    // An array element can be anything.
+    fn set_element(&mut self, target: Value, position: usize, value: Value) -> Result<Value, RuntimeError> {
+        if let Value::Array(elements) = &target {
+            if position >= elements.len() {
+                return Err(RuntimeError::with_kind(RuntimeErrorKind::IndexOutOfRange, format!("Index {} is out of range for an array of {} elements", position, elements.len())));
+            }
+
+            let mut updated = elements.to_vec();
+            updated[position] = value;
+            return Ok(Value::Array(Rc::from(updated)));
+        }
+
+        if let Value::Bytes(bytes) = &target {
+            let byte = match value.to_number() {
+                Ok(number) if (0.0..=255.0).contains(&number) && number.fract() == 0.0 => number as u8,
+                _ => return Err(RuntimeError::with_kind(RuntimeErrorKind::InvalidArgument, format!("A byte must be a whole number from 0 to 255 - found {}", value)))
+            };
+
+            if position >= bytes.len() {
+                return Err(RuntimeError::with_kind(RuntimeErrorKind::IndexOutOfRange, format!("Index {} is out of range for {} bytes", position, bytes.len())));
+            }
+
+            let mut updated = bytes.to_vec();
+            updated[position] = byte;
+            return Ok(Value::Bytes(Rc::from(updated)));
+        }
+
+        let (s, replacement) = match (&target, &value) {
+            (Value::String(s), Value::String(replacement)) if replacement.chars().count() == 1 => (s, replacement),
+            (Value::String(_), Value::String(replacement)) => return Err(RuntimeError::with_kind(RuntimeErrorKind::InvalidArgument, format!("A string element holds one character - found {:?}", replacement))),
+            (Value::String(_), _) => return Err(RuntimeError::with_kind(RuntimeErrorKind::Type, format!("A string element can only be set to a string - found a {}", value.type_name()))),
+            _ => return Err(RuntimeError::with_kind(RuntimeErrorKind::Type, format!("Only strings, bytes, arrays and maps can be indexed - found a {}", target.type_name())))
+        };
+
+        let length = s.chars().count();
+        if position >= length {
+            return Err(RuntimeError::with_kind(RuntimeErrorKind::IndexOutOfRange, format!("Index {} is out of range for a string of {} characters", position, length)));
+        }
+
+        let updated: String = s.chars()
+            .enumerate()
+            .map(|(i, c)| if i == position { replacement.to_string() } else { c.to_string() })
+            .collect();
+        Ok(Value::String(Rc::from(updated)))
+    }
+
+    fn process_binary_op(&mut self, binary_op: &ast::BinaryOp) -> Result<Value, RuntimeError> {
+        let ast::Expression::BinaryOp(left) = &binary_op.left_term else {
+            let left_value = self.process_operand(&binary_op.left_term)?;
+            let right_value = self.process_operand(&binary_op.right_term)?;
+            return self.apply_binary_op(binary_op, left_value, right_value);
+        };
+
+        // A chain like a + b + c nests down its left side - walk it here rather than
+        // recursing once per operator
+        let mut chain = vec![binary_op, left.as_ref()];
+        while let ast::Expression::BinaryOp(left) = &chain[chain.len() - 1].left_term {
+            chain.push(left);
+        }
+
+        let mut value = self.process_operand(&chain[chain.len() - 1].left_term)?;
+        for binary_op in chain.into_iter().rev() {
+            let right_value = self.process_operand(&binary_op.right_term)?;
+            value = self.apply_binary_op(binary_op, value, right_value)?;
+        }
+
+        Ok(value)
+    }
+
+    fn apply_binary_op(&mut self, binary_op: &ast::BinaryOp, left_value: Value, right_value: Value) -> Result<Value, RuntimeError> {
+        // Two floats is nearly all the arithmetic a loop does, so it skips the checks below
+        if let (Value::Number(left), Value::Number(right)) = (&left_value, &right_value) {
+            return Ok(Value::Number(number_op(&binary_op.operator, *left, *right)));
+        }
+
+        self.note_coercion(&left_value, &right_value);
+
+        // A decimal on either side keeps the whole operation exact
+        if left_value.is_decimal() || right_value.is_decimal() {
+            return self.process_decimal_op(&binary_op.operator, &left_value, &right_value);
+        }
+
+        let left_expression: f32 = match left_value.to_number() {
             Ok(number) => number,
-            Err(err) => panic!("Invalid number used in binary op - {}", err)
+            Err(err) => return Err(RuntimeError::with_kind(RuntimeErrorKind::Type, format!("Invalid number used in binary op - {}", err)))
         };
 
-        let right_expression: f32 = match self.process_expression(&binary_op.right_term).parse() {
+        let right_expression: f32 = match right_value.to_number() {
             Ok(number) => number,
-            Err(err) => panic!("Invalid number used in binary op - {}", err)
+            Err(err) => return Err(RuntimeError::with_kind(RuntimeErrorKind::Type, format!("Invalid number used in binary op - {}", err)))
         };
 
-        match binary_op.operator {
-            ast::Operator::Plus => (left_expression + right_expression).to_string(),
-            ast::Operator::Minus => (left_expression - right_expression).to_string(),
-            ast::Operator::Times => (left_expression * right_expression).to_string(),
-            ast::Operator::Divides => (left_expression / right_expression).to_string(),
+        Ok(Value::Number(number_op(&binary_op.operator, left_expression, right_expression)))
+    }
+
+    // A variable or literal beside an operator is read straight off, without the trip
+    // through process_expression - anything else goes the usual way
+    fn process_operand(&mut self, expression: &ast::Expression) -> Result<Value, RuntimeError> {
+        match expression {
+            ast::Expression::Ident(ident) => {
+                if let Some(value) = self.lookup_variable(ident) {
+                    return Ok(value.clone());
+                }
+            },
+            ast::Expression::Literal(literal, id) => return self.process_literal(literal, *id),
+            _ => {}
         }
+
+        self.process_expression(expression)
     }
 
-    fn process_condition(&mut self, condition: &ast::Condition) -> bool {
-        let left_expression: f32 = match self.process_expression(&condition.left_expression).parse() {
+    fn process_decimal_op(&mut self, operator: &ast::Operator, left_value: &Value, right_value: &Value) -> Result<Value, RuntimeError> {
+        let left_expression: Decimal = match left_value.to_decimal() {
             Ok(number) => number,
-            Err(err) => panic!("Invalid number used in binary op - {}", err)
+            Err(err) => return Err(RuntimeError::with_kind(RuntimeErrorKind::Type, format!("Invalid number used in binary op - {}", err)))
         };
 
-        let right_expression: f32 = match self.process_expression(&condition.right_expression).parse() {
+        let right_expression: Decimal = match right_value.to_decimal() {
             Ok(number) => number,
-            Err(err) => panic!("Invalid number used in binary op - {}", err)
+            Err(err) => return Err(RuntimeError::with_kind(RuntimeErrorKind::Type, format!("Invalid number used in binary op - {}", err)))
         };
 
-        match condition.comparator {
-            ast::Comparator::Equal => left_expression == right_expression,
-            ast::Comparator::NotEqual => left_expression != right_expression,
-            ast::Comparator::GreaterThan => left_expression > right_expression,
-            ast::Comparator::GreaterThanOrEqual => left_expression >= right_expression,
-            ast::Comparator::LessThan => left_expression < right_expression,
-            ast::Comparator::LessThanOrEqual => left_expression <= right_expression
+        // Unlike floats there is no infinity to fall back on
+        let result = match operator {
+            ast::Operator::Plus => left_expression.checked_add(right_expression),
+            ast::Operator::Minus => left_expression.checked_sub(right_expression),
+            ast::Operator::Times => left_expression.checked_mul(right_expression),
+            ast::Operator::Divides => {
+                if right_expression.is_zero() {
+                    return Err(RuntimeError::with_kind(RuntimeErrorKind::DivisionByZero, String::from("Decimal division by zero")));
+                }
+
+                left_expression.checked_div(right_expression)
+            },
+            ast::Operator::Power => decimal_power(left_expression, right_expression)?
+        };
+
+        match result {
+            Some(number) => Ok(Value::Decimal(number)),
+            None => Err(RuntimeError::with_kind(RuntimeErrorKind::Overflow, format!("Decimal overflow in {} {:?} {}", left_expression, operator, right_expression)))
         }
     }
 
-    fn process_literal(&mut self, literal: &ast::Literal) -> String {
+    fn process_condition(&mut self, condition: &ast::Condition) -> Result<bool, RuntimeError> {
+        match condition {
+            ast::Condition::Compare(comparison) => self.process_comparison(comparison),
+            ast::Condition::And(left, right) => Ok(self.process_condition(left)? && self.process_condition(right)?),
+            ast::Condition::Or(left, right) => Ok(self.process_condition(left)? || self.process_condition(right)?),
+            ast::Condition::Not(condition) => Ok(!self.process_condition(condition)?)
+        }
+    }
+
+    fn process_comparison(&mut self, condition: &ast::Comparison) -> Result<bool, RuntimeError> {
+        let left_value = self.process_operand(&condition.left_expression)?;
+        let right_value = self.process_operand(&condition.right_expression)?;
+
+        if let (Value::Number(left), Value::Number(right)) = (&left_value, &right_value) {
+            return Ok(compare(&condition.comparator, left, right));
+        }
+
+        // Literals are interned, so comparing two of them is a pointer check
+        if let (Value::String(left), Value::String(right)) = (&left_value, &right_value) {
+            match condition.comparator {
+                ast::Comparator::Equal => return Ok(self.strings.equal(left, right)),
+                ast::Comparator::NotEqual => return Ok(!self.strings.equal(left, right)),
+                _ => {}
+            }
+        }
+
+        if let (Value::Bytes(_), Value::Bytes(_)) | (Value::Array(_), Value::Array(_)) | (Value::Map(_), Value::Map(_)) = (&left_value, &right_value) {
+            match condition.comparator {
+                ast::Comparator::Equal => return Ok(left_value.equals(&right_value)),
+                ast::Comparator::NotEqual => return Ok(!left_value.equals(&right_value)),
+                _ => {}
+            }
+        }
+
+        // Booleans are only ever equal or not, and only to other booleans
+        match (&left_value, &right_value, &condition.comparator) {
+            (Value::Boolean(left), Value::Boolean(right), ast::Comparator::Equal) => return Ok(left == right),
+            (Value::Boolean(left), Value::Boolean(right), ast::Comparator::NotEqual) => return Ok(left != right),
+            (Value::Boolean(_), Value::Boolean(_), _) => return Err(RuntimeError::with_kind(RuntimeErrorKind::Type, String::from("Booleans can only be compared with == and !="))),
+            (Value::Boolean(_), other, _) | (other, Value::Boolean(_), _) => return Err(RuntimeError::with_kind(RuntimeErrorKind::Type, format!("Expected a boolean in condition - found a {}", other.type_name()))),
+            _ => {}
+        }
+
+        if let (Value::DateTime(left), Value::DateTime(right)) = (&left_value, &right_value) {
+            return Ok(compare(&condition.comparator, left, right));
+        }
+
+        self.note_coercion(&left_value, &right_value);
+
+        if left_value.is_decimal() || right_value.is_decimal() {
+            let left_expression: Decimal = match left_value.to_decimal() {
+                Ok(number) => number,
+                Err(err) => return Err(RuntimeError::with_kind(RuntimeErrorKind::Type, format!("Invalid number used in condition - {}", err)))
+            };
+
+            let right_expression: Decimal = match right_value.to_decimal() {
+                Ok(number) => number,
+                Err(err) => return Err(RuntimeError::with_kind(RuntimeErrorKind::Type, format!("Invalid number used in condition - {}", err)))
+            };
+
+            return Ok(compare(&condition.comparator, left_expression, right_expression));
+        }
+
+        let left_expression: f32 = match left_value.to_number() {
+            Ok(number) => number,
+            Err(err) => return Err(RuntimeError::with_kind(RuntimeErrorKind::Type, format!("Invalid number used in condition - {}", err)))
+        };
+
+        let right_expression: f32 = match right_value.to_number() {
+            Ok(number) => number,
+            Err(err) => return Err(RuntimeError::with_kind(RuntimeErrorKind::Type, format!("Invalid number used in condition - {}", err)))
+        };
+
+        Ok(compare(&condition.comparator, left_expression, right_expression))
+    }
+
+    // A string about to be read as a number - often a value that was meant to be converted
+    // first, or a comparison of strings that only works while they happen to hold numbers.
+    // One that isn't a number fails with an error of its own.
+    fn note_coercion(&mut self, left_value: &Value, right_value: &Value) {
+        let string = match (left_value, right_value) {
+            (Value::String(string), _) | (_, Value::String(string)) if string.parse::<f32>().is_ok() => string,
+            _ => return
+        };
+
+        let line = self.call_stack.last().map(|frame| frame.line).unwrap_or(0);
+        if self.enabled_warnings.is_enabled(WarningKind::Coercion) && self.coerced_lines.insert(line) {
+            self.warnings.push(Warning::new(WarningKind::Coercion, format!("String {:?} used as a number", string), Span::new(line, 0)));
+        }
+    }
+
+    fn process_literal(&mut self, literal: &ast::Literal, id: NodeId) -> Result<Value, RuntimeError> {
+        let constant = self.symbol_table.get_constant(literal, id).and_then(|index| self.constants.get(index));
+        if let Some(Some(value)) = constant {
+            return Ok(value.clone());
+        }
+
+        self.literal_value(literal)
+    }
+
+    fn literal_value(&mut self, literal: &ast::Literal) -> Result<Value, RuntimeError> {
         match literal {
-            ast::Literal::String(s) => String::from(s),
-            ast::Literal::Number(s) => String::from(s),
+            ast::Literal::String(s) => Ok(Value::String(self.strings.intern(s))),
+            ast::Literal::Number(s) => match s.parse() {
+                Ok(number) => Ok(Value::Number(number)),
+                Err(err) => Err(RuntimeError::new(format!("Invalid number literal {} - {}", s, err)))
+            },
+            ast::Literal::Decimal(s) => match s.parse() {
+                Ok(number) => Ok(Value::Decimal(number)),
+                Err(err) => Err(RuntimeError::new(format!("Invalid decimal literal {} - {}", s, err)))
+            },
+            ast::Literal::Boolean(b) => Ok(Value::Boolean(*b))
         }
     }
 
-    fn process_if(&mut self, condition: &ast::Condition, block: &ast::Block, other: &Option<Box<ast::IfStatement>>) {
-        if self.process_condition(condition) {
-            self.process_block(block);
+    fn process_if(&mut self, condition: &ast::Condition, block: &ast::Block, other: &Option<Box<ast::IfStatement>>) -> Result<Flow, RuntimeError> {
+        if self.process_condition(condition)? {
+            self.process_block(block)
         } else if let Some(else_if_statement) = other {
-            self.process_else_if(else_if_statement);
+            self.process_else_if(else_if_statement)
+        } else {
+            Ok(Flow::Normal)
         }
     }
 
-    fn process_else_if(&mut self, else_if: &ast::IfStatement) {
+    fn process_else_if(&mut self, else_if: &ast::IfStatement) -> Result<Flow, RuntimeError> {
         match else_if {
             ast::IfStatement::If(condition, block, other) => self.process_if(condition, block, other),
             ast::IfStatement::ElseIf(condition, block, other) => self.process_if(condition, block, other),
@@ -153,3 +1474,83 @@ impl<'a> Interpreter<'a> {
         }
     }
 }
+
+fn describe_variable(name: &str, value: &Value) -> String {
+    match value {
+        // Quoted so leading or trailing spaces are visible
+        Value::String(s) => format!("{}: {} = {:?}", name, value.type_name(), s),
+        _ => format!("{}: {} = {}", name, value.type_name(), value)
+    }
+}
+
+// Only whole powers stay exact, so those are all a decimal can be raised to
+fn decimal_power(base: Decimal, exponent: Decimal) -> Result<Option<Decimal>, RuntimeError> {
+    let times = match exponent.to_i64() {
+        Some(times) if exponent.fract().is_zero() => times,
+        _ => return Err(RuntimeError::with_kind(RuntimeErrorKind::InvalidArgument, format!("A decimal can only be raised to a whole number - found {}", exponent)))
+    };
+
+    if base.is_zero() && times < 0 {
+        return Err(RuntimeError::with_kind(RuntimeErrorKind::DivisionByZero, String::from("Decimal 0 raised to a negative power")));
+    }
+
+    // Squaring as it goes keeps a large exponent to a few dozen multiplications
+    let mut result = Some(Decimal::ONE);
+    let mut square = Some(base);
+    let mut remaining = times.unsigned_abs();
+    while remaining > 0 {
+        if remaining % 2 == 1 {
+            result = result.zip(square).and_then(|(result, square)| result.checked_mul(square));
+        }
+        remaining /= 2;
+        if remaining > 0 {
+            square = square.and_then(|square| square.checked_mul(square));
+        }
+    }
+
+    Ok(if times < 0 { result.and_then(|result| Decimal::ONE.checked_div(result)) } else { result })
+}
+
+// A key given twice keeps its first place with the last value
+fn set_entry(entries: &mut Vec<(Rc<str>, Value)>, key: Rc<str>, value: Value) {
+    match entries.iter_mut().find(|(existing, _)| *existing == key) {
+        Some(entry) => entry.1 = value,
+        None => entries.push((key, value))
+    }
+}
+
+fn number_op(operator: &ast::Operator, left: f32, right: f32) -> f32 {
+    match operator {
+        ast::Operator::Plus => left + right,
+        ast::Operator::Minus => left - right,
+        ast::Operator::Times => left * right,
+        ast::Operator::Divides => left / right,
+        ast::Operator::Power => left.powf(right),
+    }
+}
+
+// wasm32-unknown-unknown has no clock, and Instant::now panics there - run timings read as zero
+fn start_timing() -> Option<Instant> {
+    if cfg!(target_arch = "wasm32") {
+        return None;
+    }
+
+    Some(Instant::now())
+}
+
+fn elapsed_since(started: Option<Instant>) -> Duration {
+    started.map(|started| started.elapsed()).unwrap_or_default()
+}
+
+// NAN follows IEEE 754 - it is unequal to everything, itself included, and neither above
+// nor below any number, so only != holds. ISNAN is how a script tests for it.
+fn compare<T: PartialOrd>(comparator: &ast::Comparator, left: T, right: T) -> bool {
+    match comparator {
+        ast::Comparator::Equal => left == right,
+        ast::Comparator::NotEqual => left != right,
+        ast::Comparator::GreaterThan => left > right,
+        ast::Comparator::GreaterThanOrEqual => left >= right,
+        ast::Comparator::LessThan => left < right,
+        ast::Comparator::LessThanOrEqual => left <= right
+    }
+}
@@ -1,3 +1,6 @@
+use super::error::LexError;
+use super::error::LexErrorKind;
+use super::error::Span;
 use super::token::Token;
 use super::token::TokenType;
 
@@ -6,28 +9,119 @@ use std::iter::Peekable;
 
 pub struct Lexer<'a> {
     input: Peekable<Chars<'a>>,
+    line: usize,
+    column: usize,
+    offset: usize,
+    // When recovering, errors are collected and an ILLEGAL token stands in for the bad input
+    recover: bool,
+    errors: Vec<LexError>,
+    // Keywords only count when written in capitals
+    case_sensitive_keywords: bool,
+    // Symbols declared with OPERATOR so far - see add_operator
+    operators: Vec<String>,
+}
+
+// Lexes a whole source string, stopping at the first error
+pub fn lex_all(source: &str) -> Result<Vec<Token>, LexError> {
+    lex_all_with_operators(source, &[])
+}
+
+// Like lex_all, with these symbols lexed as declared operators throughout
+pub fn lex_all_with_operators(source: &str, operators: &[String]) -> Result<Vec<Token>, LexError> {
+    let mut lexer = Lexer::new(source.chars().peekable());
+    for symbol in operators {
+        lexer.add_operator(symbol.clone());
+    }
+
+    let mut tokens = Vec::new();
+
+    loop {
+        let token = lexer.get_token()?;
+        let at_end = token.get_token_type() == &TokenType::EOF;
+        tokens.push(token);
+
+        if at_end {
+            return Ok(tokens);
+        }
+    }
 }
 
 impl<'a> Lexer<'a> {
 
-    pub fn new(input: Peekable<Chars<'a>>) -> Lexer {
-        Lexer { input: input }
+    pub fn new(input: Peekable<Chars<'a>>) -> Lexer<'a> {
+        Lexer::starting_at(input, Span::new(1, 1))
+    }
+
+    // For input that is a tail of a larger source - positions carry on from where it begins
+    pub fn starting_at(input: Peekable<Chars<'a>>, span: Span) -> Lexer<'a> {
+        Lexer {
+            input: input,
+            line: span.line,
+            column: span.column,
+            offset: span.offset,
+            recover: false,
+            errors: Vec::new(),
+            case_sensitive_keywords: false,
+            operators: Vec::new(),
+        }
+    }
+
+    pub fn set_recovery(&mut self, recover: bool) {
+        self.recover = recover;
     }
 
-    pub fn get_token(&mut self) -> Token {
+    pub fn set_case_sensitive_keywords(&mut self, case_sensitive_keywords: bool) {
+        self.case_sensitive_keywords = case_sensitive_keywords;
+    }
+
+    // Lexes the symbol as one SYMBOL token from here on. Where it overlaps another operator
+    // or a declared symbol, the longest one written wins.
+    pub fn add_operator(&mut self, symbol: String) {
+        if !self.operators.contains(&symbol) {
+            self.operators.push(symbol);
+        }
+    }
+
+    // Errors skipped over while recovering, in source order
+    pub fn take_errors(&mut self) -> Vec<LexError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    pub fn get_token(&mut self) -> Result<Token, LexError> {
 
         while self.is_next_whitespace() {
-            self.input.next();
+            self.next_char();
+        }
+
+        let span = self.get_span();
+        match self.lex_token(span) {
+            Ok(token) => Ok(token),
+            Err(err) if self.recover => {
+                let token = Token::new(TokenType::ILLEGAL, String::from(err.get_message())).at(span);
+                self.errors.push(err);
+                Ok(token)
+            },
+            Err(err) => Err(err)
+        }
+    }
+
+    fn lex_token(&mut self, span: Span) -> Result<Token, LexError> {
+        if let Some(symbol) = self.match_operator() {
+            for _ in symbol.chars() {
+                self.next_char();
+            }
+            return Ok(Token::new(TokenType::SYMBOL, symbol).at(span));
         }
 
-        match self.input.next() {
+        let token = match self.next_char() {
             Some('+')                     => Token::new(TokenType::PLUS, String::from("+")),
             Some('-')                     => Token::new(TokenType::MINUS, String::from("-")),
             Some('*')                     => Token::new(TokenType::ASTERISK, String::from("*")),
             Some('/')                     => Token::new(TokenType::SLASH, String::from("/")),
+            Some('^')                     => Token::new(TokenType::CARET, String::from("^")),
             Some('=')                     => {
                 if self.is_next_check('=') {
-                    self.input.next();
+                    self.next_char();
                     Token::new(TokenType::EQEQ, String::from("=="))
                 } else {
                     Token::new(TokenType::EQ, String::from("="))
@@ -35,15 +129,15 @@ impl<'a> Lexer<'a> {
             },
             Some('>')                     => {
                 if self.is_next_check('=') {
-                    self.input.next();
+                    self.next_char();
                     Token::new(TokenType::GTEQ, String::from(">="))
                 } else {
                     Token::new(TokenType::GT, String::from(">"))
                 }
             },
             Some('<')                     => {
-                if self.is_next_check('<') {
-                    self.input.next();
+                if self.is_next_check('=') {
+                    self.next_char();
                     Token::new(TokenType::LTEQ, String::from("<="))
                 } else {
                     Token::new(TokenType::LT, String::from("<"))
@@ -51,20 +145,29 @@ impl<'a> Lexer<'a> {
             },
             Some('!')                     => {
                 if self.is_next_check('=') {
-                    self.input.next();
+                    self.next_char();
                     Token::new(TokenType::NOTEQ, String::from("!="))
                 } else {
-                    panic!("Invalid token found");
+                    return Err(LexError::new(LexErrorKind::UnexpectedCharacter, String::from("Expected = after !"), span));
                 }
             },
-            Some('"')                     => self.process_string(),
-            Some(c) if c.is_ascii_digit() => self.process_number(&c),
+            Some('"')                     => self.process_string(span)?,
+            Some(c) if c.is_ascii_digit() => self.process_number(&c, span)?,
             Some(c) if c.is_alphabetic()  => self.process_alpha(&c),
             Some(';')                     => Token::new(TokenType::SEMICOLON, String::from(";")),
-            Some(_)                       => Token::new(TokenType::ILLEGAL, String::from("")),
+            Some(':')                     => Token::new(TokenType::COLON, String::from(":")),
+            Some('(')                     => Token::new(TokenType::LPAREN, String::from("(")),
+            Some(')')                     => Token::new(TokenType::RPAREN, String::from(")")),
+            Some(',')                     => Token::new(TokenType::COMMA, String::from(",")),
+            Some('[')                     => Token::new(TokenType::LBRACKET, String::from("[")),
+            Some(']')                     => Token::new(TokenType::RBRACKET, String::from("]")),
+            Some('{')                     => Token::new(TokenType::LBRACE, String::from("{")),
+            Some('}')                     => Token::new(TokenType::RBRACE, String::from("}")),
+            Some(c)                       => return Err(LexError::new(LexErrorKind::UnexpectedCharacter, format!("Unexpected character {:?}", c), span)),
             None                          => Token::new(TokenType::EOF, String::from("\0")),
-        }
+        };
 
+        Ok(token.at(span))
     }
 
     pub fn has_next_token(&mut self) -> bool {
@@ -74,65 +177,99 @@ impl<'a> Lexer<'a> {
         }
     }
 
-    fn process_string(&mut self) -> Token {
+    fn process_string(&mut self, span: Span) -> Result<Token, LexError> {
+        let start = self.input.clone();
+        let (line, column, offset) = (self.line, self.column, self.offset);
         let mut value = String::from("");
-        let mut end_value = '"';
-        while let Some(c) = self.input.next() {
+        // Anything but a quote, so a lone quote at the end of input is still unclosed
+        let mut end_value = '\0';
+        while let Some(c) = self.next_char() {
             // If end of string, with quote, break
             end_value = c;
             if c == '"' {
                 break;
             }
 
-            value.push_str(&c.to_string());
+            value.push(c);
         }
 
         if end_value != '"' {
             // End value was not end quote, bad string - exit lexer
             // as we can't process this
-            panic!("Unclosed string literal found");
+            if self.recover {
+                // The string ran to the end of the input - only give up the rest of its line
+                self.input = start;
+                self.line = line;
+                self.column = column;
+                self.offset = offset;
+                while !self.is_next_check('\n') && self.has_next_token() {
+                    self.next_char();
+                }
+            }
+
+            return Err(LexError::new(LexErrorKind::UnclosedString, String::from("Unclosed string literal found"), span));
         }
 
-        Token::new(TokenType::STRING, value)
+        Ok(Token::new(TokenType::STRING, value))
     }
 
-    fn process_number(&mut self, start_char: &char) -> Token {
+    fn process_number(&mut self, start_char: &char, span: Span) -> Result<Token, LexError> {
         let mut value = start_char.to_string();
         while self.is_next_digit() {
             // Safe to unwrap from the above digit check
-            let next = self.input.next().unwrap();
-            value.push_str(&next.to_string());
+            let next = self.next_char().unwrap();
+            value.push(next);
         }
 
         // Check for decimal
         if self.is_next_check('.') {
             // Safe to unwrap here
-            value.push_str(&(self.input.next().unwrap()).to_string());
+            value.push(self.next_char().unwrap());
 
             // Check for more digits on right side of decimal
             if !self.is_next_digit() {
-                panic!("Invalid number found");
+                return Err(LexError::new(LexErrorKind::InvalidNumber, format!("Invalid number found - {}", value), span));
             }
 
             while self.is_next_digit() {
                 // Safe to unwrap from the above digit check
-                let next = self.input.next().unwrap();
-                value.push_str(&next.to_string());
+                let next = self.next_char().unwrap();
+                value.push(next);
+            }
+        }
+
+        // A d suffix makes an exact decimal rather than a float - 19.99d
+        if self.is_next_check('d') || self.is_next_check('D') {
+            // Safe to unwrap from the above check
+            let suffix = self.next_char().unwrap();
+
+            // 10dollars is a mistake, not the decimal 10 and then ollars - take the whole word
+            // so lexing picks up after it
+            if self.is_next_alphanumeric() {
+                let mut word = format!("{}{}", value, suffix);
+                while self.is_next_alphanumeric() {
+                    // Safe to unwrap from the above alphanumeric check
+                    word.push(self.next_char().unwrap());
+                }
+                return Err(LexError::new(LexErrorKind::InvalidNumber, format!("Invalid number found - {}", word), span));
             }
+
+            return Ok(Token::new(TokenType::DECIMAL, value));
         }
 
-        Token::new(TokenType::NUMBER, value)
+        Ok(Token::new(TokenType::NUMBER, value))
     }
 
     fn process_alpha(&mut self, start_char: &char) -> Token {
         let mut value = start_char.to_string();
         while self.is_next_alphanumeric() {
             // Safe to unwrap from the above alphenumeric check
-            let next = self.input.next().unwrap();
-            value.push_str(&next.to_string());
+            let next = self.next_char().unwrap();
+            value.push(next);
         }
 
-        let token_type = TokenType::get_keyword_token(&value);
+        let token_type = TokenType::get_keyword_token(&value)
+            .filter(|_| !self.case_sensitive_keywords || value == value.to_uppercase());
 
         // If we found a keyword, return that token, otherwise
         // Random alphanumeric non-quoted string will be an ident
@@ -142,6 +279,40 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    // The longest declared operator the input starts with
+    fn match_operator(&self) -> Option<String> {
+        self.operators.iter()
+            .filter(|symbol| self.input.clone().take(symbol.chars().count()).eq(symbol.chars()))
+            .max_by_key(|symbol| symbol.len())
+            .cloned()
+    }
+
+    fn get_span(&self) -> Span {
+        Span {
+            line: self.line,
+            column: self.column,
+            offset: self.offset,
+        }
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let next = self.input.next();
+        match next {
+            Some('\n') => {
+                self.line += 1;
+                self.column = 1;
+            },
+            Some(_) => self.column += 1,
+            None => ()
+        }
+
+        if let Some(c) = next {
+            self.offset += c.len_utf8();
+        }
+
+        next
+    }
+
     fn is_next_whitespace(&mut self) -> bool {
         match self.input.peek() {
             Some(&c) => c.is_whitespace(),
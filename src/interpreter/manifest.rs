@@ -0,0 +1,123 @@
+use serde::Deserialize;
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+pub const MANIFEST_FILE: &str = "project.toml";
+
+// A project.toml describing a multi-file program
+//
+//     [project]
+//     main = "main.bas"
+//     sources = ["shapes.bas", "util.bas"]
+//
+//     [options]
+//     strict = true
+//     backend = "tree"
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    project: ProjectSection,
+    #[serde(default)]
+    options: Options,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProjectSection {
+    #[serde(default)]
+    name: Option<String>,
+    main: String,
+    #[serde(default)]
+    sources: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Options {
+    // false lets plain assignments declare variables, like --implicit-vars
+    pub strict: bool,
+    pub backend: Backend,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            strict: true,
+            backend: Backend::Tree,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    // Walk the syntax tree directly - the only backend so far
+    Tree,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestError {
+    message: String,
+}
+
+impl ManifestError {
+    pub fn new(message: String) -> ManifestError {
+        ManifestError { message: message }
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid {} - {}", MANIFEST_FILE, self.message)
+    }
+}
+
+impl Manifest {
+    pub fn parse(text: &str) -> Result<Manifest, ManifestError> {
+        toml::from_str(text).map_err(|err| ManifestError::new(err.to_string()))
+    }
+
+    // Loads project.toml from a directory, resolving source paths against that directory
+    pub fn load(dir: &Path) -> Result<Manifest, ManifestError> {
+        let path = dir.join(MANIFEST_FILE);
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) => return Err(ManifestError::new(format!("Unable to read {} - {}", path.display(), err)))
+        };
+
+        let mut manifest = Manifest::parse(&text)?;
+        manifest.project.main = resolve(dir, &manifest.project.main);
+        manifest.project.sources = manifest.project.sources.iter().map(|file| resolve(dir, file)).collect();
+
+        Ok(manifest)
+    }
+
+    pub fn get_name(&self) -> Option<&str> {
+        self.project.name.as_deref()
+    }
+
+    pub fn get_main(&self) -> &str {
+        &self.project.main
+    }
+
+    pub fn get_options(&self) -> &Options {
+        &self.options
+    }
+
+    // Every file in the order it runs - the other sources first, main last
+    pub fn get_files(&self) -> Vec<String> {
+        let mut files = self.project.sources.clone();
+        files.push(self.project.main.clone());
+        files
+    }
+}
+
+fn resolve(dir: &Path, file: &str) -> String {
+    dir.join(file).to_string_lossy().into_owned()
+}
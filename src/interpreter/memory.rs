@@ -0,0 +1,121 @@
+use std::alloc::GlobalAlloc;
+use std::alloc::Layout;
+use std::alloc::System;
+use std::collections::BTreeMap;
+use std::mem;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use super::date::DateTime;
+use super::value::Value;
+
+use rust_decimal::Decimal;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+// Allocation counts are only available when the binary installs this as its
+// global allocator - otherwise they read as zero
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record_allocation(layout.size());
+        }
+
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+            record_allocation(new_size);
+        }
+
+        new_ptr
+    }
+}
+
+fn record_allocation(size: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATED_BYTES.fetch_add(size, Ordering::Relaxed);
+    let live = LIVE_BYTES.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK_BYTES.fetch_max(live, Ordering::Relaxed);
+}
+
+pub struct MemoryStats {
+    start_allocations: usize,
+    start_allocated_bytes: usize,
+    peak_variables: usize,
+    peak_scope_bytes: usize,
+}
+
+impl Default for MemoryStats {
+    fn default() -> MemoryStats {
+        MemoryStats::new()
+    }
+}
+
+impl MemoryStats {
+    pub fn new() -> MemoryStats {
+        // Only measure from here on, not whatever ran before the program
+        PEAK_BYTES.store(LIVE_BYTES.load(Ordering::Relaxed), Ordering::Relaxed);
+
+        MemoryStats {
+            start_allocations: ALLOCATIONS.load(Ordering::Relaxed),
+            start_allocated_bytes: ALLOCATED_BYTES.load(Ordering::Relaxed),
+            peak_variables: 0,
+            peak_scope_bytes: 0,
+        }
+    }
+
+    pub fn observe_scope(&mut self, scope: &BTreeMap<&str, &Value>) {
+        let scope_bytes = scope.iter().map(|(name, value)| name.len() + value_size(value)).sum();
+
+        self.peak_variables = self.peak_variables.max(scope.len());
+        self.peak_scope_bytes = self.peak_scope_bytes.max(scope_bytes);
+    }
+
+    // Takes the values of every frame still alive, not just the global scope
+    pub fn report(&self, values: &[&Value]) -> String {
+        let heap_values = values.iter().filter(|value| is_heap_backed(value)).count();
+        let strings = values.iter().filter(|value| matches!(value, Value::String(_))).count();
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - self.start_allocations;
+        let allocated_bytes = ALLOCATED_BYTES.load(Ordering::Relaxed) - self.start_allocated_bytes;
+
+        let mut output = String::from("Memory usage:\n");
+        output.push_str(&format!("  peak scope size:  {} variables ({} bytes)\n", self.peak_variables, self.peak_scope_bytes));
+        output.push_str(&format!("  live heap values: {} ({} strings)\n", heap_values, strings));
+        output.push_str(&format!("  allocations:      {} ({} bytes)\n", allocations, allocated_bytes));
+        output.push_str(&format!("  peak heap:        {} bytes", PEAK_BYTES.load(Ordering::Relaxed)));
+        output
+    }
+}
+
+// Numbers, booleans and dates live inline in the slot - the rest point at an allocation
+fn is_heap_backed(value: &Value) -> bool {
+    matches!(value, Value::String(_) | Value::Bytes(_) | Value::Array(_) | Value::Map(_))
+}
+
+fn value_size(value: &Value) -> usize {
+    match value {
+        Value::Number(_) => mem::size_of::<f32>(),
+        Value::Decimal(_) => mem::size_of::<Decimal>(),
+        Value::String(s) => s.len(),
+        Value::Boolean(_) => mem::size_of::<bool>(),
+        Value::DateTime(_) => mem::size_of::<DateTime>(),
+        Value::Bytes(bytes) => bytes.len(),
+        Value::Array(elements) => elements.iter().map(value_size).sum(),
+        Value::Map(entries) => entries.iter().map(|(key, value)| key.len() + value_size(value)).sum(),
+    }
+}
@@ -1,6 +1,26 @@
 pub mod ast;
+pub mod builtins;
+pub mod coverage;
+pub mod cst;
+pub mod date;
+pub mod debugger;
+pub mod diagnostic;
+pub mod error;
+pub mod events;
+pub mod incremental;
 pub mod intr;
 pub mod lexer;
+pub mod manifest;
+pub mod memory;
+pub mod options;
 pub mod parser;
+pub mod postmortem;
+pub mod profile;
+pub mod preprocessor;
+pub mod program;
+pub mod project;
 pub mod symbol;
-pub mod token;
\ No newline at end of file
+pub mod token;
+pub mod trace;
+pub mod value;
+pub mod warning;
\ No newline at end of file
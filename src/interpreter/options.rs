@@ -0,0 +1,144 @@
+use super::manifest::Backend;
+
+use serde::Deserialize;
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::path::PathBuf;
+
+pub const CONFIG_FILE: &str = ".rustinterp.toml";
+
+// Defaults for the command line, read from .rustinterp.toml in the current directory or,
+// failing that, the home directory. Anything left out keeps the built-in default, and a
+// flag given on the command line wins over the file.
+//
+//     strict = false
+//     backend = "tree"
+//     color = "never"
+//     modules = ["math", "string"]
+//     case_sensitive_keywords = true
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    // false lets plain assignments declare variables, like --implicit-vars
+    pub strict: Option<bool>,
+    pub backend: Option<Backend>,
+    pub color: Option<ColorChoice>,
+    // The builtin modules scripts may use, like --modules
+    pub modules: Option<Vec<String>>,
+    // Keywords only count when written in capitals - print becomes an ordinary name
+    pub case_sensitive_keywords: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    // Only when stderr is a terminal
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    path: PathBuf,
+    message: String,
+}
+
+impl ConfigError {
+    pub fn new(path: &Path, message: String) -> ConfigError {
+        ConfigError {
+            path: path.to_path_buf(),
+            message: message,
+        }
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid {} - {}", self.path.display(), self.message)
+    }
+}
+
+impl ColorChoice {
+    pub fn from_name(name: &str) -> Option<ColorChoice> {
+        match name {
+            "auto" => Some(ColorChoice::Auto),
+            "always" => Some(ColorChoice::Always),
+            "never" => Some(ColorChoice::Never),
+            _ => None
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            ColorChoice::Auto => io::stderr().is_terminal(),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+impl Config {
+    pub fn parse(path: &Path, text: &str) -> Result<Config, ConfigError> {
+        toml::from_str(text).map_err(|err| ConfigError::new(path, err.to_string()))
+    }
+
+    // The first config file found, or an empty config when there is none
+    pub fn load() -> Result<Config, ConfigError> {
+        let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"));
+        let mut candidates = vec![PathBuf::from(CONFIG_FILE)];
+        if let Some(home) = home {
+            candidates.push(Path::new(&home).join(CONFIG_FILE));
+        }
+
+        for path in candidates {
+            match fs::read_to_string(&path) {
+                Ok(text) => return Config::parse(&path, &text),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(ConfigError::new(&path, format!("Unable to read file - {}", err)))
+            }
+        }
+
+        Ok(Config::default())
+    }
+
+    // Settings in other take the place of these
+    pub fn merge(self, other: Config) -> Config {
+        Config {
+            strict: other.strict.or(self.strict),
+            backend: other.backend.or(self.backend),
+            color: other.color.or(self.color),
+            modules: other.modules.or(self.modules),
+            case_sensitive_keywords: other.case_sensitive_keywords.or(self.case_sensitive_keywords),
+        }
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict.unwrap_or(true)
+    }
+
+    pub fn get_backend(&self) -> Backend {
+        self.backend.unwrap_or(Backend::Tree)
+    }
+
+    pub fn get_color(&self) -> ColorChoice {
+        self.color.unwrap_or(ColorChoice::Auto)
+    }
+
+    pub fn get_modules(&self) -> Option<&[String]> {
+        self.modules.as_deref()
+    }
+
+    pub fn has_case_sensitive_keywords(&self) -> bool {
+        self.case_sensitive_keywords.unwrap_or(false)
+    }
+}
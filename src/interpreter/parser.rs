@@ -1,14 +1,149 @@
 use super::ast;
+use super::cst::SyntaxKind;
 
+mod macros;
+
+use super::error::ParseError;
+use super::error::ParseErrorKind;
+use super::error::Span;
 use super::lexer::Lexer;
+use super::warning::Warning;
+use super::warning::WarningKind;
+use super::warning::WarningSet;
 
 use super::token::Token;
 use super::token::TokenType;
 
+use macros::Macro;
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::rc::Rc;
+
+struct InfixOperator {
+    token_type: TokenType,
+    operator: ast::Operator,
+    precedence: u8,
+    right_associative: bool,
+}
+
+// Every binary operator, with how tightly it binds - higher binds first. Adding an
+// operator is a row here plus its evaluation in the interpreter.
+static INFIX_OPERATORS: &[InfixOperator] = &[
+    InfixOperator { token_type: TokenType::PLUS, operator: ast::Operator::Plus, precedence: 10, right_associative: false },
+    InfixOperator { token_type: TokenType::MINUS, operator: ast::Operator::Minus, precedence: 10, right_associative: false },
+    InfixOperator { token_type: TokenType::ASTERISK, operator: ast::Operator::Times, precedence: 20, right_associative: false },
+    InfixOperator { token_type: TokenType::SLASH, operator: ast::Operator::Divides, precedence: 20, right_associative: false },
+    // 2 ^ 3 ^ 2 is 2 ^ 9, and -2 ^ 2 is -(2 ^ 2)
+    InfixOperator { token_type: TokenType::CARET, operator: ast::Operator::Power, precedence: 40, right_associative: true },
+];
+
+static PREFIX_OPERATORS: &[(TokenType, ast::Operator)] = &[
+    (TokenType::PLUS, ast::Operator::Plus),
+    (TokenType::MINUS, ast::Operator::Minus),
+];
+
+// Prefix operators bind tighter than any infix one but ^, so -a * b is (-a) * b
+const PREFIX_PRECEDENCE: u8 = 30;
+
+// What a declared operator can be made of. It can't be the start of an operator the language
+// already has, or that operator could no longer be written.
+const OPERATOR_CHARACTERS: &str = "~!@#$%&|?.:<>=+-*/^";
+const BUILTIN_OPERATORS: &[&str] = &["+", "-", "*", "/", "^", "=", "==", "!=", "<", "<=", ">", ">=", ":"];
+
+// An operator declared by the script - `OPERATOR ".." concat 10;` makes a .. b a call to
+// concat(a, b), binding like + and -. It takes effect from the declaration on.
+#[derive(Debug, Clone)]
+struct UserOperator {
+    function: String,
+    precedence: u8,
+}
+
+// Parsing and running both recurse once per level of nesting, so past this a program is
+// turned away rather than left to overflow the stack. A debug build takes about 34KB of stack
+// per block and 10KB per level of an expression, so this fits within the 2MB a spawned thread
+// gets - raise it with set_limits on a thread with more.
+const MAX_NESTING: usize = 32;
+
+// A chain like 1 + 1 + ... + 1 is parsed and run in a loop, but the passes over the tree
+// still recurse once per operator in it - at around 1KB a level, far cheaper than nesting
+const MAX_CHAIN: usize = 500;
+
+// How much the parser takes on before giving up on a program - worth lowering when the
+// source comes from somewhere that can't be trusted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParseLimits {
+    // Blocks inside one another, or levels of a single expression
+    pub max_nesting: usize,
+    // Tokens read, counting those spliced in by macros - None for no limit
+    pub max_tokens: Option<usize>,
+}
+
+impl Default for ParseLimits {
+    fn default() -> ParseLimits {
+        ParseLimits {
+            max_nesting: MAX_NESTING,
+            max_tokens: None,
+        }
+    }
+}
+
+fn infix_operator(token_type: &TokenType) -> Option<&'static InfixOperator> {
+    INFIX_OPERATORS.iter().find(|infix| &infix.token_type == token_type)
+}
+
+fn prefix_operator(token_type: &TokenType) -> Option<ast::Operator> {
+    PREFIX_OPERATORS.iter()
+        .find(|(prefix, _)| prefix == token_type)
+        .map(|(_, operator)| operator.clone())
+}
+
+// Parses what follows an embedder's statement keyword - see Parser::register_statement
+pub type StatementPlugin = Rc<dyn Fn(&mut Parser) -> Result<ast::HostStatement, ParseError>>;
+
 pub struct Parser<'a> {
     lexer: &'a mut Lexer<'a>,
     current_token: Token,
     next_token: Token,
+    // When recovering, a bad statement is recorded and skipped rather than ending the parse
+    recover: bool,
+    errors: Vec<ParseError>,
+    warnings: Vec<Warning>,
+    enabled_warnings: WarningSet,
+    macros: HashMap<String, Macro>,
+    operators: HashMap<String, UserOperator>,
+    // Tokens from macro expansions, read before going back to the lexer
+    pending: VecDeque<Token>,
+    expansions: usize,
+    plugins: HashMap<String, StatementPlugin>,
+    // Labels of the loops around the statement being parsed, innermost last
+    loops: Vec<Option<String>>,
+    // How many blocks the statement being parsed is inside - FUNCTION is only allowed at 1
+    depth: usize,
+    // How deeply the expression being parsed is nested so far
+    nesting: usize,
+    // Operators chained to the left of the expression being parsed, outer chains included
+    chained: usize,
+    limits: ParseLimits,
+    tokens: usize,
+    // Where each node began and ended in the source, kept only for a lossless parse
+    syntax: Option<Vec<(SyntaxKind, Range<usize>)>>,
+    next_id: u32,
+}
+
+// Parses a whole source string into a syntax tree
+pub fn parse_str(source: &str) -> Result<ast::AbstractSyntaxTree, ParseError> {
+    let mut lexer = Lexer::new(source.chars().peekable());
+    let mut parser = Parser::new(&mut lexer);
+    parser.parse()
+}
+
+// Like parse_str, but reports every independent problem in the source instead of the first
+pub fn parse_str_all(source: &str) -> Result<ast::AbstractSyntaxTree, Vec<ParseError>> {
+    let mut lexer = Lexer::new(source.chars().peekable());
+    let mut parser = Parser::new(&mut lexer);
+    parser.parse_all()
 }
 
 impl<'a> Parser<'a> {
@@ -18,80 +153,593 @@ impl<'a> Parser<'a> {
             lexer: lexer,
             current_token: Token::new(TokenType::ILLEGAL, String::from("")),
             next_token: Token::new(TokenType::ILLEGAL, String::from("")),
+            recover: false,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+            enabled_warnings: WarningSet::default(),
+            macros: HashMap::new(),
+            operators: HashMap::new(),
+            pending: VecDeque::new(),
+            expansions: 0,
+            plugins: HashMap::new(),
+            loops: Vec::new(),
+            depth: 0,
+            nesting: 0,
+            chained: 0,
+            limits: ParseLimits::default(),
+            tokens: 0,
+            syntax: None,
+            next_id: 1,
+        }
+    }
+
+    pub fn parse_all(&mut self) -> Result<ast::AbstractSyntaxTree, Vec<ParseError>> {
+        self.recover = true;
+        self.lexer.set_recovery(true);
+
+        let result = self.parse();
+
+        let mut errors: Vec<ParseError> = self.lexer.take_errors().into_iter()
+            .map(ParseError::from)
+            .collect();
+        errors.append(&mut self.errors);
+        let ast = match result {
+            Ok(ast) => Some(ast),
+            Err(err) => {
+                errors.push(err);
+                None
+            }
+        };
+
+        errors.sort_by_key(|err| (err.get_span().line, err.get_span().column));
+
+        self.recover = false;
+        self.lexer.set_recovery(false);
+
+        match ast {
+            Some(ast) if errors.is_empty() => Ok(ast),
+            _ => Err(errors)
         }
     }
 
-    pub fn parse(&mut self) -> ast::AbstractSyntaxTree {
+    pub fn parse(&mut self) -> Result<ast::AbstractSyntaxTree, ParseError> {
         // Process twice on first parse - this will ensure next and current are both set
-        self.process_next();
-        self.process_next();
+        self.process_next()?;
+        self.process_next()?;
+
+        Ok(ast::AbstractSyntaxTree::new(self.parse_program()?))
+    }
+
+    pub fn set_limits(&mut self, limits: ParseLimits) {
+        self.limits = limits;
+    }
+
+    pub fn get_limits(&self) -> ParseLimits {
+        self.limits
+    }
+
+    pub fn set_enabled_warnings(&mut self, enabled_warnings: WarningSet) {
+        self.enabled_warnings = enabled_warnings;
+    }
+
+    // Adds a statement keyword of the embedder's own. The plugin is called with the keyword
+    // already consumed and reads the rest of the statement, semicolon included, through
+    // get_current_token, advance, expect_token and parse_host_expression. Keywords are
+    // case-insensitive and only take effect where a statement starts, so a variable of the
+    // same name can still be assigned.
+    pub fn register_statement<F>(&mut self, keyword: &str, plugin: F)
+        where F: Fn(&mut Parser) -> Result<ast::HostStatement, ParseError> + 'static {
+        self.plugins.insert(keyword.to_uppercase(), Rc::new(plugin));
+    }
+
+    pub fn get_current_token(&self) -> &Token {
+        &self.current_token
+    }
+
+    // Moves past the current token, handing it back
+    pub fn advance(&mut self) -> Result<Token, ParseError> {
+        let token = self.current_token.clone();
+        self.process_next()?;
+        Ok(token)
+    }
+
+    pub fn expect_token(&mut self, token_type: TokenType) -> Result<Token, ParseError> {
+        let token = self.current_token.clone();
+        self.match_token(token_type)?;
+        Ok(token)
+    }
+
+    pub fn parse_host_expression(&mut self) -> Result<ast::Expression, ParseError> {
+        self.parse_expression()
+    }
+
+    // An error at the current token, for plugins to report what they didn't expect
+    pub fn host_error(&self, message: String) -> ParseError {
+        self.error(ParseErrorKind::InvalidStatement, message)
+    }
+
+    // Warnings found by the last parse - they don't stop the program from running
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    pub fn parse_single_expression(&mut self) -> Result<ast::Expression, ParseError> {
+        self.process_next()?;
+        self.process_next()?;
+
+        let expression = self.parse_expression()?;
+        self.match_token(TokenType::EOF)?;
+        Ok(expression)
+    }
+
+    // For incremental reparsing - reads the first tokens, as parse does, before the caller
+    // pulls statements one at a time with parse_next_statement
+    pub(crate) fn begin(&mut self) -> Result<(), ParseError> {
+        self.process_next()?;
+        self.process_next()
+    }
+
+    // The next top-level statement with the line it starts on, or None at the end of the source
+    pub(crate) fn parse_next_statement(&mut self) -> Result<Option<(ast::Statement, usize)>, ParseError> {
+        let mut statements: Vec<ast::Statement> = Vec::new();
+        let mut lines: Vec<usize> = Vec::new();
+
+        // Macro definitions don't produce a statement, so keep going until something does
+        while statements.is_empty() && !self.check_token(&TokenType::EOF) {
+            self.parse_block_statement(&mut statements, &mut lines)?;
+        }
+
+        Ok(statements.pop().zip(lines.pop()))
+    }
+
+    // Parsing part of a tree - ids carry on from the rest of it rather than starting over
+    pub(crate) fn set_next_id(&mut self, next_id: u32) {
+        self.next_id = next_id;
+    }
+
+    pub(crate) fn get_next_id(&self) -> u32 {
+        self.next_id
+    }
+
+    // Macros and operators change how the rest of the source parses
+    pub(crate) fn defines_syntax(&self) -> bool {
+        !self.macros.is_empty() || !self.operators.is_empty()
+    }
+
+    pub(crate) fn get_operator_symbols(&self) -> Vec<String> {
+        self.operators.keys().cloned().collect()
+    }
+
+    // For a lossless parse - see cst::parse_lossless
+    pub(crate) fn record_syntax(&mut self) {
+        self.syntax = Some(Vec::new());
+    }
+
+    pub(crate) fn take_syntax(&mut self) -> Vec<(SyntaxKind, Range<usize>)> {
+        self.syntax.take().unwrap_or_default()
+    }
 
-        ast::AbstractSyntaxTree::new(self.parse_program())
+    // Notes the bytes a node covered, from its first token up to the current one. Nothing
+    // a macro expanded to is in the source, so those nodes are left out.
+    fn record_node(&mut self, kind: SyntaxKind, start: Span, expanded: bool) {
+        let end = self.current_token.get_span().offset;
+        if let Some(syntax) = self.syntax.as_mut() {
+            if !expanded {
+                syntax.push((kind, start.offset..end));
+            }
+        }
     }
 
-    fn parse_program(&mut self) -> ast::Block {
+    fn parse_program(&mut self) -> Result<ast::Block, ParseError> {
         let mut statements: Vec<ast::Statement> = Vec::new();
+        let mut lines: Vec<usize> = Vec::new();
 
         while !self.check_token(&TokenType::EOF) {
-            statements.push(self.parse_statement());
+            self.parse_block_statement(&mut statements, &mut lines)?;
+        }
+
+        Ok(ast::Block::with_lines(statements, lines))
+    }
+
+    // Parses the next statement of a block - while recovering, a statement that fails is
+    // recorded and skipped so the rest of the block still gets checked
+    fn parse_block_statement(&mut self, statements: &mut Vec<ast::Statement>, lines: &mut Vec<usize>) -> Result<(), ParseError> {
+        let start = self.current_token.get_span();
+        let line = self.current_token.get_line();
+        // Spliced-in tokens wait in pending until the expansion has been read
+        let expanded = !self.pending.is_empty();
+
+        // Macro definitions and uses don't become statements themselves
+        let result = if self.check_token(&TokenType::MACRO) {
+            self.parse_macro().map(|_| None)
+        } else if self.check_token(&TokenType::OPERATOR) {
+            self.parse_operator().map(|_| None)
+        } else if self.is_macro_call() {
+            self.expand_macro().map(|_| None)
+        } else if self.depth >= self.limits.max_nesting {
+            Err(self.error(ParseErrorKind::TooDeep, format!("Blocks are nested more than {} deep", self.limits.max_nesting)))
+        } else {
+            self.depth += 1;
+            let statement = self.parse_statement();
+            self.depth -= 1;
+            statement.map(Some)
+        };
+
+        match result {
+            Ok(Some(statement)) => {
+                self.record_node(SyntaxKind::Statement, start, expanded);
+                lines.push(line);
+                statements.push(statement);
+                Ok(())
+            },
+            Ok(None) => Ok(()),
+            // Carrying on past a limit would only hit it again, once for every level below
+            // or every token after
+            Err(err) if self.recover && !matches!(err.get_kind(), ParseErrorKind::TooDeep | ParseErrorKind::TooLong) => {
+                // A bad token was already reported by the lexer - what the parser makes of it isn't news
+                if self.current_token.get_token_type() != &TokenType::ILLEGAL {
+                    self.errors.push(err);
+                }
+
+                self.synchronize(start)
+            },
+            Err(err) => Err(err)
+        }
+    }
+
+    // Skips to where the next statement should begin - just past a semicolon, or at a keyword
+    // that starts or ends a statement
+    fn synchronize(&mut self, start: Span) -> Result<(), ParseError> {
+        // Always move forward, or a token no statement can start with would be hit forever
+        if self.current_token.get_span() == start {
+            self.process_next()?;
         }
 
-        ast::Block::new(statements)
+        loop {
+            match self.current_token.get_token_type() {
+                TokenType::SEMICOLON => return self.process_next(),
+                TokenType::EOF | TokenType::END | TokenType::ELSE | TokenType::ELSEIF |
+                TokenType::LET | TokenType::PRINT | TokenType::EPRINT | TokenType::IF |
+                TokenType::WHILE | TokenType::BREAKPOINT | TokenType::INSPECT | TokenType::MACRO |
+                TokenType::BREAK | TokenType::CONTINUE | TokenType::FUNCTION | TokenType::MEMO | TokenType::RETURN | TokenType::FOR |
+                TokenType::GLOBAL | TokenType::LOCAL | TokenType::OPERATOR => return Ok(()),
+                _ => self.process_next()?
+            }
+        }
     }
 
-    fn parse_statement(&mut self) -> ast::Statement {
+    fn parse_statement(&mut self) -> Result<ast::Statement, ParseError> {
         match self.current_token.get_token_type() {
             TokenType::PRINT => {
-                self.process_next();
-                let statement = ast::Statement::Print(self.parse_expression());
-                self.match_token(TokenType::SEMICOLON);
-                statement
+                self.process_next()?;
+                let statement = ast::Statement::Print(self.parse_expression_list()?);
+                self.match_token(TokenType::SEMICOLON)?;
+                Ok(statement)
+            },
+            TokenType::EPRINT => {
+                self.process_next()?;
+                let statement = ast::Statement::EPrint(self.parse_expression_list()?);
+                self.match_token(TokenType::SEMICOLON)?;
+                Ok(statement)
             },
             TokenType::LET => {
-                self.process_next();
+                self.process_next()?;
                 // The next token should be an IDENT token - add it to variables
                 // If IDENT isn't next, the parser will error out anyways
-                let ident = ast::Ident::new(String::from(self.current_token.get_token_text()));
-                self.match_token(TokenType::IDENT);
-                self.match_token(TokenType::EQ);
-                let statement = ast::Statement::Let(ident, self.parse_expression());
-                self.match_token(TokenType::SEMICOLON);
-                statement
+                let ident = self.ident(String::from(self.current_token.get_token_text()));
+                self.match_token(TokenType::IDENT)?;
+                self.match_token(TokenType::EQ)?;
+                let statement = ast::Statement::Let(ident, self.parse_expression()?);
+                self.match_token(TokenType::SEMICOLON)?;
+                Ok(statement)
+            },
+            TokenType::IDENT if self.next_token.get_token_type() == &TokenType::COLON => {
+                let label = String::from(self.current_token.get_token_text());
+                if self.loops.contains(&Some(label.clone())) {
+                    return Err(self.error(ParseErrorKind::InvalidStatement, format!("Loop label {} is already used by a loop this one is inside", label)));
+                }
+
+                self.process_next()?;
+                self.match_token(TokenType::COLON)?;
+                if !self.check_token(&TokenType::WHILE) && !self.check_token(&TokenType::FOR) {
+                    return Err(self.error(ParseErrorKind::InvalidStatement, format!("Only loops can be labeled - expected WHILE or FOR after {}:", label)));
+                }
+
+                let statement = self.parse_loop(Some(label.clone()))?;
+                Ok(ast::Statement::Labeled(self.ident(label), Box::new(statement)))
+            },
+            TokenType::IDENT if self.is_host_statement() => {
+                let plugin = Rc::clone(&self.plugins[&self.current_token.get_token_text().to_uppercase()]);
+                self.process_next()?;
+                Ok(ast::Statement::Host(plugin(self)?))
+            },
+            TokenType::IDENT if self.next_token.get_token_type() == &TokenType::LPAREN => {
+                let statement = ast::Statement::Call(self.parse_call()?);
+                self.match_token(TokenType::SEMICOLON)?;
+                Ok(statement)
             },
             TokenType::IDENT => {
-                let ident = ast::Ident::new(String::from(self.current_token.get_token_text()));
-                self.match_token(TokenType::IDENT);
-                self.match_token(TokenType::EQ);
-                let statement = ast::Statement::Assignment(ident, self.parse_expression());
-                self.match_token(TokenType::SEMICOLON);
-                statement
+                let ident = self.ident(String::from(self.current_token.get_token_text()));
+                self.match_token(TokenType::IDENT)?;
+                let mut target = ast::LValue::Ident(ident);
+                while self.check_token(&TokenType::LBRACKET) {
+                    self.process_next()?;
+                    let index = self.parse_expression()?;
+                    self.match_token(TokenType::RBRACKET)?;
+                    target = ast::LValue::Index(Box::new(target), index);
+                }
+
+                self.match_token(TokenType::EQ)?;
+                let statement = ast::Statement::Assignment(target, self.parse_expression()?);
+                self.match_token(TokenType::SEMICOLON)?;
+                Ok(statement)
             }
             TokenType::IF => {
-                ast::Statement::If(self.parse_if())
+                Ok(ast::Statement::If(self.parse_if()?))
             }
-            TokenType::WHILE => {
-                self.process_next();
+            TokenType::WHILE | TokenType::FOR => self.parse_loop(None),
+            TokenType::FUNCTION | TokenType::MEMO => self.parse_function(),
+            // Outside of a function, RETURN ends the program
+            TokenType::RETURN => {
+                self.process_next()?;
+                let mut expression = None;
+                if !self.check_token(&TokenType::SEMICOLON) {
+                    expression = Some(self.parse_expression()?);
+                }
 
-                let condition = self.parse_condition();
-                self.match_token(TokenType::THEN);
+                self.match_token(TokenType::SEMICOLON)?;
+                Ok(ast::Statement::Return(expression))
+            },
+            TokenType::BREAK | TokenType::CONTINUE => {
+                let is_break = self.check_token(&TokenType::BREAK);
+                let keyword = if is_break { "BREAK" } else { "CONTINUE" };
+                let span = self.current_token.get_span();
+                self.process_next()?;
+
+                let mut label = None;
+                if self.check_token(&TokenType::IDENT) {
+                    let name = String::from(self.current_token.get_token_text());
+                    if !self.loops.contains(&Some(name.clone())) {
+                        return Err(self.error(ParseErrorKind::InvalidStatement, format!("{} names {}, which isn't the label of a loop it's inside", keyword, name)));
+                    }
+
+                    label = Some(self.ident(name));
+                    self.process_next()?;
+                } else if self.loops.is_empty() {
+                    return Err(ParseError::new(ParseErrorKind::InvalidStatement, format!("{} used outside of a loop", keyword), span));
+                }
 
-                let mut statements: Vec<ast::Statement> = Vec::new();
-                while !self.check_token(&TokenType::END) {
-                    statements.push(self.parse_statement());
+                self.match_token(TokenType::SEMICOLON)?;
+                Ok(if is_break { ast::Statement::Break(label) } else { ast::Statement::Continue(label) })
+            },
+            TokenType::BREAKPOINT => {
+                self.process_next()?;
+                self.match_token(TokenType::SEMICOLON)?;
+                Ok(ast::Statement::Breakpoint)
+            },
+            TokenType::INSPECT => {
+                self.process_next()?;
+                let mut ident = None;
+                if self.check_token(&TokenType::IDENT) {
+                    ident = Some(self.ident(String::from(self.current_token.get_token_text())));
+                    self.process_next()?;
                 }
 
-                self.match_token(TokenType::END);
-                let block = ast::Block::new(statements);
+                self.match_token(TokenType::SEMICOLON)?;
+                Ok(ast::Statement::Inspect(ident))
+            },
+            TokenType::GLOBAL | TokenType::LOCAL => {
+                let is_global = self.check_token(&TokenType::GLOBAL);
+                self.process_next()?;
 
-                ast::Statement::While(condition, block)
+                let mut idents = Vec::new();
+                loop {
+                    idents.push(self.ident(String::from(self.current_token.get_token_text())));
+                    self.match_token(TokenType::IDENT)?;
+                    if !self.check_token(&TokenType::COMMA) {
+                        break;
+                    }
+                    self.process_next()?;
+                }
 
+                self.match_token(TokenType::SEMICOLON)?;
+                Ok(if is_global { ast::Statement::Global(idents) } else { ast::Statement::Local(idents) })
             },
-            _ => panic!("Invalid statement found - {:?}", self.current_token.get_token_type())
+            _ => Err(self.error(ParseErrorKind::InvalidStatement, format!("Invalid statement found - {:?}", self.current_token.get_token_type())))
+        }
+    }
+
+    fn parse_function(&mut self) -> Result<ast::Statement, ParseError> {
+        if self.depth > 1 {
+            return Err(self.error(ParseErrorKind::InvalidStatement, String::from("FUNCTION can only be defined at the top level of a program")));
+        }
+
+        let memo = self.check_token(&TokenType::MEMO);
+        if memo {
+            self.process_next()?;
+        }
+
+        self.match_token(TokenType::FUNCTION)?;
+        let name = self.ident(String::from(self.current_token.get_token_text()));
+        self.match_token(TokenType::IDENT)?;
+        self.match_token(TokenType::LPAREN)?;
+
+        let mut parameters: Vec<ast::Ident> = Vec::new();
+        let mut defaults: Vec<Option<ast::Expression>> = Vec::new();
+        while !self.check_token(&TokenType::RPAREN) {
+            if !parameters.is_empty() {
+                self.match_token(TokenType::COMMA)?;
+            }
+
+            let parameter = String::from(self.current_token.get_token_text());
+            if parameters.iter().any(|existing| existing.symbol == parameter) {
+                return Err(self.error(ParseErrorKind::InvalidStatement, format!("Parameter {} is given more than once", parameter)));
+            }
+
+            parameters.push(self.ident(parameter.clone()));
+            self.match_token(TokenType::IDENT)?;
+
+            // Leaving an argument out leaves out every one after it, so they need defaults too
+            if self.check_token(&TokenType::EQ) {
+                self.process_next()?;
+                defaults.push(Some(self.parse_expression()?));
+            } else if defaults.last().is_some_and(Option::is_some) {
+                return Err(self.error(ParseErrorKind::InvalidStatement, format!("Parameter {} needs a default, since the one before it has one", parameter)));
+            } else {
+                defaults.push(None);
+            }
+        }
+        self.match_token(TokenType::RPAREN)?;
+
+        let mut statements: Vec<ast::Statement> = Vec::new();
+        let mut lines: Vec<usize> = Vec::new();
+        while !self.check_token(&TokenType::END) && !self.check_token(&TokenType::EOF) {
+            self.parse_block_statement(&mut statements, &mut lines)?;
+        }
+
+        self.match_token(TokenType::END)?;
+        let body = ast::Block::with_lines(statements, lines);
+
+        let mut function = ast::FunctionDef::new(name, parameters, body);
+        function.defaults = defaults;
+        function.memo = memo;
+        Ok(ast::Statement::FunctionDef(function))
+    }
+
+    fn parse_operator(&mut self) -> Result<(), ParseError> {
+        let start = self.current_token.get_span();
+        let expanded = !self.pending.is_empty();
+        if self.depth > 0 {
+            return Err(self.error(ParseErrorKind::InvalidStatement, String::from("OPERATOR can only be declared at the top level of a program")));
+        }
+
+        self.match_token(TokenType::OPERATOR)?;
+        let symbol = String::from(self.current_token.get_token_text());
+        self.match_token(TokenType::STRING)?;
+        let function = String::from(self.current_token.get_token_text());
+        self.match_token(TokenType::IDENT)?;
+
+        let problem = if symbol.is_empty() || !symbol.chars().all(|c| OPERATOR_CHARACTERS.contains(c)) {
+            Some(format!("Operator {:?} can only be made of {}", symbol, OPERATOR_CHARACTERS))
+        } else if BUILTIN_OPERATORS.iter().any(|builtin| builtin.starts_with(symbol.as_str())) {
+            Some(format!("Operator {:?} is already part of the language", symbol))
+        } else if self.operators.contains_key(&symbol) {
+            Some(format!("Operator {:?} is declared more than once", symbol))
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            return Err(self.error(ParseErrorKind::InvalidStatement, problem));
+        }
+
+        let precedence = match self.current_token.get_token_text().parse::<u8>() {
+            Ok(precedence) if self.check_token(&TokenType::NUMBER) && precedence > 0 => precedence,
+            _ => return Err(self.error(ParseErrorKind::InvalidStatement, format!("Operator {:?} needs a precedence from 1 to 255 - + and - are 10, * and / are 20", symbol)))
+        };
+
+        // The token after the semicolon is the first the lexer reads knowing the symbol
+        self.lexer.add_operator(symbol.clone());
+        self.process_next()?;
+        self.match_token(TokenType::SEMICOLON)?;
+        self.record_node(SyntaxKind::OperatorDefinition, start, expanded);
+
+        self.operators.insert(symbol, UserOperator {
+            function: function,
+            precedence: precedence,
+        });
+
+        Ok(())
+    }
+
+    fn parse_loop(&mut self, label: Option<String>) -> Result<ast::Statement, ParseError> {
+        // Kept while the body is parsed so BREAK and CONTINUE can be checked against it
+        self.loops.push(label);
+        let result = if self.check_token(&TokenType::FOR) {
+            self.parse_for_loop()
+        } else {
+            self.parse_while_loop()
+        };
+        self.loops.pop();
+        result
+    }
+
+    fn parse_for_loop(&mut self) -> Result<ast::Statement, ParseError> {
+        self.match_token(TokenType::FOR)?;
+
+        let variable = self.ident(String::from(self.current_token.get_token_text()));
+        self.match_token(TokenType::IDENT)?;
+        self.match_token(TokenType::EQ)?;
+        let start = self.parse_expression()?;
+        self.match_token(TokenType::TO)?;
+        let end = self.parse_expression()?;
+
+        let mut step = None;
+        if self.check_token(&TokenType::STEP) {
+            self.process_next()?;
+            step = Some(self.parse_expression()?);
         }
+
+        let mut statements: Vec<ast::Statement> = Vec::new();
+        let mut lines: Vec<usize> = Vec::new();
+        while !self.check_token(&TokenType::END) && !self.check_token(&TokenType::EOF) {
+            self.parse_block_statement(&mut statements, &mut lines)?;
+        }
+
+        self.match_token(TokenType::END)?;
+        let body = ast::Block::with_lines(statements, lines);
+
+        Ok(ast::Statement::For(ast::ForLoop::new(variable, start, end, step, body)))
+    }
+
+    fn parse_while_loop(&mut self) -> Result<ast::Statement, ParseError> {
+        self.match_token(TokenType::WHILE)?;
+
+        let condition = self.parse_condition()?;
+        self.match_token(TokenType::THEN)?;
+
+        let mut statements: Vec<ast::Statement> = Vec::new();
+        let mut lines: Vec<usize> = Vec::new();
+        while !self.check_token(&TokenType::END) && !self.check_token(&TokenType::EOF) {
+            self.parse_block_statement(&mut statements, &mut lines)?;
+        }
+
+        self.match_token(TokenType::END)?;
+        let block = ast::Block::with_lines(statements, lines);
+
+        Ok(ast::Statement::While(condition, block))
+    }
+
+    fn parse_condition(&mut self) -> Result<ast::Condition, ParseError> {
+        let mut condition = self.parse_and_condition()?;
+        while self.check_token(&TokenType::OR) {
+            self.process_next()?;
+            condition = ast::Condition::Or(Box::new(condition), Box::new(self.parse_and_condition()?));
+        }
+
+        Ok(condition)
+    }
+
+    fn parse_and_condition(&mut self) -> Result<ast::Condition, ParseError> {
+        let mut condition = self.parse_not_condition()?;
+        while self.check_token(&TokenType::AND) {
+            self.process_next()?;
+            condition = ast::Condition::And(Box::new(condition), Box::new(self.parse_not_condition()?));
+        }
+
+        Ok(condition)
+    }
+
+    fn parse_not_condition(&mut self) -> Result<ast::Condition, ParseError> {
+        if self.check_token(&TokenType::NOT) {
+            self.process_next()?;
+            return Ok(ast::Condition::Not(Box::new(self.parse_not_condition()?)));
+        }
+
+        self.parse_comparison()
     }
 
-    fn parse_condition(&mut self) -> ast::Condition {
-        let left_expression = self.parse_expression();
+    fn parse_comparison(&mut self) -> Result<ast::Condition, ParseError> {
+        let left_expression = self.parse_expression()?;
         let comparator = match self.current_token.get_token_type() {
             TokenType::EQEQ => ast::Comparator::Equal,
             TokenType::NOTEQ => ast::Comparator::NotEqual,
@@ -99,155 +747,339 @@ impl<'a> Parser<'a> {
             TokenType::GTEQ => ast::Comparator::GreaterThanOrEqual,
             TokenType::LT => ast::Comparator::LessThan,
             TokenType::LTEQ => ast::Comparator::LessThanOrEqual,
-            _ => panic!("Expected comparison operator to evaluate to bool")
+            // Classic BASIC compares with a single =, so read it that way but point it out
+            TokenType::EQ => {
+                if self.enabled_warnings.is_enabled(WarningKind::AssignmentInCondition) {
+                    self.warnings.push(Warning::new(
+                        WarningKind::AssignmentInCondition,
+                        String::from("Assignment-looking = in condition - did you mean ==?"),
+                        self.current_token.get_span()));
+                }
+                ast::Comparator::Equal
+            },
+            // A condition on its own, as in IF done THEN, holds when it is TRUE
+            TokenType::THEN | TokenType::AND | TokenType::OR => return Ok(ast::Condition::new(left_expression, ast::Comparator::Equal, self.literal(ast::Literal::Boolean(true)))),
+            _ => return Err(self.error(ParseErrorKind::UnexpectedToken, String::from("Expected comparison operator to evaluate to bool")))
         };
 
-        self.process_next();
-        let right_expression = self.parse_expression();
+        self.process_next()?;
+        let right_expression = self.parse_expression()?;
+
+        Ok(ast::Condition::new(left_expression, comparator, right_expression))
+    }
 
-        // TODO multiple sequential conditions
+    fn parse_expression_list(&mut self) -> Result<Vec<ast::Expression>, ParseError> {
+        let mut expressions = vec![self.parse_expression()?];
+        while self.check_token(&TokenType::COMMA) {
+            self.process_next()?;
+            expressions.push(self.parse_expression()?);
+        }
 
-        ast::Condition::new(left_expression, comparator, right_expression)
+        Ok(expressions)
     }
 
-    fn parse_expression(&mut self) -> ast::Expression {
-        match self.current_token.get_token_type() {
-            TokenType::STRING => {
-                let literal = ast::Literal::String(String::from(self.current_token.get_token_text()));
-                let expression = ast::Expression::Literal(literal);
-                self.process_next();
-                expression
-            },
-            _ => {
-                let mut term = self.parse_term();
-                while self.check_token(&TokenType::PLUS) || self.check_token(&TokenType::MINUS) {
-                    let operator = match self.current_token.get_token_type() {
-                        TokenType::PLUS => ast::Operator::Plus,
-                        TokenType::MINUS => ast::Operator::Minus,
-                        _ => panic!("Invalid operator found")
-                    };
-
-                    self.process_next();
-                    let first_term = term;
-                    let second_term = self.parse_term();
-                    let binary_op = ast::BinaryOp::new(first_term, operator, second_term);
-                    term = ast::Expression::BinaryOp(Box::new(binary_op));
-                }
+    fn parse_expression(&mut self) -> Result<ast::Expression, ParseError> {
+        self.parse_operators(0)
+    }
 
-                term
-            }
-        }
+    // Pratt parsing - keeps taking infix operators that bind at least as tightly as
+    // min_precedence, so the tables below decide how an expression groups
+    fn parse_operators(&mut self, min_precedence: u8) -> Result<ast::Expression, ParseError> {
+        let outer = (self.nesting, self.chained);
+        let result = self.parse_nested_operators(min_precedence);
+        (self.nesting, self.chained) = outer;
+        result
     }
 
-    fn parse_term(&mut self) -> ast::Expression {
-        let mut unary = self.parse_unary();
-        while self.check_token(&TokenType::SLASH) || self.check_token(&TokenType::ASTERISK) {
-            let operator = match self.current_token.get_token_type() {
-                TokenType::ASTERISK => ast::Operator::Times,
-                TokenType::SLASH => ast::Operator::Divides,
-                _ => panic!("Invalid operator found")
+    fn parse_nested_operators(&mut self, min_precedence: u8) -> Result<ast::Expression, ParseError> {
+        self.nest()?;
+        let mut left = self.parse_prefix()?;
+
+        loop {
+            let infix = infix_operator(self.current_token.get_token_type());
+            let declared = match self.current_token.get_token_type() {
+                TokenType::SYMBOL => self.operators.get(self.current_token.get_token_text()).cloned(),
+                _ => None
+            };
+            let (precedence, right_associative) = match (infix, &declared) {
+                (Some(infix), _) => (infix.precedence, infix.right_associative),
+                // Declared operators all group to the left
+                (None, Some(declared)) => (declared.precedence, false),
+                (None, None) => break
+            };
+            if precedence < min_precedence {
+                break;
+            }
+
+            // Each operator taken wraps everything to its left one level deeper
+            self.chained += 1;
+            if self.chained > MAX_CHAIN {
+                return Err(self.error(ParseErrorKind::TooDeep, format!("Expression chains more than {} operators", MAX_CHAIN)));
+            }
+            self.process_next()?;
+            // A left associative operator stops the right side at another of its own level
+            let next_precedence = if right_associative { precedence } else { precedence.saturating_add(1) };
+            let right = self.parse_operators(next_precedence)?;
+            left = match (declared, infix) {
+                // A declared operator is a call to its function with the two sides
+                (Some(declared), _) => ast::Expression::Call(ast::Call::new(self.ident(declared.function), vec![left, right])),
+                (None, Some(infix)) => ast::Expression::BinaryOp(Box::new(ast::BinaryOp::new(left, infix.operator.clone(), right))),
+                (None, None) => break
             };
+        }
+
+        Ok(left)
+    }
 
-            self.process_next();
-            let first_unary = unary;
-            let second_unary = self.parse_unary();
-            let binary_op = ast::BinaryOp::new(first_unary, operator, second_unary);
-            unary = ast::Expression::BinaryOp(Box::new(binary_op));
+    fn nest(&mut self) -> Result<(), ParseError> {
+        self.nesting += 1;
+        if self.nesting > self.limits.max_nesting {
+            return Err(self.error(ParseErrorKind::TooDeep, format!("Expression is nested more than {} deep", self.limits.max_nesting)));
         }
 
-        unary
+        Ok(())
     }
 
-    fn parse_unary(&mut self) -> ast::Expression {
-        match self.current_token.get_token_type() {
-            TokenType::PLUS => {
-                // Unary can start with + or - but it is not required
-                self.process_next();
-                let unary_op = ast::UnaryOp::new(ast::Operator::Plus, self.parse_primary());
-                ast::Expression::UnaryOp(Box::new(unary_op))
+    fn parse_prefix(&mut self) -> Result<ast::Expression, ParseError> {
+        match prefix_operator(self.current_token.get_token_type()) {
+            Some(operator) => {
+                self.process_next()?;
+                let term = self.parse_operators(PREFIX_PRECEDENCE)?;
+                Ok(ast::Expression::UnaryOp(Box::new(ast::UnaryOp::new(operator, term))))
             },
-            TokenType::MINUS => {
-                // Unary can start with + or - but it is not required
-                self.process_next();
-                let unary_op = ast::UnaryOp::new(ast::Operator::Minus, self.parse_primary());
-                ast::Expression::UnaryOp(Box::new(unary_op))
-            },
-            _ => self.parse_primary()
+            None => self.parse_primary()
         }
     }
 
-    fn parse_primary(&mut self) -> ast::Expression {
+    fn parse_primary(&mut self) -> Result<ast::Expression, ParseError> {
         let primary = match self.current_token.get_token_type() {
             TokenType::NUMBER => {
                 let number = String::from(self.current_token.get_token_text());
-                ast::Expression::Literal(ast::Literal::Number(number))
+                self.literal(ast::Literal::Number(number))
+            },
+            TokenType::DECIMAL => {
+                let number = String::from(self.current_token.get_token_text());
+                self.literal(ast::Literal::Decimal(number))
+            },
+            TokenType::NAN => self.literal(ast::Literal::Number(String::from("NaN"))),
+            TokenType::INF => self.literal(ast::Literal::Number(String::from("inf"))),
+            TokenType::STRING => self.literal(ast::Literal::String(String::from(self.current_token.get_token_text()))),
+            TokenType::TRUE => self.literal(ast::Literal::Boolean(true)),
+            TokenType::FALSE => self.literal(ast::Literal::Boolean(false)),
+            TokenType::IDENT if self.next_token.get_token_type() == &TokenType::LPAREN => {
+                let call = ast::Expression::Call(self.parse_call()?);
+                return self.parse_indexes(call);
             },
             TokenType::IDENT => {
-                let ident = ast::Ident::new(String::from(self.current_token.get_token_text()));
-                ast::Expression::Ident(ident)
+                let ident = self.ident(String::from(self.current_token.get_token_text()));
+                self.process_next()?;
+                return self.parse_indexes(ast::Expression::Ident(ident));
+            },
+            TokenType::LPAREN => {
+                self.process_next()?;
+                let expression = self.parse_expression()?;
+                self.match_token(TokenType::RPAREN)?;
+                return self.parse_indexes(expression);
+            },
+            TokenType::LBRACKET => {
+                let array = self.parse_array()?;
+                return self.parse_indexes(array);
             },
-            _ => panic!("Syntax Error! Expected number of ident")
+            TokenType::LBRACE => {
+                let map = self.parse_map()?;
+                return self.parse_indexes(map);
+            },
+            _ => return Err(self.error(ParseErrorKind::UnexpectedToken, format!("Expected number or ident, found {:?}", self.current_token.get_token_type())))
         };
 
-        self.process_next();
-        primary
+        self.process_next()?;
+        Ok(primary)
+    }
+
+    fn parse_array(&mut self) -> Result<ast::Expression, ParseError> {
+        self.match_token(TokenType::LBRACKET)?;
+
+        let mut elements = Vec::new();
+        if !self.check_token(&TokenType::RBRACKET) {
+            elements.push(self.parse_expression()?);
+            while self.check_token(&TokenType::COMMA) {
+                self.process_next()?;
+                elements.push(self.parse_expression()?);
+            }
+        }
+
+        self.match_token(TokenType::RBRACKET)?;
+        Ok(ast::Expression::Array(elements))
+    }
+
+    fn parse_map(&mut self) -> Result<ast::Expression, ParseError> {
+        self.match_token(TokenType::LBRACE)?;
+
+        let mut entries = Vec::new();
+        if !self.check_token(&TokenType::RBRACE) {
+            entries.push(self.parse_map_entry()?);
+            while self.check_token(&TokenType::COMMA) {
+                self.process_next()?;
+                entries.push(self.parse_map_entry()?);
+            }
+        }
+
+        self.match_token(TokenType::RBRACE)?;
+        Ok(ast::Expression::Map(entries))
+    }
+
+    fn parse_map_entry(&mut self) -> Result<(ast::Expression, ast::Expression), ParseError> {
+        let key = self.parse_expression()?;
+        self.match_token(TokenType::COLON)?;
+        Ok((key, self.parse_expression()?))
+    }
+
+    // Any number of [index] suffixes - s[0], or grid[1][2] for an array of arrays
+    fn parse_indexes(&mut self, mut target: ast::Expression) -> Result<ast::Expression, ParseError> {
+        while self.check_token(&TokenType::LBRACKET) {
+            self.process_next()?;
+            let index = self.parse_expression()?;
+            self.match_token(TokenType::RBRACKET)?;
+            target = ast::Expression::Index(Box::new(ast::Index::new(target, index)));
+        }
+
+        Ok(target)
+    }
+
+    fn parse_call(&mut self) -> Result<ast::Call, ParseError> {
+        let function = self.ident(String::from(self.current_token.get_token_text()));
+        self.match_token(TokenType::IDENT)?;
+        self.match_token(TokenType::LPAREN)?;
+
+        let mut arguments = Vec::new();
+        let mut names: Vec<Option<String>> = Vec::new();
+        while !self.check_token(&TokenType::RPAREN) {
+            if !arguments.is_empty() {
+                self.match_token(TokenType::COMMA)?;
+            }
+
+            // `name = value` gives the argument to a parameter by name
+            if self.check_token(&TokenType::IDENT) && self.next_token.get_token_type() == &TokenType::EQ {
+                names.push(Some(String::from(self.current_token.get_token_text())));
+                self.process_next()?;
+                self.process_next()?;
+            } else if names.last().is_some_and(Option::is_some) {
+                return Err(self.error(ParseErrorKind::InvalidStatement, format!("Argument {} to {} follows a named one, so it needs a name too", arguments.len() + 1, function.symbol)));
+            } else {
+                names.push(None);
+            }
+            arguments.push(self.parse_expression()?);
+        }
+
+        self.match_token(TokenType::RPAREN)?;
+        let mut call = ast::Call::new(function, arguments);
+        call.names = names;
+        Ok(call)
     }
 
-    fn parse_if(&mut self) -> ast::IfStatement {
-        let current_token_type = self.current_token.get_token_type().clone();
+    fn parse_if(&mut self) -> Result<ast::IfStatement, ParseError> {
+        let mut current_token_type = self.current_token.get_token_type().clone();
 
-        self.process_next();
+        // ELSE IF on one line is the same as ELSEIF - an IF on the next line starts a
+        // nested IF inside the ELSE
+        if current_token_type == TokenType::ELSE
+            && self.next_token.get_token_type() == &TokenType::IF
+            && self.next_token.get_span().line == self.current_token.get_span().line {
+            self.process_next()?;
+            current_token_type = TokenType::ELSEIF;
+        }
+
+        self.process_next()?;
         let mut condition: Option<ast::Condition> = None;
         if current_token_type != TokenType::ELSE {
-            condition = Some(self.parse_condition());
-            self.match_token(TokenType::THEN);
+            condition = Some(self.parse_condition()?);
+            self.match_token(TokenType::THEN)?;
         }
 
         let mut statements: Vec<ast::Statement> = Vec::new();
+        let mut lines: Vec<usize> = Vec::new();
 
         // We can have lots of statements inside our IF block - so loop until we find an END
         let mut other: Option<Box<ast::IfStatement>> = None;
-        while !self.check_token(&TokenType::END) {
+        while !self.check_token(&TokenType::END) && !self.check_token(&TokenType::EOF) {
             // If it's an ELSEIF or ELSE statement, we need to recurseively parse our IF
             if self.check_token(&TokenType::ELSEIF) || self.check_token(&TokenType::ELSE) {
-                // ELSE must be last so if we already ARE an ELSE and we find another, panic
+                // ELSE must be last so if we already ARE an ELSE and we find another, bail out
                 if current_token_type == TokenType::ELSE {
-                    panic!("Invalid elseif");
+                    return Err(self.error(ParseErrorKind::InvalidStatement, String::from("Invalid elseif - ELSE must be the last branch")));
                 }
 
-                other = Some(Box::new(self.parse_if()));
+                other = Some(Box::new(self.parse_if()?));
 
             } else {
-                statements.push(self.parse_statement());
+                self.parse_block_statement(&mut statements, &mut lines)?;
             }
         }
 
         // Only force Match END once - if this was the first IF statement
         if current_token_type == TokenType::IF {
-            self.match_token(TokenType::END);
+            self.match_token(TokenType::END)?;
         }
 
-        let block = ast::Block::new(statements);
-        match current_token_type {
-            TokenType::IF => ast::IfStatement::If(condition.unwrap(), block, other),
-            TokenType::ELSEIF => ast::IfStatement::ElseIf(condition.unwrap(), block, other),
-            TokenType::ELSE => ast::IfStatement::Else(block),
-            _ => panic!("Invalid IF statement constructed")
+        let block = ast::Block::with_lines(statements, lines);
+        match (current_token_type, condition) {
+            (TokenType::IF, Some(condition)) => Ok(ast::IfStatement::If(condition, block, other)),
+            (TokenType::ELSEIF, Some(condition)) => Ok(ast::IfStatement::ElseIf(condition, block, other)),
+            (TokenType::ELSE, _) => Ok(ast::IfStatement::Else(block)),
+            _ => Err(self.error(ParseErrorKind::InvalidStatement, String::from("Invalid IF statement constructed")))
         }
     }
 
-    fn process_next(&mut self) {
+    fn is_host_statement(&self) -> bool {
+        self.next_token.get_token_type() != &TokenType::EQ
+            && self.plugins.contains_key(&self.current_token.get_token_text().to_uppercase())
+    }
+
+    fn process_next(&mut self) -> Result<(), ParseError> {
         self.current_token = self.next_token.clone();
-        self.next_token = self.lexer.get_token();
+        self.next_token = match self.pending.pop_front() {
+            Some(token) => token,
+            None => self.lexer.get_token()?
+        };
+
+        if self.next_token.get_token_type() != &TokenType::EOF {
+            self.tokens += 1;
+            if let Some(max_tokens) = self.limits.max_tokens.filter(|max_tokens| self.tokens > *max_tokens) {
+                return Err(self.error(ParseErrorKind::TooLong, format!("Program is longer than {} tokens", max_tokens)));
+            }
+        }
+
+        Ok(())
     }
 
-    fn match_token(&mut self, token_type: TokenType) {
+    fn match_token(&mut self, token_type: TokenType) -> Result<(), ParseError> {
         if !self.check_token(&token_type) {
-            panic!("Syntax error! - Expected {:?} found {:?}", token_type, self.current_token.get_token_type());
+            return Err(self.error(ParseErrorKind::UnexpectedToken, format!("Expected {:?} found {:?}", token_type, self.current_token.get_token_type())));
         }
 
         // Match was successful, advance to next token
-        self.process_next();
+        self.process_next()
+    }
+
+    fn ident(&mut self, symbol: String) -> ast::Ident {
+        let id = ast::NodeId(self.next_id);
+        self.next_id += 1;
+        ast::Ident::with_id(symbol, id)
+    }
+
+    fn literal(&mut self, literal: ast::Literal) -> ast::Expression {
+        let id = ast::NodeId(self.next_id);
+        self.next_id += 1;
+        ast::Expression::Literal(literal, id)
+    }
+
+    fn error(&self, kind: ParseErrorKind, message: String) -> ParseError {
+        // Whatever was wanted, running out of tokens is what went wrong
+        let kind = match (kind, self.current_token.get_token_type()) {
+            (ParseErrorKind::UnexpectedToken | ParseErrorKind::InvalidStatement, TokenType::EOF) => ParseErrorKind::UnexpectedEnd,
+            (kind, _) => kind
+        };
+        ParseError::new(kind, message, self.current_token.get_span())
     }
 
     fn check_token(&mut self, token_type: &TokenType) -> bool {
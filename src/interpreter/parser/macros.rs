@@ -0,0 +1,256 @@
+// Macros are expanded while parsing. A definition records its body as tokens:
+//
+//     MACRO repeat(n, body)
+//         LET i = 0;
+//         WHILE i < n THEN
+//             body;
+//             i = i + 1;
+//         END
+//     END
+//
+// and a use like `repeat(3) THEN print "hi"; END` swaps the arguments in for the parameters
+// and feeds the result back through the parser. Writing `name(args);` passes expressions
+// only - a THEN ... END block after the arguments fills the last parameter, which the body
+// runs as a statement with `body;`.
+//
+// Variables the body declares with LET are renamed on every expansion, so they can't
+// collide with the caller's variables or another expansion's.
+
+use super::Parser;
+
+use crate::interpreter::cst::SyntaxKind;
+use crate::interpreter::error::ParseError;
+use crate::interpreter::error::ParseErrorKind;
+use crate::interpreter::token::Token;
+use crate::interpreter::token::TokenType;
+
+use std::collections::HashMap;
+
+// Guards against a macro that expands to itself forever
+const MAX_EXPANSIONS: usize = 10000;
+
+#[derive(Debug, Clone)]
+pub(super) struct Macro {
+    parameters: Vec<String>,
+    body: Vec<Token>,
+}
+
+impl<'a> Parser<'a> {
+    pub(super) fn is_macro_call(&self) -> bool {
+        self.current_token.get_token_type() == &TokenType::IDENT
+            && self.next_token.get_token_type() == &TokenType::LPAREN
+            && self.macros.contains_key(self.current_token.get_token_text())
+    }
+
+    pub(super) fn parse_macro(&mut self) -> Result<(), ParseError> {
+        let start = self.current_token.get_span();
+        let expanded = !self.pending.is_empty();
+        self.match_token(TokenType::MACRO)?;
+
+        let name = String::from(self.current_token.get_token_text());
+        self.match_token(TokenType::IDENT)?;
+        self.match_token(TokenType::LPAREN)?;
+
+        let mut parameters = Vec::new();
+        while !self.check_token(&TokenType::RPAREN) {
+            if !parameters.is_empty() {
+                self.match_token(TokenType::COMMA)?;
+            }
+
+            let parameter = String::from(self.current_token.get_token_text());
+            if parameters.contains(&parameter) {
+                return Err(self.error(ParseErrorKind::InvalidStatement, format!("Macro {} has two parameters named {}", name, parameter)));
+            }
+
+            self.match_token(TokenType::IDENT)?;
+            parameters.push(parameter);
+        }
+
+        self.match_token(TokenType::RPAREN)?;
+        let body = self.collect_until_end()?;
+        self.record_node(SyntaxKind::MacroDefinition, start, expanded);
+
+        self.macros.insert(name, Macro {
+            parameters: parameters,
+            body: body,
+        });
+
+        Ok(())
+    }
+
+    pub(super) fn expand_macro(&mut self) -> Result<(), ParseError> {
+        let name = String::from(self.current_token.get_token_text());
+        let span = self.current_token.get_span();
+        let expanded = !self.pending.is_empty();
+        let definition = self.macros[&name].clone();
+
+        self.expansions += 1;
+        if self.expansions > MAX_EXPANSIONS {
+            return Err(ParseError::new(ParseErrorKind::InvalidStatement, format!("Macro {} expanded more than {} times - does it use itself?", name, MAX_EXPANSIONS), span));
+        }
+
+        self.match_token(TokenType::IDENT)?;
+        self.match_token(TokenType::LPAREN)?;
+
+        let mut arguments = Vec::new();
+        while !self.check_token(&TokenType::RPAREN) {
+            if !arguments.is_empty() {
+                self.match_token(TokenType::COMMA)?;
+            }
+
+            arguments.push(self.collect_argument()?);
+        }
+
+        self.match_token(TokenType::RPAREN)?;
+
+        let block = if self.check_token(&TokenType::THEN) {
+            self.process_next()?;
+            Some(self.collect_until_end()?)
+        } else {
+            self.match_token(TokenType::SEMICOLON)?;
+            None
+        };
+        self.record_node(SyntaxKind::MacroUse, span, expanded);
+
+        if block.is_some() && definition.parameters.is_empty() {
+            return Err(ParseError::new(ParseErrorKind::InvalidStatement, format!("Macro {} has no parameter to take a block", name), span));
+        }
+
+        let expected = definition.parameters.len() - if block.is_some() { 1 } else { 0 };
+        if arguments.len() != expected {
+            return Err(ParseError::new(ParseErrorKind::InvalidStatement, format!("Macro {} expects {} argument(s) but was given {}", name, expected, arguments.len()), span));
+        }
+
+        let mut bindings: HashMap<&str, Vec<Token>> = HashMap::new();
+        for (parameter, argument) in definition.parameters.iter().zip(arguments) {
+            bindings.insert(parameter, argument);
+        }
+
+        let block_parameter = match block {
+            Some(block) => {
+                let parameter = definition.parameters.last().unwrap();
+                bindings.insert(parameter, block);
+                Some(parameter.as_str())
+            },
+            None => None
+        };
+
+        // Names the body declares, renamed with a character no source identifier can contain
+        let mut renames: HashMap<&str, String> = HashMap::new();
+        for pair in definition.body.windows(2) {
+            if pair[0].get_token_type() == &TokenType::LET && !bindings.contains_key(pair[1].get_token_text()) {
+                renames.insert(pair[1].get_token_text(), format!("{}#{}", pair[1].get_token_text(), self.expansions));
+            }
+        }
+
+        let mut expansion = Vec::new();
+        let mut body = definition.body.iter().peekable();
+        while let Some(token) = body.next() {
+            let text = token.get_token_text();
+            if token.get_token_type() != &TokenType::IDENT {
+                expansion.push(token.clone());
+            } else if Some(text) == block_parameter {
+                expansion.extend(bindings[text].iter().cloned());
+                // The block is whole statements, so it brings its own semicolons
+                if body.peek().map(|next| next.get_token_type() == &TokenType::SEMICOLON).unwrap_or(false) {
+                    body.next();
+                }
+            } else if let Some(argument) = bindings.get(text) {
+                // Bracketed so an argument like a + b keeps together inside a * b
+                if argument.len() == 1 {
+                    expansion.push(argument[0].clone());
+                } else {
+                    expansion.push(Token::new(TokenType::LPAREN, String::from("(")).at(token.get_span()));
+                    expansion.extend(argument.iter().cloned());
+                    expansion.push(Token::new(TokenType::RPAREN, String::from(")")).at(token.get_span()));
+                }
+            } else if let Some(renamed) = renames.get(text) {
+                expansion.push(Token::new(TokenType::IDENT, renamed.clone()).at(token.get_span()));
+            } else {
+                expansion.push(token.clone());
+            }
+        }
+
+        self.splice(expansion)
+    }
+
+    // Puts tokens in front of the current one, as if they had been in the source there
+    fn splice(&mut self, tokens: Vec<Token>) -> Result<(), ParseError> {
+        let current = self.current_token.clone();
+        let next = self.next_token.clone();
+
+        self.pending.push_front(next);
+        self.pending.push_front(current);
+        for token in tokens.into_iter().rev() {
+            self.pending.push_front(token);
+        }
+
+        self.process_next()?;
+        self.process_next()
+    }
+
+    // The tokens of one argument, up to a comma or the closing bracket of the call
+    fn collect_argument(&mut self) -> Result<Vec<Token>, ParseError> {
+        let mut tokens = Vec::new();
+        let mut depth = 0;
+
+        loop {
+            match self.current_token.get_token_type() {
+                TokenType::COMMA | TokenType::RPAREN if depth == 0 => break,
+                TokenType::LPAREN | TokenType::LBRACKET | TokenType::LBRACE => depth += 1,
+                TokenType::RPAREN | TokenType::RBRACKET | TokenType::RBRACE => depth -= 1,
+                TokenType::EOF | TokenType::SEMICOLON => {
+                    return Err(self.error(ParseErrorKind::UnexpectedToken, format!("Expected RPAREN found {:?}", self.current_token.get_token_type())));
+                },
+                _ => {}
+            }
+
+            tokens.push(self.current_token.clone());
+            self.process_next()?;
+        }
+
+        if tokens.is_empty() {
+            return Err(self.error(ParseErrorKind::UnexpectedToken, String::from("Expected a macro argument")));
+        }
+
+        Ok(tokens)
+    }
+
+    // The tokens up to the END that closes the current block, which is consumed but not kept
+    fn collect_until_end(&mut self) -> Result<Vec<Token>, ParseError> {
+        let mut tokens: Vec<Token> = Vec::new();
+        let mut depth = 0;
+        // IF, ELSEIF and WHILE headers end in a THEN of their own - any other THEN opens a
+        // macro block
+        let mut headers = 0;
+
+        loop {
+            // ELSE IF on one line is an ELSEIF, the way parse_if reads it
+            let else_if = tokens.last().is_some_and(|previous| previous.get_token_type() == &TokenType::ELSE
+                && previous.get_span().line == self.current_token.get_span().line);
+
+            match self.current_token.get_token_type() {
+                TokenType::END if depth == 0 => break,
+                TokenType::END => depth -= 1,
+                TokenType::IF if else_if => headers += 1,
+                TokenType::IF | TokenType::WHILE => {
+                    depth += 1;
+                    headers += 1;
+                },
+                // Closed by an END, but with no THEN to wait for
+                TokenType::FOR | TokenType::FUNCTION | TokenType::MACRO => depth += 1,
+                TokenType::ELSEIF => headers += 1,
+                TokenType::THEN if headers > 0 => headers -= 1,
+                TokenType::THEN => depth += 1,
+                TokenType::EOF => return Err(self.error(ParseErrorKind::UnexpectedToken, String::from("Expected END found EOF"))),
+                _ => {}
+            }
+
+            tokens.push(self.current_token.clone());
+            self.process_next()?;
+        }
+
+        self.match_token(TokenType::END)?;
+        Ok(tokens)
+    }
+}
@@ -0,0 +1,46 @@
+use super::intr::Interpreter;
+use super::lexer::Lexer;
+use super::parser::Parser;
+
+use std::io;
+use std::io::Write;
+
+pub fn run(interpreter: &mut Interpreter) {
+    println!("Entering post-mortem prompt - type an expression to evaluate it, vars to list variables or quit to exit");
+
+    loop {
+        print!("(post-mortem) ");
+        let _ = io::stdout().flush();
+
+        let mut line = String::new();
+        match io::stdin().read_line(&mut line) {
+            Ok(0) | Err(_) => return,
+            Ok(_) => {}
+        }
+
+        match line.trim() {
+            "" => {},
+            "q" | "quit" => return,
+            "vars" => {
+                for (name, value) in interpreter.get_global_scope() {
+                    println!("{} = {}", name, value);
+                }
+            },
+            source => print_evaluated(interpreter, source)
+        }
+    }
+}
+
+// Parses the line as one expression and prints its value, or whatever went wrong
+pub(crate) fn print_evaluated(interpreter: &mut Interpreter, source: &str) {
+    let mut lexer = Lexer::new(source.chars().peekable());
+    let mut parser = Parser::new(&mut lexer);
+
+    match parser.parse_single_expression() {
+        Ok(expression) => match interpreter.evaluate(&expression) {
+            Ok(value) => println!("{}", value),
+            Err(err) => println!("{}", err)
+        },
+        Err(err) => println!("{}", err)
+    }
+}
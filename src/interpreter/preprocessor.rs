@@ -0,0 +1,137 @@
+use super::error::ParseError;
+use super::error::ParseErrorKind;
+use super::error::Span;
+
+use std::collections::HashMap;
+
+// Runs before the lexer, handling lines that start with a directive:
+//
+//     #DEFINE NAME value    later uses of NAME are replaced with value (1 when left off)
+//     #IF NAME              keep what follows only if NAME is defined and not 0
+//     #ELSE
+//     #END
+//
+// Defines can also come from outside, e.g. -D DEBUG=1 on the command line. Directive lines
+// and skipped sections are left as blank lines so errors still point at the right line.
+pub fn preprocess(source: &str, defines: &HashMap<String, String>) -> Result<String, ParseError> {
+    let mut defines = defines.clone();
+    // One entry per open #IF - whether its current branch is kept
+    let mut conditions: Vec<Condition> = Vec::new();
+    let mut in_string = false;
+    let mut output = String::with_capacity(source.len());
+
+    for (i, line) in source.split('\n').enumerate() {
+        let line_number = i + 1;
+        if i > 0 {
+            output.push('\n');
+        }
+
+        let trimmed = line.trim();
+        if !in_string && trimmed.starts_with('#') {
+            let active = conditions.iter().all(|condition| condition.active);
+            let mut words = trimmed[1..].split_whitespace();
+            let directive = words.next().unwrap_or("").to_ascii_uppercase();
+
+            match directive.as_str() {
+                "DEFINE" if active => match words.next() {
+                    Some(name) => {
+                        let value: Vec<&str> = words.collect();
+                        let value = if value.is_empty() { String::from("1") } else { value.join(" ") };
+                        defines.insert(String::from(name), value);
+                    },
+                    None => return Err(error("#DEFINE needs a name", line_number))
+                },
+                "DEFINE" => {},
+                "IF" => match words.next() {
+                    Some(name) => conditions.push(Condition {
+                        active: is_set(&defines, name),
+                        in_else: false,
+                        line: line_number,
+                    }),
+                    None => return Err(error("#IF needs a name to test", line_number))
+                },
+                "ELSE" => match conditions.last_mut() {
+                    Some(condition) if !condition.in_else => {
+                        condition.active = !condition.active;
+                        condition.in_else = true;
+                    },
+                    Some(_) => return Err(error("#ELSE already seen for this #IF", line_number)),
+                    None => return Err(error("#ELSE without a matching #IF", line_number))
+                },
+                "END" => {
+                    if conditions.pop().is_none() {
+                        return Err(error("#END without a matching #IF", line_number));
+                    }
+                },
+                _ => return Err(error(&format!("Unknown directive #{}", directive), line_number))
+            }
+
+            continue;
+        }
+
+        if conditions.iter().all(|condition| condition.active) {
+            in_string = substitute(line, &defines, in_string, &mut output);
+        }
+    }
+
+    match conditions.last() {
+        Some(condition) => Err(error("#IF is never closed with #END", condition.line)),
+        None => Ok(output)
+    }
+}
+
+struct Condition {
+    active: bool,
+    in_else: bool,
+    line: usize,
+}
+
+fn is_set(defines: &HashMap<String, String>, name: &str) -> bool {
+    match defines.get(name) {
+        Some(value) => !value.is_empty() && value != "0",
+        None => false
+    }
+}
+
+// Copies one line, replacing defined names outside of string literals. Strings can run
+// over several lines, so whether the line ends inside one is passed along.
+fn substitute(line: &str, defines: &HashMap<String, String>, mut in_string: bool, output: &mut String) -> bool {
+    let mut chars = line.chars().peekable();
+    // A word straight after a digit is a number suffix like the d of 1.5d, not a name
+    let mut after_digit = false;
+
+    while let Some(c) = chars.next() {
+        if c == '"' {
+            in_string = !in_string;
+        }
+
+        if in_string || !c.is_alphabetic() {
+            after_digit = c.is_ascii_digit();
+            output.push(c);
+            continue;
+        }
+
+        let mut word = c.to_string();
+        while let Some(&next) = chars.peek() {
+            if !next.is_alphanumeric() {
+                break;
+            }
+
+            word.push(next);
+            chars.next();
+        }
+
+        match defines.get(&word) {
+            Some(value) if !after_digit => output.push_str(value),
+            _ => output.push_str(&word)
+        }
+
+        after_digit = false;
+    }
+
+    in_string
+}
+
+fn error(message: &str, line: usize) -> ParseError {
+    ParseError::new(ParseErrorKind::Directive, String::from(message), Span::new(line, 1))
+}
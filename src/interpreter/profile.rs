@@ -0,0 +1,149 @@
+use serde::Serialize;
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::time::Duration;
+use std::time::Instant;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProfileFormat {
+    // Columns lined up for reading, slowest function first
+    Table,
+    // {"function":"fib","calls":177,...} - one object per line
+    Json,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FunctionStats {
+    pub calls: u64,
+    // The whole of every call, including the functions it called
+    pub inclusive_us: u128,
+    // Only the time spent in the function's own statements
+    pub exclusive_us: u128,
+    // The most calls to the function that were under way at once
+    pub max_depth: usize,
+}
+
+#[derive(Serialize)]
+struct ProfileLine<'a> {
+    function: &'a str,
+    #[serde(flatten)]
+    stats: &'a FunctionStats,
+}
+
+struct ActiveCall {
+    name: String,
+    started: Instant,
+    // Time spent in the calls this one made, taken off its exclusive time
+    in_callees: Duration,
+}
+
+// Counts and times every call to a FUNCTION, with the top level of the program as one call
+// to <main>. Builtins aren't included - they are quick and would only crowd the report.
+pub struct Profiler {
+    functions: HashMap<String, FunctionStats>,
+    // Exclusive time for each distinct chain of calls, outermost first - main;outer;inner
+    stacks: BTreeMap<String, u128>,
+    active: Vec<ActiveCall>,
+}
+
+impl ProfileFormat {
+    pub fn from_name(name: &str) -> Option<ProfileFormat> {
+        match name {
+            "table" => Some(ProfileFormat::Table),
+            "json" => Some(ProfileFormat::Json),
+            _ => None
+        }
+    }
+}
+
+impl Default for Profiler {
+    fn default() -> Profiler {
+        Profiler::new()
+    }
+}
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler {
+            functions: HashMap::new(),
+            stacks: BTreeMap::new(),
+            active: Vec::new(),
+        }
+    }
+
+    pub fn enter(&mut self, name: &str) {
+        let depth = self.active.iter().filter(|call| call.name == name).count() + 1;
+        let stats = self.functions.entry(String::from(name)).or_default();
+        stats.calls += 1;
+        stats.max_depth = stats.max_depth.max(depth);
+
+        self.active.push(ActiveCall {
+            name: String::from(name),
+            started: Instant::now(),
+            in_callees: Duration::ZERO,
+        });
+    }
+
+    // Ends the innermost call - every enter is matched by an exit, even when the call fails
+    pub fn exit(&mut self) {
+        let call = match self.active.pop() {
+            Some(call) => call,
+            None => return
+        };
+
+        let elapsed = call.started.elapsed();
+        let exclusive = elapsed.saturating_sub(call.in_callees).as_micros();
+
+        let mut stack: Vec<&str> = self.active.iter().map(|active| active.name.as_str()).collect();
+        stack.push(&call.name);
+        *self.stacks.entry(stack.join(";")).or_insert(0) += exclusive;
+
+        if let Some(caller) = self.active.last_mut() {
+            caller.in_callees += elapsed;
+        }
+
+        // A recursive call is already counted in the outermost one's inclusive time
+        let stats = self.functions.entry(call.name.clone()).or_default();
+        if !self.active.iter().any(|active| active.name == call.name) {
+            stats.inclusive_us += elapsed.as_micros();
+        }
+        stats.exclusive_us += exclusive;
+    }
+
+    // Folded stacks, the input flamegraph tools such as inferno take - one chain of calls per
+    // line followed by the microseconds spent in its innermost function. Chains that took
+    // less than a microsecond are left out.
+    pub fn write_folded(&self, path: &str) -> io::Result<()> {
+        let lines: Vec<String> = self.stacks.iter()
+            .filter(|(_, micros)| **micros > 0)
+            .map(|(stack, micros)| format!("{} {}\n", stack, micros))
+            .collect();
+        fs::write(path, lines.concat())
+    }
+
+    pub fn report(&self, format: ProfileFormat) -> String {
+        let mut functions: Vec<(&String, &FunctionStats)> = self.functions.iter().collect();
+        functions.sort_by(|a, b| b.1.inclusive_us.cmp(&a.1.inclusive_us).then_with(|| a.0.cmp(b.0)));
+
+        match format {
+            ProfileFormat::Json => functions.iter()
+                .map(|(name, stats)| {
+                    let line = ProfileLine { function: name, stats: stats };
+                    serde_json::to_string(&line).expect("Profile lines always serialize")
+                })
+                .collect::<Vec<String>>()
+                .join("\n"),
+            ProfileFormat::Table => {
+                let width = functions.iter().map(|(name, _)| name.len()).max().unwrap_or(0).max("function".len());
+                let mut output = format!("{:<width$}  {:>8}  {:>14}  {:>14}  {:>9}", "function", "calls", "inclusive (us)", "exclusive (us)", "max depth", width = width);
+                for (name, stats) in functions {
+                    output.push_str(&format!("\n{:<width$}  {:>8}  {:>14}  {:>14}  {:>9}", name, stats.calls, stats.inclusive_us, stats.exclusive_us, stats.max_depth, width = width));
+                }
+                output
+            }
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use super::ast::AbstractSyntaxTree;
+
+use super::error::ParseError;
+use super::error::RuntimeError;
+use super::events;
+use super::events::Event;
+use super::intr::Interpreter;
+use super::intr::RunResult;
+use super::parser;
+
+use std::io::Write;
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+
+// A parsed program that any number of interpreters can run at once, from any thread.
+// Cloning only bumps a reference count - each run gets its own interpreter and scope.
+#[derive(Debug, Clone)]
+pub struct Program {
+    ast: Arc<AbstractSyntaxTree>,
+}
+
+impl Program {
+    pub fn new(ast: AbstractSyntaxTree) -> Program {
+        Program { ast: Arc::new(ast) }
+    }
+
+    pub fn parse(source: &str) -> Result<Program, ParseError> {
+        Ok(Program::new(parser::parse_str(source)?))
+    }
+
+    pub fn get_ast(&self) -> &AbstractSyntaxTree {
+        &self.ast
+    }
+
+    pub fn run(&self) -> Result<RunResult, RuntimeError> {
+        Interpreter::standalone().run(&self.ast)
+    }
+
+    pub fn run_with_output(&self, output: &mut dyn Write) -> Result<RunResult, RuntimeError> {
+        let mut interpreter = Interpreter::standalone();
+        interpreter.set_output(Box::new(output));
+        interpreter.run(&self.ast)
+    }
+
+    // Runs with every statement, assignment and piece of output sent down the channel,
+    // e.g. from a worker thread to a GUI
+    pub fn run_with_events(&self, sender: Sender<Event>) -> Result<RunResult, RuntimeError> {
+        let mut interpreter = Interpreter::standalone();
+        interpreter.set_event_handler(events::channel_handler(sender));
+        interpreter.run(&self.ast)
+    }
+}
+
+impl From<AbstractSyntaxTree> for Program {
+    fn from(ast: AbstractSyntaxTree) -> Program {
+        Program::new(ast)
+    }
+}
+
+// Fails to compile if the syntax tree ever picks up something that can't cross threads
+fn _assert_program_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Program>();
+}
@@ -0,0 +1,132 @@
+use super::ast::AbstractSyntaxTree;
+use super::ast::Block;
+
+use super::error::ParseError;
+use super::lexer::Lexer;
+use super::parser::ParseLimits;
+use super::parser::Parser;
+use super::preprocessor;
+use super::warning::Warning;
+use super::warning::WarningSet;
+
+use rayon::prelude::*;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+
+// A warning the parser gave, and the path of the file it's about
+pub type FileWarning = (String, Warning);
+
+// One file's syntax tree and the parser's warnings about it, or everything wrong with it
+type ParsedFile = Result<(AbstractSyntaxTree, Vec<Warning>), Vec<FileDiagnostic>>;
+
+// A problem with one file of a multi-file program
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileDiagnostic {
+    path: String,
+    message: String,
+    // The syntax error behind the message - None when the file couldn't be read
+    error: Option<ParseError>,
+}
+
+impl FileDiagnostic {
+    pub fn new(path: String, message: String) -> FileDiagnostic {
+        FileDiagnostic {
+            path: path,
+            message: message,
+            error: None,
+        }
+    }
+
+    pub fn from_error(path: String, error: ParseError) -> FileDiagnostic {
+        FileDiagnostic {
+            path: path,
+            message: error.to_string(),
+            error: Some(error),
+        }
+    }
+
+    pub fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn get_error(&self) -> Option<&ParseError> {
+        self.error.as_ref()
+    }
+}
+
+impl fmt::Display for FileDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+// Reads and parses every file in parallel, then joins them into one program in the
+// order given. Every file is parsed even if an earlier one fails, so all problems are
+// reported together. Each file is preprocessed on its own with the same defines. The
+// parser's warnings come back with the path of the file they were found in.
+pub fn parse_files(paths: &[String], defines: &HashMap<String, String>, case_sensitive_keywords: bool, enabled_warnings: &WarningSet, limits: ParseLimits) -> Result<(AbstractSyntaxTree, Vec<FileWarning>), Vec<FileDiagnostic>> {
+    let results: Vec<ParsedFile> = paths.par_iter()
+        .map(|path| parse_file(path, defines, case_sensitive_keywords, enabled_warnings, limits))
+        .collect();
+
+    let mut block = Block::new(Vec::new());
+    let mut diagnostics = Vec::new();
+    let mut warnings = Vec::new();
+    let mut last_id = 0;
+
+    for (file, result) in results.into_iter().enumerate() {
+        match result {
+            Ok((mut ast, file_warnings)) => {
+                warnings.extend(file_warnings.into_iter().map(|warning| (paths[file].clone(), warning)));
+
+                // Each file's ids start from 1 - move them past the files before it
+                let offset = last_id;
+                ast.block.for_each_id_mut(&mut |id| {
+                    if id.0 > 0 {
+                        id.0 += offset;
+                        last_id = last_id.max(id.0);
+                    }
+                });
+                ast.block.set_file(file);
+                block.append(ast.block);
+            },
+            Err(mut file_diagnostics) => diagnostics.append(&mut file_diagnostics)
+        }
+    }
+
+    if diagnostics.is_empty() {
+        Ok((AbstractSyntaxTree::new(block), warnings))
+    } else {
+        Err(diagnostics)
+    }
+}
+
+fn parse_file(path: &str, defines: &HashMap<String, String>, case_sensitive_keywords: bool, enabled_warnings: &WarningSet, limits: ParseLimits) -> ParsedFile {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(err) => return Err(vec![FileDiagnostic::new(String::from(path), format!("Unable to read file - {}", err))])
+    };
+
+    let source = match preprocessor::preprocess(&source, defines) {
+        Ok(source) => source,
+        Err(err) => return Err(vec![FileDiagnostic::from_error(String::from(path), err)])
+    };
+
+    let mut lexer = Lexer::new(source.chars().peekable());
+    lexer.set_case_sensitive_keywords(case_sensitive_keywords);
+    let mut parser = Parser::new(&mut lexer);
+    parser.set_limits(limits);
+    parser.set_enabled_warnings(enabled_warnings.clone());
+    match parser.parse_all() {
+        Ok(ast) => Ok((ast, parser.take_warnings())),
+        Err(errors) => Err(errors.into_iter()
+            .map(|err| FileDiagnostic::from_error(String::from(path), err))
+            .collect())
+    }
+}
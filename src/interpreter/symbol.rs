@@ -1,32 +1,271 @@
 use super::ast;
+use super::ast::NodeId;
+use super::error::RuntimeError;
+use super::error::RuntimeErrorKind;
+use super::error::Span;
+use super::warning::Warning;
+use super::warning::WarningKind;
+use super::warning::WarningSet;
 
 use std::collections::HashMap;
+use std::collections::HashSet;
 
 #[derive(PartialEq, Eq, Hash)]
 pub struct Symbol {
-    pub name: String
+    pub name: String,
+    // The identifier that declared it - its LET, or the first assignment when declarations
+    // are implicit
+    pub declaration: NodeId,
+    // Where the variable's value is kept in each scope - given out by define_symbol
+    pub slot: usize,
+}
+
+// What a value is known to be before the program runs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Type {
+    Number,
+    Decimal,
+    String,
+    Boolean,
+    Array,
+    Map,
+}
+
+// The scope a GLOBAL or LOCAL in a function sends a variable to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scope {
+    Global,
+    Local,
+}
+
+// What the pass has worked out about a variable or expression so far
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Inferred {
+    Known(Type),
+    // Could be more than one type, or nothing can be said until it runs
+    Mixed,
 }
 
 impl Symbol {
     pub fn new(name: String) -> Symbol {
-        Symbol { name: name }
+        Symbol::declared_at(name, NodeId::default())
+    }
+
+    pub fn declared_at(name: String, declaration: NodeId) -> Symbol {
+        Symbol {
+            name: name,
+            declaration: declaration,
+            slot: 0,
+        }
     }
 }
 
+// Where a LET or parameter declared its variable, for the warnings that need the whole tree
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Declared {
+    Global,
+    InFunction,
+    Parameter,
+}
+
+// The function being walked - its parameters, its GLOBAL and LOCAL declarations, and every
+// name it has used so far
+#[derive(Default)]
+struct FunctionScope {
+    parameters: HashSet<String>,
+    declared: HashMap<String, Scope>,
+    used: HashSet<String>,
+}
+
+// A WHILE guard of the form `ident comparator number`. The loop checks it by reading the
+// variable's slot and making one comparison - the whole condition is only evaluated when
+// the variable isn't holding a number.
+#[derive(Debug, Clone)]
+pub struct Guard {
+    pub slot: usize,
+    pub comparator: ast::Comparator,
+    pub limit: f32,
+}
+
+#[derive(Default)]
 pub struct SymbolTable {
-    symbols: HashMap<String, Symbol>
+    symbols: HashMap<String, Symbol>,
+    // The name of the variable in each slot. A name keeps its slot for as long as the table
+    // lives, so values stored by an earlier run are still found by a later one.
+    names: Vec<String>,
+    // Classic BASIC style - a plain assignment declares the variable if LET never did
+    implicit_declarations: bool,
+    // Side tables for the last tree processed, keyed by the id of each identifier - the
+    // variable it names, and the type it's known to hold
+    resolutions: HashMap<NodeId, String>,
+    types: HashMap<NodeId, Type>,
+    // The identifiers a GLOBAL or LOCAL sends to one scope, with the name like get_slot checks
+    scopes: HashMap<NodeId, (String, Scope)>,
+    // Indexed by id rather than hashed, since the interpreter asks on every read and write
+    slots: Vec<Option<usize>>,
+    // The constant pool - each distinct literal in the tree once, however often it's written
+    constants: Vec<ast::Literal>,
+    constant_indexes: HashMap<ast::Literal, usize>,
+    // Indexed by id like slots - where each literal is in the pool
+    constant_ids: Vec<Option<usize>>,
+    // The number each WHILE guard compares against, keyed by the id of its literal
+    guards: HashMap<NodeId, f32>,
+    // Gathered while walking the tree - every assignment, then every other use of a variable
+    assignments: Vec<(String, NodeId, ast::Expression)>,
+    uses: Vec<(String, NodeId)>,
+    // Function parameters - they can be given anything, so their type is never known
+    parameters: Vec<(String, NodeId)>,
+    // Each LET and parameter with the line it's on, checked once the walk is done
+    declarations: Vec<(String, usize, Declared)>,
+    line: usize,
+    in_function: bool,
+    function: FunctionScope,
+    enabled_warnings: WarningSet,
+    warnings: Vec<Warning>,
 }
 
 impl SymbolTable {
     pub fn new() -> SymbolTable {
-        SymbolTable { symbols: HashMap::new() }
+        SymbolTable {
+            symbols: HashMap::new(),
+            names: Vec::new(),
+            implicit_declarations: false,
+            resolutions: HashMap::new(),
+            types: HashMap::new(),
+            scopes: HashMap::new(),
+            slots: Vec::new(),
+            constants: Vec::new(),
+            constant_indexes: HashMap::new(),
+            constant_ids: Vec::new(),
+            guards: HashMap::new(),
+            assignments: Vec::new(),
+            uses: Vec::new(),
+            parameters: Vec::new(),
+            declarations: Vec::new(),
+            line: 0,
+            in_function: false,
+            function: FunctionScope::default(),
+            enabled_warnings: WarningSet::default(),
+            warnings: Vec::new(),
+        }
+    }
+
+    pub fn set_implicit_declarations(&mut self, implicit_declarations: bool) {
+        self.implicit_declarations = implicit_declarations;
+    }
+
+    pub fn set_enabled_warnings(&mut self, enabled_warnings: WarningSet) {
+        self.enabled_warnings = enabled_warnings;
+    }
+
+    // Warnings about the last tree processed
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    pub fn process_abstract_syntax_tree(&mut self, ast: &ast::AbstractSyntaxTree) -> Result<(), RuntimeError> {
+        self.resolutions.clear();
+        self.types.clear();
+        self.scopes.clear();
+        self.slots.clear();
+        self.constants.clear();
+        self.constant_indexes.clear();
+        self.constant_ids.clear();
+        self.guards.clear();
+
+        let result = self.process_block(&ast.block);
+        self.check_declarations();
+        self.resolve();
+        result
+    }
+
+    // The variable an identifier names - for a declaration, the variable it declares
+    pub fn get_resolution(&self, id: NodeId) -> Option<&Symbol> {
+        self.resolutions.get(&id).and_then(|name| self.symbols.get(name))
+    }
+
+    // The type an identifier is known to hold, when every assignment to it agrees
+    pub fn get_type(&self, id: NodeId) -> Option<Type> {
+        self.types.get(&id).copied()
+    }
+
+    // The slot of the variable an identifier names. Ids only mean something in the tree last
+    // processed - an identifier from anywhere else, like EVAL's text or a function defined
+    // by an earlier run, is found by its name instead.
+    pub fn get_slot(&self, ident: &ast::Ident) -> Option<usize> {
+        let resolved = self.slots.get(ident.id.0 as usize)
+            .copied()
+            .flatten()
+            .filter(|slot| self.names[*slot] == ident.symbol);
+
+        resolved.or_else(|| self.symbols.get(&ident.symbol).map(|symbol| symbol.slot))
+    }
+
+    // The scope a GLOBAL or LOCAL sends an identifier to - None leaves it to the usual rule,
+    // where a function's own variables hide the program's
+    pub fn get_scope(&self, ident: &ast::Ident) -> Option<Scope> {
+        self.scopes.get(&ident.id)
+            .filter(|(name, _)| *name == ident.symbol)
+            .map(|(_, scope)| *scope)
+    }
+
+    // Where a literal is in the constant pool - checked against the literal itself, since
+    // like slots the ids only hold for the tree last processed
+    pub fn get_constant(&self, literal: &ast::Literal, id: NodeId) -> Option<usize> {
+        self.constant_ids.get(id.0 as usize)
+            .copied()
+            .flatten()
+            .filter(|index| self.constants[*index] == *literal)
+    }
+
+    // The guard for a WHILE condition, when the pass found it to be a simple one. The literal
+    // is checked against the pool, so a condition from another tree is never mistaken for one.
+    pub fn get_guard(&self, condition: &ast::Condition) -> Option<Guard> {
+        let comparison = match condition {
+            ast::Condition::Compare(comparison) => comparison,
+            _ => return None
+        };
+
+        let (ident, literal, id) = match (&comparison.left_expression, &comparison.right_expression) {
+            (ast::Expression::Ident(ident), ast::Expression::Literal(literal, id)) => (ident, literal, *id),
+            _ => return None
+        };
+
+        self.get_constant(literal, id)?;
+        // The guard reads whichever scope has the variable, which a GLOBAL or LOCAL overrides
+        if self.get_scope(ident).is_some() {
+            return None;
+        }
+
+        Some(Guard {
+            slot: self.get_slot(ident)?,
+            comparator: comparison.comparator.clone(),
+            limit: *self.guards.get(&id)?,
+        })
     }
 
-    pub fn process_abstract_syntax_tree(&mut self, ast: &mut ast::AbstractSyntaxTree) {
-        self.process_block(&ast.block);
+    pub fn get_constants(&self) -> &[ast::Literal] {
+        &self.constants
     }
 
-    pub fn define_symbol(&mut self, symbol: Symbol) {
+    pub fn get_slot_name(&self, slot: usize) -> &str {
+        &self.names[slot]
+    }
+
+    pub fn slot_count(&self) -> usize {
+        self.names.len()
+    }
+
+    // Declaring a name again keeps the slot it already has
+    pub fn define_symbol(&mut self, mut symbol: Symbol) {
+        symbol.slot = match self.symbols.get(&symbol.name) {
+            Some(existing) => existing.slot,
+            None => {
+                self.names.push(symbol.name.clone());
+                self.names.len() - 1
+            }
+        };
+
         self.symbols.insert(symbol.name.clone(), symbol);
     }
 
@@ -34,39 +273,406 @@ impl SymbolTable {
         self.symbols.get(key)
     }
 
+    // Every variable declared, in the order they were first declared
+    pub fn get_names(&self) -> Vec<&str> {
+        self.names.iter().map(String::as_str).collect()
+    }
+
     pub fn output(&mut self) {
-        for key in self.symbols.keys() {
-            println!("{}", key);
+        for name in &self.names {
+            println!("{}", name);
         }
     }
 
-    fn process_block(&mut self, block: &ast::Block) {
-        for i in 0..block.get_length() {
-            self.process_statement(block.get_statement(i));
+    fn process_block(&mut self, block: &ast::Block) -> Result<(), RuntimeError> {
+        for (file, line, statement) in block.iter_with_files() {
+            self.line = line;
+            if let Err(mut err) = self.process_statement(statement) {
+                if err.get_line() == 0 {
+                    err.set_line(line);
+                    err.set_file(file);
+                }
+
+                return Err(err);
+            }
         }
+
+        Ok(())
     }
 
-    fn process_statement(&mut self, statement: &ast::Statement) {
+    fn process_statement(&mut self, statement: &ast::Statement) -> Result<(), RuntimeError> {
         match statement {
-            ast::Statement::Let(ident, _) => {
-                let symbol = Symbol { name: ident.symbol.clone() };
-                self.define_symbol(symbol);
+            ast::Statement::Let(ident, expression) => {
+                if self.function.declared.get(&ident.symbol) == Some(&Scope::Global) {
+                    return Err(RuntimeError::with_kind(RuntimeErrorKind::Syntax, format!("LET {} in a function that declares it GLOBAL - assign to it without LET", &ident.symbol)));
+                }
+
+                let declared = if self.in_function { Declared::InFunction } else { Declared::Global };
+                self.declarations.push((ident.symbol.clone(), self.line, declared));
+                self.define_symbol(Symbol::declared_at(ident.symbol.clone(), ident.id));
+                self.process_assignment(ident, expression);
             },
-            ast::Statement::Assignment(ident, _) => {
-                let symbol = self.lookup(&ident.symbol);
-                if symbol.is_none() {
-                    panic!("Referenced symbol {} before assignment", &ident.symbol);
+            ast::Statement::Assignment(target, _) if !self.symbols.contains_key(&target.get_ident().symbol) && !self.implicit_declarations => {
+                let ident = target.get_ident();
+                return Err(RuntimeError::with_kind(RuntimeErrorKind::UndefinedVariable, format!("Assignment to {} before it is declared - use LET {} = ...", &ident.symbol, &ident.symbol)));
+            },
+            // Changing an element keeps the variable's type, so only the reads count
+            ast::Statement::Assignment(ast::LValue::Index(target, index), expression) => {
+                let ident = target.get_ident();
+                if !self.symbols.contains_key(&ident.symbol) {
+                    return Err(RuntimeError::with_kind(RuntimeErrorKind::UndefinedVariable, format!("Assignment to an element of {} before it is declared", &ident.symbol)));
                 }
+
+                self.process_lvalue(target);
+                self.process_expression(index);
+                self.process_expression(expression);
             },
-            ast::Statement::If(if_statement) => {
-                match if_statement {
-                    ast::IfStatement::If(_, block, _) => self.process_block(block),
-                    ast::IfStatement::ElseIf(_, block, _) => self.process_block(block),
-                    ast::IfStatement::Else(block) => self.process_block(block)
+            ast::Statement::Assignment(ast::LValue::Ident(ident), expression) => {
+                if !self.symbols.contains_key(&ident.symbol) {
+                    self.define_symbol(Symbol::declared_at(ident.symbol.clone(), ident.id));
+                }
+
+                self.process_assignment(ident, expression);
+            },
+            ast::Statement::Print(expressions) | ast::Statement::EPrint(expressions) => {
+                for expression in expressions {
+                    self.process_expression(expression);
+                }
+            },
+            ast::Statement::Host(host) => {
+                for expression in &host.arguments {
+                    self.process_expression(expression);
+                }
+            },
+            ast::Statement::Inspect(Some(ident)) => self.add_use(ident),
+            ast::Statement::If(if_statement) => self.process_if(if_statement)?,
+            ast::Statement::While(condition, block) => {
+                self.process_condition(condition);
+                self.add_guard(condition);
+                self.process_block(block)?;
+            },
+            // The loop variable is declared like LET, starting from the first value
+            ast::Statement::For(for_loop) => {
+                self.define_symbol(Symbol::declared_at(for_loop.variable.symbol.clone(), for_loop.variable.id));
+                self.process_assignment(&for_loop.variable, &for_loop.start);
+                self.process_expression(&for_loop.end);
+                if let Some(step) = &for_loop.step {
+                    self.process_expression(step);
+                }
+                self.process_block(&for_loop.body)?;
+            },
+            ast::Statement::Labeled(_, statement) => self.process_statement(statement)?,
+            ast::Statement::FunctionDef(function) => {
+                for parameter in &function.parameters {
+                    self.define_symbol(Symbol::declared_at(parameter.symbol.clone(), parameter.id));
+                    self.parameters.push((parameter.symbol.clone(), parameter.id));
+                    self.declarations.push((parameter.symbol.clone(), self.line, Declared::Parameter));
+                }
+
+                self.function = FunctionScope::default();
+                self.function.parameters = function.parameters.iter().map(|parameter| parameter.symbol.clone()).collect();
+                self.in_function = true;
+                for default in function.defaults.iter().flatten() {
+                    self.process_expression(default);
+                }
+                let result = self.process_block(&function.body);
+                self.in_function = false;
+                self.function = FunctionScope::default();
+                result?;
+            },
+            ast::Statement::Return(Some(expression)) => self.process_expression(expression),
+            // The function name is a builtin or a FUNCTION, not a variable
+            ast::Statement::Call(call) => {
+                for argument in &call.arguments {
+                    self.process_expression(argument);
                 }
             },
-            ast::Statement::While(_, block) => self.process_block(block),
+            ast::Statement::Global(idents) | ast::Statement::Local(idents) => {
+                let scope = if matches!(statement, ast::Statement::Global(_)) { Scope::Global } else { Scope::Local };
+                self.declare_scope(idents, scope)?;
+            },
             _ => {}
         }
+
+        Ok(())
+    }
+
+    fn declare_scope(&mut self, idents: &[ast::Ident], scope: Scope) -> Result<(), RuntimeError> {
+        let keyword = if scope == Scope::Global { "GLOBAL" } else { "LOCAL" };
+        if !self.in_function {
+            return Err(RuntimeError::with_kind(RuntimeErrorKind::Syntax, format!("{} can only be used inside a FUNCTION", keyword)));
+        }
+
+        for ident in idents {
+            let name = &ident.symbol;
+            let problem = match self.function.declared.get(name) {
+                Some(existing) if *existing != scope => Some(format!("{} is declared both GLOBAL and LOCAL", name)),
+                _ if scope == Scope::Global && self.function.parameters.contains(name) => Some(format!("GLOBAL {} names a parameter of the function", name)),
+                // Otherwise the function would see one variable above the declaration and another below it
+                _ if self.function.used.contains(name) => Some(format!("{} is used in the function before {} {}", name, keyword, name)),
+                _ => None
+            };
+            if let Some(problem) = problem {
+                return Err(RuntimeError::with_kind(RuntimeErrorKind::Syntax, problem));
+            }
+
+            // Declared here if nothing else did, so assignments to it are allowed
+            if !self.symbols.contains_key(name) {
+                self.define_symbol(Symbol::declared_at(name.clone(), ident.id));
+            }
+            self.function.declared.insert(name.clone(), scope);
+        }
+
+        Ok(())
+    }
+
+    // Notes a name used in a function, and the scope its GLOBAL or LOCAL gave it
+    fn add_scope(&mut self, ident: &ast::Ident) {
+        if !self.in_function {
+            return;
+        }
+
+        // Only the parser hands out ids - a tree built in code has none to key on
+        match self.function.declared.get(&ident.symbol) {
+            Some(scope) if ident.id.0 != 0 => {
+                self.scopes.insert(ident.id, (ident.symbol.clone(), *scope));
+            },
+            _ => ()
+        }
+        self.function.used.insert(ident.symbol.clone());
+    }
+
+    fn add_use(&mut self, ident: &ast::Ident) {
+        self.add_scope(ident);
+        self.uses.push((ident.symbol.clone(), ident.id));
+    }
+
+    // A variable given a value but never read, and a FUNCTION's own variable taking the name
+    // of a global. Functions are defined before the program runs, so a global declared after
+    // one is still hidden by it.
+    fn check_declarations(&mut self) {
+        let declarations = std::mem::take(&mut self.declarations);
+        let mut warnings = Vec::new();
+
+        if self.enabled_warnings.is_enabled(WarningKind::Unused) {
+            let mut seen: HashSet<&str> = self.uses.iter().map(|(name, _)| name.as_str()).collect();
+            for (name, line, declared) in &declarations {
+                // Warned about once, at the first LET
+                if *declared != Declared::Parameter && seen.insert(name) {
+                    warnings.push(Warning::new(WarningKind::Unused, format!("{} is given a value but never read", name), Span::new(*line, 0)));
+                }
+            }
+        }
+
+        if self.enabled_warnings.is_enabled(WarningKind::Shadowing) {
+            let globals: HashMap<&str, usize> = declarations.iter().rev()
+                .filter(|(_, _, declared)| *declared == Declared::Global)
+                .map(|(name, line, _)| (name.as_str(), *line))
+                .collect();
+            for (name, line, declared) in &declarations {
+                let message = match (declared, globals.get(name.as_str())) {
+                    (Declared::Parameter, Some(global_line)) => format!("Parameter {} hides the global variable declared at line {}", name, global_line),
+                    (Declared::InFunction, Some(global_line)) => format!("{} hides the global variable declared at line {}", name, global_line),
+                    _ => continue
+                };
+
+                warnings.push(Warning::new(WarningKind::Shadowing, message, Span::new(*line, 0)));
+            }
+        }
+
+        warnings.sort_by_key(|warning| warning.get_line());
+        self.warnings = warnings;
+    }
+
+    fn add_constant(&mut self, literal: &ast::Literal, id: NodeId) {
+        // Only the parser hands out ids - a tree built in code has none to key on
+        if id.0 == 0 {
+            return;
+        }
+
+        let index = match self.constant_indexes.get(literal) {
+            Some(index) => *index,
+            None => {
+                self.constants.push(literal.clone());
+                self.constant_indexes.insert(literal.clone(), self.constants.len() - 1);
+                self.constants.len() - 1
+            }
+        };
+
+        let position = id.0 as usize;
+        if position >= self.constant_ids.len() {
+            self.constant_ids.resize(position + 1, None);
+        }
+        self.constant_ids[position] = Some(index);
+    }
+
+    fn process_assignment(&mut self, ident: &ast::Ident, expression: &ast::Expression) {
+        self.add_scope(ident);
+        self.assignments.push((ident.symbol.clone(), ident.id, expression.clone()));
+        self.process_expression(expression);
+    }
+
+    fn process_lvalue(&mut self, target: &ast::LValue) {
+        match target {
+            ast::LValue::Ident(ident) => self.add_use(ident),
+            ast::LValue::Index(target, index) => {
+                self.process_lvalue(target);
+                self.process_expression(index);
+            }
+        }
+    }
+
+    fn add_guard(&mut self, condition: &ast::Condition) {
+        let comparison = match condition {
+            ast::Condition::Compare(comparison) => comparison,
+            _ => return
+        };
+
+        if let (ast::Expression::Ident(_), ast::Expression::Literal(ast::Literal::Number(number), id)) = (&comparison.left_expression, &comparison.right_expression) {
+            if let Ok(limit) = number.parse() {
+                self.guards.insert(*id, limit);
+            }
+        }
     }
-}
\ No newline at end of file
+
+    fn process_condition(&mut self, condition: &ast::Condition) {
+        match condition {
+            ast::Condition::Compare(comparison) => {
+                self.process_expression(&comparison.left_expression);
+                self.process_expression(&comparison.right_expression);
+            },
+            ast::Condition::And(left, right) | ast::Condition::Or(left, right) => {
+                self.process_condition(left);
+                self.process_condition(right);
+            },
+            ast::Condition::Not(condition) => self.process_condition(condition)
+        }
+    }
+
+    fn process_expression(&mut self, expression: &ast::Expression) {
+        match expression {
+            ast::Expression::Literal(literal, id) => self.add_constant(literal, *id),
+            ast::Expression::Ident(ident) => self.add_use(ident),
+            ast::Expression::BinaryOp(op) => {
+                self.process_expression(&op.left_term);
+                self.process_expression(&op.right_term);
+            },
+            ast::Expression::UnaryOp(op) => self.process_expression(&op.term),
+            // The function name is a builtin or a FUNCTION, not a variable
+            ast::Expression::Call(call) => {
+                for argument in &call.arguments {
+                    self.process_expression(argument);
+                }
+            },
+            ast::Expression::Index(index) => {
+                self.process_expression(&index.target);
+                self.process_expression(&index.index);
+            },
+            ast::Expression::Array(elements) => {
+                for element in elements {
+                    self.process_expression(element);
+                }
+            },
+            ast::Expression::Map(entries) => {
+                for (key, value) in entries {
+                    self.process_expression(key);
+                    self.process_expression(value);
+                }
+            }
+        }
+    }
+
+    // Fills the side tables once the whole tree has been seen - a variable can be used
+    // above the assignment that gives it its type, inside a loop
+    fn resolve(&mut self) {
+        let assignments = std::mem::take(&mut self.assignments);
+        let uses = std::mem::take(&mut self.uses);
+        let parameters = std::mem::take(&mut self.parameters);
+
+        // Widen each variable's type until no assignment changes it - a type can only go
+        // from unknown to known to mixed, so this settles
+        let mut variables: HashMap<&str, Inferred> = parameters.iter()
+            .map(|(name, _)| (name.as_str(), Inferred::Mixed))
+            .collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (name, _, expression) in &assignments {
+                let inferred = match infer(expression, &variables) {
+                    Some(inferred) => inferred,
+                    None => continue
+                };
+
+                let merged = match variables.get(name.as_str()) {
+                    None => inferred,
+                    Some(current) if *current == inferred => inferred,
+                    Some(_) => Inferred::Mixed
+                };
+
+                if variables.insert(name, merged) != Some(merged) {
+                    changed = true;
+                }
+            }
+        }
+
+        let references = assignments.iter().map(|(name, id, _)| (name, id))
+            .chain(uses.iter().map(|(name, id)| (name, id)))
+            .chain(parameters.iter().map(|(name, id)| (name, id)));
+        for (name, id) in references {
+            // Only the parser hands out ids - a tree built in code has none to key on
+            if id.0 == 0 {
+                continue;
+            }
+
+            if let Some(symbol) = self.symbols.get(name) {
+                self.resolutions.insert(*id, name.clone());
+
+                let index = id.0 as usize;
+                if index >= self.slots.len() {
+                    self.slots.resize(index + 1, None);
+                }
+                self.slots[index] = Some(symbol.slot);
+            }
+
+            if let Some(Inferred::Known(known)) = variables.get(name.as_str()) {
+                self.types.insert(*id, *known);
+            }
+        }
+    }
+
+    fn process_if(&mut self, if_statement: &ast::IfStatement) -> Result<(), RuntimeError> {
+        match if_statement {
+            ast::IfStatement::If(condition, block, other) | ast::IfStatement::ElseIf(condition, block, other) => {
+                self.process_condition(condition);
+                self.process_block(block)?;
+                match other {
+                    Some(other) => self.process_if(other),
+                    None => Ok(())
+                }
+            },
+            ast::IfStatement::Else(block) => self.process_block(block)
+        }
+    }
+}
+
+// None until every variable the expression reads has been given a type
+fn infer(expression: &ast::Expression, variables: &HashMap<&str, Inferred>) -> Option<Inferred> {
+    match expression {
+        ast::Expression::Literal(ast::Literal::String(_), _) => Some(Inferred::Known(Type::String)),
+        ast::Expression::Literal(ast::Literal::Number(_), _) => Some(Inferred::Known(Type::Number)),
+        ast::Expression::Literal(ast::Literal::Decimal(_), _) => Some(Inferred::Known(Type::Decimal)),
+        ast::Expression::Literal(ast::Literal::Boolean(_), _) => Some(Inferred::Known(Type::Boolean)),
+        ast::Expression::Array(_) => Some(Inferred::Known(Type::Array)),
+        ast::Expression::Map(_) => Some(Inferred::Known(Type::Map)),
+        ast::Expression::Ident(ident) => variables.get(ident.symbol.as_str()).copied(),
+        ast::Expression::BinaryOp(op) => {
+            match (infer(&op.left_term, variables)?, infer(&op.right_term, variables)?) {
+                (Inferred::Known(left), Inferred::Known(right)) if left == right => Some(Inferred::Known(left)),
+                _ => Some(Inferred::Mixed)
+            }
+        },
+        ast::Expression::UnaryOp(op) => infer(&op.term, variables),
+        // Builtins and indexing don't say what they return
+        ast::Expression::Call(_) | ast::Expression::Index(_) => Some(Inferred::Mixed)
+    }
+}
@@ -1,30 +1,67 @@
+use super::error::Span;
+
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 pub enum TokenType {
     EOF,
     PROGRAM,
     NEWLINE,
     NUMBER,
+    DECIMAL,
     IDENT,
     STRING,
     SEMICOLON,
+    COLON,
+    LPAREN,
+    RPAREN,
+    LBRACKET,
+    RBRACKET,
+    LBRACE,
+    RBRACE,
+    COMMA,
     BLOCK,
     ILLEGAL,
+    // An operator the script declared with OPERATOR
+    SYMBOL,
 
     // Keywords
     LET,
     PRINT,
+    EPRINT,
     END,
     IF,
     THEN,
     WHILE,
     ELSEIF,
     ELSE,
+    BREAKPOINT,
+    INSPECT,
+    NAN,
+    INF,
+    TRUE,
+    FALSE,
+    AND,
+    OR,
+    NOT,
+    MACRO,
+    BREAK,
+    CONTINUE,
+    FUNCTION,
+    RETURN,
+    FOR,
+    TO,
+    STEP,
+    MEMO,
+    GLOBAL,
+    LOCAL,
+    OPERATOR,
 
     // Operators
     EQ,
     PLUS,
     MINUS,
     ASTERISK,
+    CARET,
     SLASH,
     EQEQ,
     NOTEQ,
@@ -34,37 +71,74 @@ pub enum TokenType {
     GTEQ,
 }
 
+// Every reserved word, as the lexer recognises it (case-insensitively)
+pub const KEYWORDS: [&str; 30] = ["LET", "PRINT", "EPRINT", "END", "IF", "THEN", "WHILE", "ELSEIF", "ELSE", "BREAKPOINT", "INSPECT", "NAN", "INF", "TRUE", "FALSE", "AND", "OR", "NOT", "MACRO", "BREAK", "CONTINUE", "FUNCTION", "RETURN", "FOR", "TO", "STEP", "MEMO", "GLOBAL", "LOCAL", "OPERATOR"];
+
 impl TokenType {
     pub fn get_keyword_token(text: &str) -> Option<TokenType> {
         let upper_text = text.to_uppercase();
         match upper_text.as_str() {
             "LET"    => Some(TokenType::LET),
             "PRINT"  => Some(TokenType::PRINT),
+            "EPRINT" => Some(TokenType::EPRINT),
             "END"    => Some(TokenType::END),
             "IF"     => Some(TokenType::IF),
             "THEN"   => Some(TokenType::THEN),
             "WHILE"  => Some(TokenType::WHILE),
             "ELSEIF" => Some(TokenType::ELSEIF),
             "ELSE"   => Some(TokenType::ELSE),
+            "BREAKPOINT" => Some(TokenType::BREAKPOINT),
+            "INSPECT" => Some(TokenType::INSPECT),
+            "NAN"    => Some(TokenType::NAN),
+            "INF"    => Some(TokenType::INF),
+            "TRUE"   => Some(TokenType::TRUE),
+            "FALSE"  => Some(TokenType::FALSE),
+            "AND"    => Some(TokenType::AND),
+            "OR"     => Some(TokenType::OR),
+            "NOT"    => Some(TokenType::NOT),
+            "MACRO"  => Some(TokenType::MACRO),
+            "BREAK"  => Some(TokenType::BREAK),
+            "CONTINUE" => Some(TokenType::CONTINUE),
+            "FUNCTION" => Some(TokenType::FUNCTION),
+            "RETURN" => Some(TokenType::RETURN),
+            "FOR"    => Some(TokenType::FOR),
+            "TO"     => Some(TokenType::TO),
+            "STEP"   => Some(TokenType::STEP),
+            "MEMO"   => Some(TokenType::MEMO),
+            "GLOBAL" => Some(TokenType::GLOBAL),
+            "LOCAL"  => Some(TokenType::LOCAL),
+            "OPERATOR" => Some(TokenType::OPERATOR),
             _        => None
         }
     }
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
 pub struct Token {
     token_type: TokenType,
     token_text: String,
+    span: Span,
 }
 
 impl Token {
     pub fn new(token_type: TokenType, token_text: String) -> Token {
         Token {
             token_type: token_type,
-            token_text: token_text
+            token_text: token_text,
+            span: Span::default(),
         }
     }
 
+    pub fn at(mut self, span: Span) -> Token {
+        self.span = span;
+        self
+    }
+
+    pub fn get_span(&self) -> Span {
+        self.span
+    }
+
     pub fn get_token_type(&self) -> &TokenType {
         &self.token_type
     }
@@ -72,4 +146,8 @@ impl Token {
     pub fn get_token_text(&self) -> &str {
         &self.token_text
     }
+
+    pub fn get_line(&self) -> usize {
+        self.span.line
+    }
 }
@@ -0,0 +1,111 @@
+use super::ast;
+
+use serde::Serialize;
+
+use std::io;
+use std::io::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TraceFormat {
+    // line 3: let x = 5
+    Text,
+    // {"line":3,"kind":"let","variable":"x","value":"5"} - one object per line
+    JsonLines,
+}
+
+#[derive(Debug, Serialize)]
+struct TraceLine {
+    line: usize,
+    kind: String,
+    variable: Option<String>,
+    value: Option<String>,
+}
+
+// Writes a line for every statement the program executes. A statement's line only goes
+// out once the next one starts, so an assignment can report the value it stored.
+pub struct Tracer<'a> {
+    format: TraceFormat,
+    output: Box<dyn Write + 'a>,
+    pending: Option<TraceLine>,
+}
+
+impl TraceFormat {
+    pub fn from_name(name: &str) -> Option<TraceFormat> {
+        match name {
+            "text" => Some(TraceFormat::Text),
+            "jsonl" => Some(TraceFormat::JsonLines),
+            _ => None
+        }
+    }
+}
+
+impl<'a> Tracer<'a> {
+    pub fn new(format: TraceFormat, output: Box<dyn Write + 'a>) -> Tracer<'a> {
+        Tracer {
+            format: format,
+            output: output,
+            pending: None,
+        }
+    }
+
+    pub fn enter(&mut self, line: usize, statement: &ast::Statement) -> io::Result<()> {
+        self.flush()?;
+        self.pending = Some(TraceLine {
+            line: line,
+            kind: statement_kind(statement),
+            variable: None,
+            value: None,
+        });
+        Ok(())
+    }
+
+    pub fn assigned(&mut self, name: &str, value: String) {
+        if let Some(pending) = &mut self.pending {
+            pending.variable = Some(String::from(name));
+            pending.value = Some(value);
+        }
+    }
+
+    // Writes out the last statement entered - called once the run is over
+    pub fn flush(&mut self) -> io::Result<()> {
+        let pending = match self.pending.take() {
+            Some(pending) => pending,
+            None => return Ok(())
+        };
+
+        match (self.format, &pending.variable, &pending.value) {
+            (TraceFormat::JsonLines, _, _) => {
+                let json = serde_json::to_string(&pending).expect("Trace lines always serialize");
+                writeln!(self.output, "{}", json)
+            },
+            (TraceFormat::Text, Some(variable), Some(value)) => writeln!(self.output, "line {}: {} {} = {}", pending.line, pending.kind, variable, value),
+            (TraceFormat::Text, _, _) => writeln!(self.output, "line {}: {}", pending.line, pending.kind)
+        }
+    }
+}
+
+fn statement_kind(statement: &ast::Statement) -> String {
+    let kind = match statement {
+        ast::Statement::Print(_) => "print",
+        ast::Statement::EPrint(_) => "eprint",
+        ast::Statement::Let(_, _) => "let",
+        ast::Statement::Assignment(_, _) => "assignment",
+        ast::Statement::If(_) => "if",
+        ast::Statement::While(_, _) => "while",
+        ast::Statement::For(_) => "for",
+        ast::Statement::Breakpoint => "breakpoint",
+        ast::Statement::Inspect(_) => "inspect",
+        // Only loops are labeled
+        ast::Statement::Labeled(_, statement) => return statement_kind(statement),
+        ast::Statement::Break(_) => "break",
+        ast::Statement::Continue(_) => "continue",
+        ast::Statement::Host(host) => return host.keyword.to_lowercase(),
+        ast::Statement::FunctionDef(_) => "function",
+        ast::Statement::Return(_) => "return",
+        ast::Statement::Call(_) => "call",
+        ast::Statement::Global(_) => "global",
+        ast::Statement::Local(_) => "local"
+    };
+
+    String::from(kind)
+}
@@ -0,0 +1,331 @@
+use super::date::DateTime;
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::rc::Rc;
+
+// Rounding further than a decimal can hold isn't meaningful
+pub const MAX_DECIMAL_PLACES: usize = 28;
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    Number(f32),
+    Decimal(Decimal),
+    String(Rc<str>),
+    Boolean(bool),
+    DateTime(DateTime),
+    // Raw file contents, kept apart from strings so bytes that aren't UTF-8 survive
+    Bytes(Rc<[u8]>),
+    // Assigning shares the elements rather than copying them, but setting an element makes
+    // a new array, so another variable holding the old one doesn't see the change. Nothing
+    // is changed in place, so an array can't end up inside itself and no cycle can form.
+    Array(Rc<[Value]>),
+    // Keys are strings, kept in the order they were first set. Copied like an array.
+    Map(Rc<[(Rc<str>, Value)]>),
+}
+
+// How numbers are turned into text - the default is the shortest form that reads back the same
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NumberFormat {
+    pub precision: Option<usize>,
+    pub thousands_separator: Option<char>,
+}
+
+// Only the program's literals are pooled, so it never grows past the source - strings built
+// while running are dropped like any other value
+#[derive(Default)]
+pub struct StringPool {
+    strings: HashSet<Rc<str>>,
+    // Where each pooled string lives - no two hold the same text, so two of them are equal
+    // exactly when they're the same one
+    addresses: HashSet<usize>,
+}
+
+impl Value {
+    pub fn to_number(&self) -> Result<f32, String> {
+        match self {
+            Value::Number(n) => Ok(*n),
+            Value::Decimal(d) => d.to_f32().ok_or_else(|| format!("{} is out of range for a number", d)),
+            Value::String(s) => s.parse().map_err(|_| format!("{:?} is not a number", s)),
+            Value::Boolean(_) | Value::DateTime(_) => Err(format!("{} is not a number", self)),
+            Value::Bytes(_) => Err(String::from("Bytes are not a number")),
+            Value::Array(_) => Err(String::from("An array is not a number")),
+            Value::Map(_) => Err(String::from("A map is not a number"))
+        }
+    }
+
+    // Floats go through their shortest printed form, so 0.1 becomes exactly 0.1
+    pub fn to_decimal(&self) -> Result<Decimal, String> {
+        match self {
+            Value::Number(n) => n.to_string().parse().map_err(|_| format!("{} can't be represented as a decimal", n)),
+            Value::Decimal(d) => Ok(*d),
+            Value::String(s) => s.parse().map_err(|_| format!("{:?} is not a number", s)),
+            Value::Boolean(_) | Value::DateTime(_) => Err(format!("{} is not a number", self)),
+            Value::Bytes(_) => Err(String::from("Bytes are not a number")),
+            Value::Array(_) => Err(String::from("An array is not a number")),
+            Value::Map(_) => Err(String::from("A map is not a number"))
+        }
+    }
+
+    pub fn is_decimal(&self) -> bool {
+        matches!(self, Value::Decimal(_))
+    }
+
+    // The == of a condition, taken into arrays and maps element by element - unlike
+    // PartialEq, a NaN isn't equal even to itself and a number equals the same decimal. A
+    // string holding a number is still only a string here.
+    pub fn equals(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) => a == b,
+            (Value::Number(_), Value::Decimal(_)) | (Value::Decimal(_), Value::Number(_)) => match (self.to_decimal(), other.to_decimal()) {
+                (Ok(a), Ok(b)) => a == b,
+                _ => false
+            },
+            (Value::Array(a), Value::Array(b)) => a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| a.equals(b)),
+            // As with PartialEq, the order the keys were set in doesn't matter
+            (Value::Map(a), Value::Map(b)) => a.len() == b.len() && a.iter().all(|(key, value)| {
+                b.iter().any(|(other_key, other_value)| key == other_key && value.equals(other_value))
+            }),
+            _ => self == other
+        }
+    }
+
+    // The same value in storage of its own, down to the last element - strings and bytes
+    // never change, so they stay shared
+    pub fn deep_copy(&self) -> Value {
+        match self {
+            Value::Array(elements) => Value::Array(elements.iter().map(Value::deep_copy).collect()),
+            Value::Map(entries) => Value::Map(entries.iter().map(|(key, value)| (Rc::clone(key), value.deep_copy())).collect()),
+            other => other.clone()
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Number(_) => "number",
+            Value::Decimal(_) => "decimal",
+            Value::String(_) => "string",
+            Value::Boolean(_) => "boolean",
+            Value::DateTime(_) => "date",
+            Value::Bytes(_) => "bytes",
+            Value::Array(_) => "array",
+            Value::Map(_) => "map",
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            // This is identity, not the numeric == of a condition - a NaN is the same value as
+            // another NaN, which keeps the debugger from seeing a change on every reassignment
+            (Value::Number(a), Value::Number(b)) => a == b || (a.is_nan() && b.is_nan()),
+            (Value::Decimal(a), Value::Decimal(b)) => a == b,
+            // Interned strings share an allocation, so equal strings are usually the same pointer
+            (Value::String(a), Value::String(b)) => Rc::ptr_eq(a, b) || a == b,
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            (Value::DateTime(a), Value::DateTime(b)) => a == b,
+            (Value::Bytes(a), Value::Bytes(b)) => a == b,
+            (Value::Array(a), Value::Array(b)) => a == b,
+            // The same keys with the same values, whichever order they were set in
+            (Value::Map(a), Value::Map(b)) => a.len() == b.len() && a.iter().all(|(key, value)| {
+                b.iter().any(|(other_key, other_value)| key == other_key && value == other_value)
+            }),
+            _ => false
+        }
+    }
+}
+
+// Identity as PartialEq has it is an equivalence, NaN included, so values can key a table
+impl Eq for Value {}
+
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            // Equal numbers hash the same - 0 and -0 are one value, and so is every NaN
+            Value::Number(n) if *n == 0.0 => 0.0f32.to_bits().hash(state),
+            Value::Number(n) if n.is_nan() => f32::NAN.to_bits().hash(state),
+            Value::Number(n) => n.to_bits().hash(state),
+            Value::Decimal(d) => d.hash(state),
+            Value::String(s) => s.hash(state),
+            Value::Boolean(b) => b.hash(state),
+            Value::DateTime(date) => date.hash(state),
+            Value::Bytes(bytes) => bytes.hash(state),
+            Value::Array(elements) => elements.hash(state),
+            // Maps are equal whatever order their keys were set in, so the entries are hashed
+            // on their own and combined in a way that doesn't depend on order
+            Value::Map(entries) => {
+                let combined = entries.iter().fold(0u64, |combined, entry| {
+                    let mut hasher = DefaultHasher::new();
+                    entry.hash(&mut hasher);
+                    combined.wrapping_add(hasher.finish())
+                });
+                entries.len().hash(state);
+                combined.hash(state);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.format(&NumberFormat::default()))
+    }
+}
+
+impl Value {
+    // Strings are left alone - only numbers are affected by the format
+    pub fn format(&self, format: &NumberFormat) -> String {
+        let text = match (self, format.precision) {
+            // Printed the way the literals are written
+            (Value::Number(n), _) if n.is_nan() => return String::from("NAN"),
+            (Value::Number(n), _) if n.is_infinite() => return String::from(if *n < 0.0 { "-INF" } else { "INF" }),
+            (Value::Number(n), Some(precision)) => format!("{:.*}", precision, n),
+            (Value::Number(n), None) => format_shortest(*n),
+            (Value::Decimal(d), Some(precision)) => format!("{:.*}", precision, d),
+            (Value::Decimal(d), None) => d.to_string(),
+            (Value::String(s), _) => return s.to_string(),
+            (Value::Boolean(b), _) => return String::from(if *b { "TRUE" } else { "FALSE" }),
+            (Value::DateTime(date), _) => return date.to_string(),
+            (Value::Bytes(bytes), _) => return encode_hex(bytes),
+            (Value::Array(elements), _) => return format_array(elements, format),
+            (Value::Map(entries), _) => return format_map(entries, format),
+        };
+
+        match format.thousands_separator {
+            Some(separator) => group_thousands(&text, separator),
+            None => text
+        }
+    }
+}
+
+// [1, "two", [3]] - strings are quoted so their edges and commas can be told apart. Arrays
+// and maps hold copies, never references, so a value can't contain itself and there's no
+// cycle to guard against.
+fn format_array(elements: &[Value], format: &NumberFormat) -> String {
+    let elements: Vec<String> = elements.iter()
+        .map(|element| format_element(element, format))
+        .collect();
+    format!("[{}]", elements.join(", "))
+}
+
+// {"a": 1, "b": [2]} - laid out the way the literal is written
+fn format_map(entries: &[(Rc<str>, Value)], format: &NumberFormat) -> String {
+    let entries: Vec<String> = entries.iter()
+        .map(|(key, value)| format!("{:?}: {}", key, format_element(value, format)))
+        .collect();
+    format!("{{{}}}", entries.join(", "))
+}
+
+fn format_element(element: &Value, format: &NumberFormat) -> String {
+    match element {
+        Value::String(s) => format!("{:?}", s),
+        _ => element.format(format)
+    }
+}
+
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// The fewest digits that read back as the same float, laid out by fixed rules so a result
+// prints the same everywhere - plain digits for everyday magnitudes and an exponent beyond
+// them, the way JavaScript does. -0 prints as 0.
+fn format_shortest(n: f32) -> String {
+    if n == 0.0 {
+        return String::from("0");
+    }
+
+    // The exponent form gives the shortest digits on their own, e.g. -1.2345e3
+    let scientific = format!("{:e}", n);
+    let (mantissa, exponent) = scientific.split_once('e').expect("Exponent formatting always has an e");
+    let exponent: i32 = exponent.parse().expect("Exponents are always whole numbers");
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", mantissa)
+    };
+
+    if !(-6..21).contains(&exponent) {
+        return format!("{}{}e{}", sign, mantissa, exponent);
+    }
+
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let point = exponent + 1;
+    let text = if point <= 0 {
+        format!("0.{}{}", "0".repeat(-point as usize), digits)
+    } else if digits.len() <= point as usize {
+        format!("{}{}", digits, "0".repeat(point as usize - digits.len()))
+    } else {
+        format!("{}.{}", &digits[..point as usize], &digits[point as usize..])
+    };
+
+    format!("{}{}", sign, text)
+}
+
+// 1234567.5 -> 1,234,567.5
+fn group_thousands(text: &str, separator: char) -> String {
+    let (sign, unsigned) = match text.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", text)
+    };
+    let (whole, fraction) = match unsigned.find('.') {
+        Some(point) => unsigned.split_at(point),
+        None => (unsigned, "")
+    };
+
+    let mut grouped = String::from(sign);
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i) % 3 == 0 {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+
+    grouped.push_str(fraction);
+    grouped
+}
+
+impl StringPool {
+    pub fn new() -> StringPool {
+        StringPool { strings: HashSet::new(), addresses: HashSet::new() }
+    }
+
+    // Literals live as long as the program, so pool them whatever their size -
+    // otherwise a long literal would be copied every time it is evaluated
+    pub fn intern(&mut self, text: &str) -> Rc<str> {
+        match self.strings.get(text) {
+            Some(interned) => Rc::clone(interned),
+            None => {
+                let interned: Rc<str> = Rc::from(text);
+                self.strings.insert(Rc::clone(&interned));
+                self.addresses.insert(address(&interned));
+                interned
+            }
+        }
+    }
+
+    // Two pooled strings compare by pointer alone - only a string built at runtime needs
+    // its text looked at
+    pub fn equal(&self, a: &Rc<str>, b: &Rc<str>) -> bool {
+        if Rc::ptr_eq(a, b) {
+            return true;
+        }
+
+        if self.addresses.contains(&address(a)) && self.addresses.contains(&address(b)) {
+            return false;
+        }
+
+        a == b
+    }
+}
+
+fn address(text: &Rc<str>) -> usize {
+    Rc::as_ptr(text) as *const u8 as usize
+}
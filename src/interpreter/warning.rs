@@ -0,0 +1,110 @@
+use super::error::Span;
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WarningKind {
+    // `=` where a comparison was expected - almost always meant to be `==`
+    AssignmentInCondition,
+    // A variable given a value with LET that nothing ever reads
+    Unused,
+    // A function's parameter or LET taking the name of a global, which it then hides
+    Shadowing,
+    // A string read as a number because it was used in arithmetic or a comparison
+    Coercion,
+}
+
+// Which kinds of warning are reported. The rest are dropped where they would be found, so
+// nothing is spent looking for them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WarningSet {
+    enabled: Vec<WarningKind>,
+}
+
+// Something suspicious in the source that doesn't stop the program from running
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    kind: WarningKind,
+    message: String,
+    span: Span,
+}
+
+impl WarningKind {
+    pub const ALL: [WarningKind; 4] = [WarningKind::AssignmentInCondition, WarningKind::Unused, WarningKind::Shadowing, WarningKind::Coercion];
+
+    // What -W and -A call it, and the code its diagnostics carry
+    pub fn name(&self) -> &'static str {
+        match self {
+            WarningKind::AssignmentInCondition => "assignment-in-condition",
+            WarningKind::Unused => "unused",
+            WarningKind::Shadowing => "shadowing",
+            WarningKind::Coercion => "coercion",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<WarningKind> {
+        WarningKind::ALL.iter().copied().find(|kind| kind.name().eq_ignore_ascii_case(name))
+    }
+}
+
+impl Default for WarningSet {
+    // Unused and shadowing are asked for with -W. EVAL's text can read a variable the
+    // analysis never sees used, and a parameter named like a global is often deliberate.
+    fn default() -> WarningSet {
+        WarningSet {
+            enabled: vec![WarningKind::AssignmentInCondition, WarningKind::Coercion],
+        }
+    }
+}
+
+impl WarningSet {
+    pub fn warn(&mut self, kind: WarningKind) {
+        if !self.is_enabled(kind) {
+            self.enabled.push(kind);
+        }
+    }
+
+    pub fn allow(&mut self, kind: WarningKind) {
+        self.enabled.retain(|enabled| *enabled != kind);
+    }
+
+    pub fn is_enabled(&self, kind: WarningKind) -> bool {
+        self.enabled.contains(&kind)
+    }
+}
+
+impl Warning {
+    pub fn new(kind: WarningKind, message: String, span: Span) -> Warning {
+        Warning {
+            kind: kind,
+            message: message,
+            span: span,
+        }
+    }
+
+    pub fn get_kind(&self) -> WarningKind {
+        self.kind
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn get_span(&self) -> Span {
+        self.span
+    }
+
+    pub fn get_line(&self) -> usize {
+        self.span.line
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Warnings from analysing or running the program only know the line
+        match self.span.column {
+            0 => write!(f, "Warning at line {} - {}", self.span.line, self.message),
+            column => write!(f, "Warning at line {}, column {} - {}", self.span.line, column, self.message)
+        }
+    }
+}
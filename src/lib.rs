@@ -0,0 +1,9 @@
+#![allow(clippy::redundant_field_names)]
+
+pub mod interpreter;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
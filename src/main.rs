@@ -1,26 +1,648 @@
-pub mod interpreter;
+mod golden;
+mod repl;
 
-use interpreter::intr::Interpreter;
-use interpreter::lexer::Lexer;
-use interpreter::parser::Parser;
+use hello_rust::interpreter::ast::AbstractSyntaxTree;
+use hello_rust::interpreter::builtins::Module;
+use hello_rust::interpreter::diagnostic::Diagnostic;
+use hello_rust::interpreter::diagnostic::Severity;
+use hello_rust::interpreter::error::RuntimeErrorKind;
+use hello_rust::interpreter::intr::Interpreter;
+use hello_rust::interpreter::lexer;
+use hello_rust::interpreter::lexer::Lexer;
+use hello_rust::interpreter::manifest;
+use hello_rust::interpreter::manifest::Manifest;
+use hello_rust::interpreter::memory::CountingAllocator;
+use hello_rust::interpreter::options::ColorChoice;
+use hello_rust::interpreter::options::Config;
+use hello_rust::interpreter::parser::ParseLimits;
+use hello_rust::interpreter::parser::Parser;
+use hello_rust::interpreter::postmortem;
+use hello_rust::interpreter::preprocessor;
+use hello_rust::interpreter::profile::ProfileFormat;
+use hello_rust::interpreter::project;
+use hello_rust::interpreter::trace::TraceFormat;
+use hello_rust::interpreter::value::MAX_DECIMAL_PLACES;
+use hello_rust::interpreter::value::NumberFormat;
+use hello_rust::interpreter::warning::WarningKind;
+use hello_rust::interpreter::warning::WarningSet;
 
+use std::collections::HashMap;
 use std::env;
+use std::fmt;
 use std::fs;
+use std::path::Path;
+use std::process;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+// Exit codes follow sysexits.h, so scripts can tell a bad command line from a bad program
+const EXIT_USAGE: i32 = 2;
+const EXIT_PARSE: i32 = 65;
+const EXIT_RUNTIME: i32 = 70;
+
+const USAGE: &str = "Usage: hello_rust [run] [--debug] [--post-mortem] [--coverage=<out.lcov>] [--mem-stats] [--profile=table|json] [--flamegraph=<out.folded>] [--emit-ast=<out.json>] [--from-json] [--timeout=<5s>] [--max-statements=<n>] [--max-depth=<n>] [--max-nesting=<n>] [--max-tokens=<n>] [--precision=<n>] [--print-separator=<text>] [--print-terminator=<text>] [--quiet] [--implicit-vars] [--modules=<math,string,...>] [--error-format=text|json] [--color=auto|always|never] [--trace] [--trace-format=text|jsonl] [--verbose] [-D NAME=value]... [-W warning]... [-A warning]... <file>... (or a project.toml in the current directory)";
+
+const FLAGS: [&str; 25] = [
+    "--debug", "--post-mortem", "--mem-stats", "--profile", "--flamegraph", "--from-json", "--quiet", "--coverage", "--emit-ast",
+    "--timeout", "--max-statements", "--max-depth", "--max-nesting", "--max-tokens", "--precision", "--print-separator", "--print-terminator", "--implicit-vars", "--modules",
+    "--error-format", "--color", "--trace", "--trace-format", "--verbose", "--help",
+];
+
+// The parser and interpreter recurse once per level of nesting, and their own limits fit any
+// thread. Running on one of our own with a known stack lets a program nest further than that.
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+const MAX_NESTING: usize = 200;
+const MAX_DEPTH: usize = 10_000;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
 
 fn main() {
-    // TODO file might not be present, if so drop to REPL
-    let args: Vec<String> = env::args().collect();
-    let filename = &args[1];
+    let runner = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run)
+        .expect("Unable to start the interpreter thread");
+
+    // A panic has already been reported by the hook - exit the way an unwinding main would
+    if runner.join().is_err() {
+        process::exit(101);
+    }
+}
+
+fn run() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+
+    if args.first().map(|arg| arg == "test-dir").unwrap_or(false) {
+        let dir = match args.get(1) {
+            Some(dir) => dir,
+            None => usage_error("Usage: hello_rust test-dir <dir>")
+        };
+        process::exit(if golden::run(dir) { 0 } else { 1 });
+    }
+
+    // `run` is the default subcommand, so it can be left off
+    if args.first().map(|arg| arg == "run").unwrap_or(false) {
+        args.remove(0);
+    }
+
+    if args.iter().any(|arg| arg == "--help") {
+        println!("{}", USAGE);
+        return;
+    }
+
+    let defines = match take_defines(&mut args) {
+        Ok(defines) => defines,
+        Err(err) => usage_error(&err)
+    };
+    let enabled_warnings = match take_warning_flags(&mut args) {
+        Ok(enabled_warnings) => enabled_warnings,
+        Err(err) => usage_error(&err)
+    };
+
+    if let Some(unknown) = args.iter().find(|arg| arg.starts_with("--") && !is_known_flag(arg)) {
+        usage_error(&format!("Unknown option {}\n{}", unknown, USAGE));
+    }
+
+    let debug = args.iter().any(|arg| arg == "--debug");
+    let post_mortem = args.iter().any(|arg| arg == "--post-mortem");
+    let mem_stats = args.iter().any(|arg| arg == "--mem-stats");
+    let from_json = args.iter().any(|arg| arg == "--from-json");
+    let quiet = args.iter().any(|arg| arg == "--quiet");
+    let verbose = args.iter().any(|arg| arg == "--verbose");
+    let coverage_path = flag_value(&args, "--coverage");
+    let flamegraph_path = flag_value(&args, "--flamegraph");
+    let emit_ast_path = flag_value(&args, "--emit-ast");
+    let timeout = match flag_value(&args, "--timeout").map(parse_duration) {
+        Some(Ok(timeout)) => Some(timeout),
+        Some(Err(err)) => usage_error(&err),
+        None => None
+    };
+    let precision = match flag_value(&args, "--precision").map(|precision| precision.parse::<usize>()) {
+        // Like FORMATNUM, no further than a decimal can hold
+        Some(Ok(precision)) => Some(precision.min(MAX_DECIMAL_PLACES)),
+        Some(Err(err)) => usage_error(&format!("Invalid --precision - {}", err)),
+        None => None
+    };
+    let print_separator = flag_value(&args, "--print-separator").map(unescape);
+    let print_terminator = flag_value(&args, "--print-terminator").map(unescape);
+    let statement_budget = match flag_value(&args, "--max-statements").map(|budget| budget.parse::<u64>()) {
+        Some(Ok(budget)) => Some(budget),
+        Some(Err(err)) => usage_error(&format!("Invalid --max-statements - {}", err)),
+        None => None
+    };
+    let max_depth = match flag_value(&args, "--max-depth").map(|depth| depth.parse::<usize>()) {
+        Some(Ok(depth)) => Some(depth),
+        Some(Err(err)) => usage_error(&format!("Invalid --max-depth - {}", err)),
+        None => None
+    };
+    let mut limits = ParseLimits {
+        max_nesting: MAX_NESTING,
+        ..ParseLimits::default()
+    };
+    match flag_value(&args, "--max-nesting").map(|nesting| nesting.parse::<usize>()) {
+        Some(Ok(nesting)) => limits.max_nesting = nesting,
+        Some(Err(err)) => usage_error(&format!("Invalid --max-nesting - {}", err)),
+        None => {}
+    }
+    match flag_value(&args, "--max-tokens").map(|tokens| tokens.parse::<usize>()) {
+        Some(Ok(tokens)) => limits.max_tokens = Some(tokens),
+        Some(Err(err)) => usage_error(&format!("Invalid --max-tokens - {}", err)),
+        None => {}
+    }
+    let json_errors = match flag_value(&args, "--error-format") {
+        None | Some("text") => false,
+        Some("json") => true,
+        Some(other) => usage_error(&format!("Unknown --error-format {:?} - use text or json", other))
+    };
+
+    // Flags given here take the place of the defaults in .rustinterp.toml
+    let flags = Config {
+        strict: if args.iter().any(|arg| arg == "--implicit-vars") { Some(false) } else { None },
+        color: match flag_value(&args, "--color").map(|color| (color, ColorChoice::from_name(color))) {
+            Some((_, Some(color))) => Some(color),
+            Some((color, None)) => usage_error(&format!("Unknown --color {:?} - use auto, always or never", color)),
+            None => None
+        },
+        modules: flag_value(&args, "--modules").map(|modules| modules.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(String::from)
+            .collect()),
+        ..Config::default()
+    };
+    let config = match Config::load() {
+        Ok(config) => config.merge(flags),
+        Err(err) => usage_error(&err.to_string())
+    };
+    let mut implicit_vars = !config.is_strict();
+    let modules = match config.get_modules().map(parse_modules) {
+        Some(Ok(modules)) => Some(modules),
+        Some(Err(err)) => usage_error(&err),
+        None => None
+    };
+    let reporter = Reporter {
+        json: json_errors,
+        color: config.get_color().is_enabled(),
+    };
+    let profile = match flag_value(&args, "--profile").map(|format| (format, ProfileFormat::from_name(format))) {
+        Some((_, Some(format))) => Some(format),
+        Some((format, None)) => usage_error(&format!("Unknown --profile {:?} - use table or json", format)),
+        None if args.iter().any(|arg| arg == "--profile") => Some(ProfileFormat::Table),
+        None => None
+    };
+    // Giving a format is enough to turn tracing on
+    let trace = match flag_value(&args, "--trace-format").map(|format| (format, TraceFormat::from_name(format))) {
+        Some((_, Some(format))) => Some(format),
+        Some((format, None)) => usage_error(&format!("Unknown --trace-format {:?} - use text or jsonl", format)),
+        None if args.iter().any(|arg| arg == "--trace") => Some(TraceFormat::Text),
+        None => None
+    };
+    let mut filenames: Vec<String> = args.iter()
+        .filter(|arg| !arg.starts_with("--"))
+        .cloned()
+        .collect();
+
+    // With no files given, run the project in the current directory
+    if filenames.is_empty() && Path::new(manifest::MANIFEST_FILE).exists() {
+        match Manifest::load(Path::new(".")) {
+            Ok(manifest) => {
+                filenames = manifest.get_files();
+                implicit_vars = implicit_vars || !manifest.get_options().strict;
+            },
+            Err(err) => usage_error(&err.to_string())
+        }
+    }
+
+    // Nothing to run - drop to interactive mode
+    if filenames.is_empty() {
+        repl::run();
+        return;
+    }
+
+    let filename = &filenames[0];
+
+    if !quiet {
+        for filename in &filenames {
+            println!("Running file {:?}", filename);
+        }
+    }
+
+    // Several files are parsed in parallel and run as one program, in the order given
+    let project_ast = if filenames.len() > 1 && !from_json {
+        let started = Instant::now();
+        match project::parse_files(&filenames, &defines, config.has_case_sensitive_keywords(), &enabled_warnings, limits) {
+            Ok((ast, warnings)) => {
+                log_phase(verbose, "parse", started, filenames.len(), "files");
+                for (path, warning) in warnings {
+                    reporter.report(&format!("{}: {}", path, warning), Diagnostic::from_warning(&warning).in_file(&path));
+                }
+                Some(ast)
+            },
+            Err(diagnostics) => {
+                for diagnostic in diagnostics {
+                    let structured = match diagnostic.get_error() {
+                        Some(err) => Diagnostic::from_parse_error(err),
+                        None => Diagnostic::new(Severity::Error, "unreadable-file", String::from(diagnostic.get_message()))
+                    };
+                    reporter.report(&diagnostic, structured.in_file(diagnostic.get_path()));
+                }
+                process::exit(EXIT_PARSE);
+            }
+        }
+    } else {
+        None
+    };
+
+    let contents = match project_ast {
+        Some(_) => String::new(),
+        None => match fs::read_to_string(filename) {
+            Ok(contents) => contents,
+            Err(err) => usage_error(&format!("Unable to read {} - {}", filename, err))
+        }
+    };
+
+    // A program saved with --emit-ast (or generated by another tool) skips the front end
+    let json_ast = if from_json {
+        match AbstractSyntaxTree::from_json(&contents) {
+            Ok(ast) => Some(ast),
+            Err(err) => {
+                let message = format!("Invalid syntax tree in {} - {}", filename, err);
+                reporter.report(&message, Diagnostic::new(Severity::Error, "invalid-syntax-tree", message.clone()).in_file(filename));
+                process::exit(EXIT_PARSE);
+            }
+        }
+    } else {
+        None
+    };
 
-    println!("Running file {:?}", filename);
+    // Directives are only handled in source text, not in a saved syntax tree
+    let started = Instant::now();
+    let source = match &json_ast {
+        Some(_) => String::new(),
+        None => match preprocessor::preprocess(&contents, &defines) {
+            Ok(source) => {
+                log_phase(verbose, "preprocess", started, contents.lines().count(), "lines");
+                source
+            },
+            Err(err) => {
+                reporter.report(&err, Diagnostic::from_parse_error(&err).in_file(filename));
+                process::exit(EXIT_PARSE);
+            }
+        }
+    };
 
-    let contents = fs::read_to_string(filename)
-        .expect("Something went wrong reading the file");
+    // The parser pulls tokens as it goes, so lexing on its own is an extra pass made just
+    // to report on it. A lex error is left for the parser to report.
+    if verbose && json_ast.is_none() && project_ast.is_none() {
+        let started = Instant::now();
+        if let Ok(tokens) = lexer::lex_all(&source) {
+            log_phase(verbose, "lex", started, tokens.len(), "tokens");
+        }
+    }
 
-    let program = contents.chars().peekable();
+    let program = source.chars().peekable();
     let mut lexer = Lexer::new(program);
+    lexer.set_case_sensitive_keywords(config.has_case_sensitive_keywords());
     let mut parser = Parser::new(&mut lexer);
+    parser.set_limits(limits);
+    parser.set_enabled_warnings(enabled_warnings.clone());
+
+    let started = Instant::now();
+    let ast = match json_ast.or(project_ast) {
+        Some(ast) => ast,
+        None => match parser.parse_all() {
+            Ok(ast) => {
+                log_phase(verbose, "parse", started, ast.block.iter().count(), "top-level statements");
+                ast
+            },
+            Err(errors) => {
+                for err in errors {
+                    reporter.report(&err, Diagnostic::from_parse_error(&err).in_file(filename));
+                }
+                process::exit(EXIT_PARSE);
+            }
+        }
+    };
+
+    for warning in parser.take_warnings() {
+        reporter.report(&warning, Diagnostic::from_warning(&warning).in_file(filename));
+    }
+
+    // Save the parsed program instead of running it
+    if let Some(path) = emit_ast_path {
+        if let Err(err) = fs::write(path, ast.to_json()) {
+            eprintln!("Unable to write syntax tree to {} - {}", path, err);
+            process::exit(EXIT_RUNTIME);
+        }
+
+        return;
+    }
+
     let mut interpreter = Interpreter::new(&mut parser);
 
-    interpreter.interpret();
+    if debug {
+        interpreter.enable_debugger();
+    }
+
+    if coverage_path.is_some() {
+        interpreter.enable_coverage();
+    }
+
+    if mem_stats {
+        interpreter.enable_memory_stats();
+    }
+
+    if profile.is_some() || flamegraph_path.is_some() {
+        interpreter.enable_profiler();
+    }
+
+    if let Some(format) = trace {
+        interpreter.enable_trace(format);
+    }
+
+    if let Some(timeout) = timeout {
+        interpreter.set_timeout(timeout);
+    }
+
+    if precision.is_some() {
+        interpreter.set_print_format(NumberFormat {
+            precision,
+            thousands_separator: None,
+        });
+    }
+
+    if let Some(separator) = &print_separator {
+        interpreter.set_print_separator(separator);
+    }
+
+    if let Some(terminator) = &print_terminator {
+        interpreter.set_print_terminator(terminator);
+    }
+
+    if let Some(budget) = statement_budget {
+        interpreter.set_statement_budget(budget);
+    }
+
+    interpreter.set_max_depth(max_depth.unwrap_or(MAX_DEPTH));
+
+    if implicit_vars {
+        interpreter.set_implicit_vars(true);
+    }
+
+    interpreter.set_enabled_warnings(enabled_warnings);
+
+    if let Some(modules) = &modules {
+        interpreter.set_modules(modules);
+    }
+
+    let result = interpreter.run(&ast);
+
+    // What the run found is worth seeing even when it failed
+    let warnings = match &result {
+        Ok(run) => run.get_warnings().clone(),
+        Err(_) => interpreter.take_warnings()
+    };
+    for warning in warnings {
+        let diagnostic = match filenames.len() {
+            1 => Diagnostic::from_warning(&warning).in_file(filename),
+            _ => Diagnostic::from_warning(&warning)
+        };
+        reporter.report(&warning, diagnostic);
+    }
+
+    // Coverage is still useful for a run that failed part way through
+    if let (Some(path), Some(coverage)) = (coverage_path, interpreter.get_coverage()) {
+        if let Err(err) = coverage.write_lcov(&filenames, path) {
+            eprintln!("Unable to write coverage to {} - {}", path, err);
+        }
+    }
+
+    if let Some(report) = interpreter.memory_report() {
+        eprintln!("{}", report);
+    }
+
+    if let (Some(format), Some(profiler)) = (profile, interpreter.get_profiler()) {
+        eprintln!("{}", profiler.report(format));
+    }
+
+    if let (Some(path), Some(profiler)) = (flamegraph_path, interpreter.get_profiler()) {
+        if let Err(err) = profiler.write_folded(path) {
+            eprintln!("Unable to write flamegraph stacks to {} - {}", path, err);
+        }
+    }
+
+    match result {
+        Ok(run) => {
+            let stats = run.get_stats();
+            if verbose {
+                eprintln!("[analyze] {} symbols in {:?}", stats.symbols, stats.analysis);
+                eprintln!("[execute] {} statements in {:?}", stats.statements, stats.elapsed - stats.analysis);
+            }
+
+            if run.get_exit_code() != 0 {
+                process::exit(run.get_exit_code());
+            }
+        },
+        // Quitting the debugger ends the run without anything having gone wrong
+        Err(err) if err.get_kind() == RuntimeErrorKind::Stopped => {},
+        Err(err) => {
+            // Lines of a multi-file program are counted within each file, so name the file too
+            let path = filenames.get(err.get_file()).unwrap_or(filename);
+            let diagnostic = Diagnostic::from_runtime_error(&err).in_file(path);
+            match filenames.len() {
+                1 => reporter.report(&err, diagnostic),
+                _ => reporter.report(&format!("{}: {}", path, err), diagnostic)
+            }
+
+            if post_mortem {
+                postmortem::run(&mut interpreter);
+            }
+
+            process::exit(EXIT_RUNTIME);
+        }
+    }
+}
+
+// One line per front end phase on stderr for --verbose
+fn log_phase(verbose: bool, phase: &str, started: Instant, count: usize, unit: &str) {
+    if verbose {
+        eprintln!("[{}] {} {} in {:?}", phase, count, unit, started.elapsed());
+    }
+}
+
+// Pulls out -W name and -A name, which report or silence a kind of warning. Later flags win,
+// so -A coercion -W coercion leaves coercion on.
+fn take_warning_flags(args: &mut Vec<String>) -> Result<WarningSet, String> {
+    let mut enabled_warnings = WarningSet::default();
+    let mut remaining = Vec::new();
+    let mut iter = args.drain(..);
+
+    while let Some(arg) = iter.next() {
+        let (flag, name) = match (arg.strip_prefix("-W"), arg.strip_prefix("-A")) {
+            (Some(name), _) => ("-W", String::from(name)),
+            (_, Some(name)) => ("-A", String::from(name)),
+            _ => {
+                remaining.push(arg);
+                continue;
+            }
+        };
+
+        let name = match name.as_str() {
+            "" => match iter.next() {
+                Some(name) => name,
+                None => return Err(format!("{} needs the name of a warning", flag))
+            },
+            _ => name
+        };
+
+        let kind = match WarningKind::from_name(&name) {
+            Some(kind) => kind,
+            None => {
+                let known: Vec<&str> = WarningKind::ALL.iter().map(|kind| kind.name()).collect();
+                return Err(format!("Unknown warning {:?} - expected one of {}", name, known.join(", ")));
+            }
+        };
+
+        match flag {
+            "-W" => enabled_warnings.warn(kind),
+            _ => enabled_warnings.allow(kind)
+        }
+    }
+
+    drop(iter);
+    *args = remaining;
+    Ok(enabled_warnings)
+}
+
+// Pulls out -D NAME=value and -DNAME=value preprocessor defines, leaving the other arguments
+fn take_defines(args: &mut Vec<String>) -> Result<HashMap<String, String>, String> {
+    let mut defines = HashMap::new();
+    let mut remaining = Vec::new();
+    let mut iter = args.drain(..);
+
+    while let Some(arg) = iter.next() {
+        let define = match arg.strip_prefix("-D") {
+            Some("") => match iter.next() {
+                Some(define) => define,
+                None => return Err(String::from("-D needs a NAME or NAME=value"))
+            },
+            Some(define) => String::from(define),
+            None => {
+                remaining.push(arg);
+                continue;
+            }
+        };
+
+        match define.split_once('=') {
+            Some((name, value)) => defines.insert(String::from(name), String::from(value)),
+            None => defines.insert(define, String::from("1"))
+        };
+    }
+
+    drop(iter);
+    *args = remaining;
+    Ok(defines)
+}
+
+// Problems with the program go to stderr as text, or as one JSON object per line for tools
+struct Reporter {
+    json: bool,
+    // Only for text - JSON is for tools
+    color: bool,
+}
+
+impl Reporter {
+    fn report(&self, text: &dyn fmt::Display, diagnostic: Diagnostic) {
+        if self.json {
+            eprintln!("{}", diagnostic.to_json());
+        } else if self.color {
+            // Red for errors, yellow for warnings
+            let color = match diagnostic.severity {
+                Severity::Error => 31,
+                Severity::Warning => 33,
+            };
+            eprintln!("\x1b[{}m{}\x1b[0m", color, text);
+        } else {
+            eprintln!("{}", text);
+        }
+    }
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(EXIT_USAGE);
+}
+
+// Boolean flags must match exactly, value flags are given as --name=value
+fn is_known_flag(arg: &str) -> bool {
+    let name = arg.split('=').next().unwrap_or(arg);
+    FLAGS.contains(&name)
+}
+
+// Value of a --name=value style flag
+fn flag_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .find(|arg| arg.starts_with(name) && arg[name.len()..].starts_with('='))
+        .map(|arg| &arg[name.len() + 1..])
+}
+
+// Durations like 5s, 250ms or 2m - a bare number is seconds
+fn parse_duration(text: &str) -> Result<Duration, String> {
+    let split = text.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(text.len());
+    let (amount, unit) = text.split_at(split);
+
+    let amount: f64 = match amount.parse() {
+        Ok(amount) => amount,
+        Err(_) => return Err(format!("Invalid duration {:?} - expected something like 5s or 500ms", text))
+    };
+
+    let seconds = match unit {
+        "ms" => amount / 1000.0,
+        "" | "s" => amount,
+        "m" => amount * 60.0,
+        _ => return Err(format!("Invalid duration unit {:?} - use ms, s or m", unit))
+    };
+
+    Duration::try_from_secs_f64(seconds)
+        .map_err(|_| format!("Invalid duration {:?} - too long", text))
+}
+
+// A comma separated allowlist like math,string - an empty list allows no builtins at all
+fn parse_modules(names: &[String]) -> Result<Vec<Module>, String> {
+    let mut modules = Vec::new();
+    for name in names {
+        match Module::from_name(name) {
+            Some(module) if !module.is_compiled() => return Err(format!("The {} module isn't included in this build", module.name())),
+            Some(module) => modules.push(module),
+            None => {
+                let known: Vec<&str> = Module::ALL.iter().map(|module| module.name()).collect();
+                return Err(format!("Unknown module {:?} - expected some of {}", name, known.join(", ")));
+            }
+        }
+    }
+
+    Ok(modules)
+}
+
+// Lets separators like a tab or CRLF be given on the command line - \t, \r, \n and \\
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('t') => unescaped.push('\t'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\')
+        }
+    }
+
+    unescaped
 }
@@ -0,0 +1,332 @@
+use hello_rust::interpreter::ast::AbstractSyntaxTree;
+use hello_rust::interpreter::ast::Expression;
+use hello_rust::interpreter::builtins;
+use hello_rust::interpreter::error::ParseError;
+use hello_rust::interpreter::error::ParseErrorKind;
+use hello_rust::interpreter::intr::Interpreter;
+use hello_rust::interpreter::lexer::Lexer;
+use hello_rust::interpreter::parser;
+use hello_rust::interpreter::parser::Parser;
+use hello_rust::interpreter::token::TokenType;
+use hello_rust::interpreter::token::KEYWORDS;
+
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::CmdKind;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::Context;
+use rustyline::Editor;
+use rustyline::Helper;
+
+use std::borrow::Cow;
+use std::fs;
+
+const KEYWORD_COLOR: &str = "\x1b[1;34m";
+const NUMBER_COLOR: &str = "\x1b[33m";
+const STRING_COLOR: &str = "\x1b[32m";
+// Bad input, unclosed strings and brackets with no partner
+const ERROR_COLOR: &str = "\x1b[1;31m";
+const RESET: &str = "\x1b[0m";
+
+const COMMANDS: [&str; 8] = [":vars", ":ast", ":type", ":load", ":reset", ":quit", ":q", ":help"];
+
+pub fn run() {
+    println!("hello_rust interactive mode - type :help for a list of commands");
+
+    let mut editor: Editor<ReplHelper, DefaultHistory> = match Editor::new() {
+        Ok(editor) => editor,
+        Err(err) => {
+            eprintln!("Unable to start interactive mode - {}", err);
+            return;
+        }
+    };
+    editor.set_helper(Some(ReplHelper { names: Vec::new() }));
+
+    let mut interpreter = Interpreter::standalone();
+    let mut pending = String::new();
+
+    loop {
+        // Variables and functions declared by the last input become completions for the next
+        if let Some(helper) = editor.helper_mut() {
+            helper.names = interpreter.get_symbol_table().get_names().iter()
+                .chain(interpreter.get_function_names().iter())
+                .map(|name| String::from(*name))
+                .collect();
+        }
+
+        let mut line = match editor.readline(if pending.is_empty() { "> " } else { "... " }) {
+            Ok(line) => line,
+            // Ctrl-C drops whatever was being typed, Ctrl-D leaves
+            Err(ReadlineError::Interrupted) => {
+                pending.clear();
+                continue;
+            },
+            Err(_) => return
+        };
+        let _ = editor.add_history_entry(line.as_str());
+        line.push('\n');
+
+        if pending.is_empty() && line.trim().starts_with(':') {
+            let mut parts = line.trim().splitn(2, char::is_whitespace);
+            let command = parts.next().unwrap_or("");
+            let argument = parts.next().unwrap_or("").trim();
+
+            match command {
+                ":q" | ":quit" => return,
+                ":reset" => {
+                    interpreter = Interpreter::standalone();
+                    println!("Scope cleared");
+                },
+                _ => run_command(&mut interpreter, command, argument)
+            }
+
+            continue;
+        }
+
+        pending.push_str(&line);
+        if pending.trim().is_empty() {
+            pending.clear();
+            continue;
+        }
+
+        match parse_input(&pending) {
+            Input::Incomplete => continue,
+            Input::Program(ast) => {
+                if let Err(err) = interpreter.run(&ast) {
+                    println!("{}", err);
+                }
+            },
+            Input::Expression(expression) => match interpreter.evaluate(&expression) {
+                Ok(value) => println!("{}", value),
+                Err(err) => println!("{}", err)
+            },
+            Input::Error(err) => println!("{}", err)
+        }
+
+        pending.clear();
+    }
+}
+
+enum Input {
+    Incomplete,
+    Program(AbstractSyntaxTree),
+    Expression(Expression),
+    Error(String),
+}
+
+fn parse_input(source: &str) -> Input {
+    match parser::parse_str(source) {
+        Ok(ast) => Input::Program(ast),
+        Err(err) => match parse_expression(source) {
+            // A bare expression is evaluated and shown rather than treated as a statement
+            Ok(expression) => Input::Expression(expression),
+            // Ran out of input part way through, e.g. a WHILE without its END yet - keep reading lines
+            Err(_) if is_unexpected_end(&err) => Input::Incomplete,
+            Err(_) => Input::Error(err.to_string())
+        }
+    }
+}
+
+fn run_command(interpreter: &mut Interpreter, command: &str, argument: &str) {
+    match command {
+        ":vars" => {
+            for (name, value) in interpreter.get_global_scope() {
+                println!("{} = {}", name, value);
+            }
+        },
+        ":ast" => {
+            // Expressions are what's usually asked about, but whole statements work too
+            match parse_expression(argument) {
+                Ok(expression) => println!("{}", expression),
+                Err(_) => match parser::parse_str(argument) {
+                    Ok(ast) => println!("{}", ast),
+                    Err(err) => println!("{}", err)
+                }
+            }
+        },
+        ":type" => match parse_expression(argument) {
+            Ok(expression) => match interpreter.evaluate(&expression) {
+                Ok(value) => println!("{}", value.type_name()),
+                Err(err) => println!("{}", err)
+            },
+            Err(err) => println!("{}", err)
+        },
+        ":load" => match fs::read_to_string(argument) {
+            Ok(source) => match parser::parse_str(&source) {
+                Ok(ast) => {
+                    if let Err(err) = interpreter.run(&ast) {
+                        println!("{}", err);
+                    }
+                },
+                Err(err) => println!("{}", err)
+            },
+            Err(err) => println!("Unable to read {} - {}", argument, err)
+        },
+        ":h" | ":help" => {
+            println!(":vars                show all assigned variables");
+            println!(":ast <expr>          show the syntax tree for an expression or statement");
+            println!(":type <expr>         show the type an expression evaluates to");
+            println!(":load <file>         run a file in the current scope");
+            println!(":reset               forget all variables");
+            println!(":quit (:q)           leave interactive mode");
+        },
+        _ => println!("Unknown command {} - type :help for a list of commands", command)
+    }
+}
+
+fn parse_expression(source: &str) -> Result<Expression, ParseError> {
+    let mut lexer = Lexer::new(source.chars().peekable());
+    let mut parser = Parser::new(&mut lexer);
+    parser.parse_single_expression()
+}
+
+fn is_unexpected_end(err: &ParseError) -> bool {
+    err.get_kind() == ParseErrorKind::UnexpectedEnd
+}
+
+// Colors the line by its tokens. The lexer recovers from bad input, so everything past a
+// mistake still gets its colors.
+fn highlight(line: &str) -> String {
+    let mut lexer = Lexer::new(line.chars().peekable());
+    lexer.set_recovery(true);
+
+    let mut tokens = Vec::new();
+    while let Ok(token) = lexer.get_token() {
+        if token.get_token_type() == &TokenType::EOF {
+            break;
+        }
+        tokens.push(token);
+    }
+
+    // Brackets that close nothing, or are never closed
+    let mut mismatched = vec![false; tokens.len()];
+    let mut open = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        match token.get_token_type() {
+            TokenType::LPAREN | TokenType::LBRACKET | TokenType::LBRACE => open.push(index),
+            TokenType::RPAREN | TokenType::RBRACKET | TokenType::RBRACE => {
+                let partner = match token.get_token_type() {
+                    TokenType::RPAREN => TokenType::LPAREN,
+                    TokenType::RBRACKET => TokenType::LBRACKET,
+                    _ => TokenType::LBRACE
+                };
+                match open.last() {
+                    Some(&last) if tokens[last].get_token_type() == &partner => {
+                        open.pop();
+                    },
+                    _ => mismatched[index] = true
+                }
+            },
+            _ => {}
+        }
+    }
+    for index in open {
+        mismatched[index] = true;
+    }
+
+    let mut highlighted = String::with_capacity(line.len());
+    let mut previous_end = 0;
+    for (index, token) in tokens.iter().enumerate() {
+        // A token's text runs up to the whitespace before the next one
+        let start = token.get_span().offset;
+        let next_start = tokens.get(index + 1).map(|next| next.get_span().offset).unwrap_or(line.len());
+        let end = start + line[start..next_start].trim_end().len();
+
+        let color = match token.get_token_type() {
+            _ if mismatched[index] => Some(ERROR_COLOR),
+            TokenType::ILLEGAL => Some(ERROR_COLOR),
+            TokenType::NUMBER | TokenType::DECIMAL => Some(NUMBER_COLOR),
+            TokenType::STRING => Some(STRING_COLOR),
+            TokenType::IDENT => None,
+            _ if TokenType::get_keyword_token(&line[start..end]).is_some() => Some(KEYWORD_COLOR),
+            _ => None
+        };
+
+        highlighted.push_str(&line[previous_end..start]);
+        match color {
+            Some(color) => {
+                highlighted.push_str(color);
+                highlighted.push_str(&line[start..end]);
+                highlighted.push_str(RESET);
+            },
+            None => highlighted.push_str(&line[start..end])
+        }
+        previous_end = end;
+    }
+    highlighted.push_str(&line[previous_end..]);
+
+    highlighted
+}
+
+struct ReplHelper {
+    names: Vec<String>,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+        let before = &line[..pos];
+
+        // Meta-commands only make sense as the first word
+        if before.starts_with(':') && !before.contains(char::is_whitespace) {
+            let candidates = COMMANDS.iter()
+                .filter(|command| command.starts_with(before))
+                .map(|command| String::from(*command))
+                .collect();
+            return Ok((0, candidates));
+        }
+
+        let start = before.rfind(|c: char| !c.is_alphanumeric()).map(|i| i + 1).unwrap_or(0);
+        let word = &before[start..];
+        if word.is_empty() {
+            return Ok((start, Vec::new()));
+        }
+
+        // Keywords are case-insensitive - follow the case being typed
+        let lowercase = word.chars().all(|c| !c.is_uppercase());
+        let upper_word = word.to_uppercase();
+        let mut candidates: Vec<String> = KEYWORDS.iter()
+            .filter(|keyword| keyword.starts_with(&upper_word))
+            .map(|keyword| if lowercase { keyword.to_lowercase() } else { String::from(*keyword) })
+            .collect();
+
+        let mut builtins: Vec<String> = builtins::names()
+            .filter(|name| name.starts_with(&upper_word))
+            .map(|name| if lowercase { name.to_lowercase() } else { String::from(name) })
+            .collect();
+        candidates.append(&mut builtins);
+
+        let mut names: Vec<String> = self.names.iter()
+            .filter(|name| name.starts_with(word))
+            .cloned()
+            .collect();
+        names.sort();
+        names.dedup();
+        candidates.append(&mut names);
+
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        Cow::Owned(highlight(line))
+    }
+
+    // Any keystroke can start or finish a keyword or close a bracket
+    fn highlight_char(&self, _line: &str, _pos: usize, _kind: CmdKind) -> bool {
+        true
+    }
+}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
@@ -0,0 +1,57 @@
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::interpreter::intr::Interpreter;
+use crate::interpreter::parser;
+
+// What a playground run produced - everything PRINT and EPRINT wrote plus any errors
+#[wasm_bindgen]
+pub struct RunOutput {
+    output: String,
+    error_output: String,
+    errors: Vec<String>,
+}
+
+#[wasm_bindgen]
+impl RunOutput {
+    #[wasm_bindgen(getter)]
+    pub fn output(&self) -> String {
+        self.output.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn error_output(&self) -> String {
+        self.error_output.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn errors(&self) -> Vec<String> {
+        self.errors.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub fn run(source: &str) -> RunOutput {
+    let mut output: Vec<u8> = Vec::new();
+    let mut error_output: Vec<u8> = Vec::new();
+    let mut errors = Vec::new();
+
+    match parser::parse_str(source) {
+        Ok(ast) => {
+            // There is no stdout in the browser - collect PRINT output and hand it back
+            let mut interpreter = Interpreter::standalone();
+            interpreter.set_output(Box::new(&mut output));
+            interpreter.set_error_output(Box::new(&mut error_output));
+
+            if let Err(err) = interpreter.run(&ast) {
+                errors.push(err.to_string());
+            }
+        },
+        Err(err) => errors.push(err.to_string())
+    }
+
+    RunOutput {
+        output: String::from_utf8_lossy(&output).into_owned(),
+        error_output: String::from_utf8_lossy(&error_output).into_owned(),
+        errors: errors,
+    }
+}
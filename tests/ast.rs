@@ -0,0 +1,86 @@
+// Syntax trees compare by structure - where a node came from in the source, and the ids the
+// parser gave it, don't count
+use hello_rust::interpreter::ast::build::add;
+use hello_rust::interpreter::ast::build::assign;
+use hello_rust::interpreter::ast::build::ident;
+use hello_rust::interpreter::ast::build::let_;
+use hello_rust::interpreter::ast::build::lt;
+use hello_rust::interpreter::ast::build::mul;
+use hello_rust::interpreter::ast::build::num;
+use hello_rust::interpreter::ast::build::print;
+use hello_rust::interpreter::ast::build::program;
+use hello_rust::interpreter::ast::build::while_;
+use hello_rust::interpreter::parser;
+use hello_rust::prog;
+
+#[test]
+fn parsed_tree_equals_built_tree() {
+    let parsed = parser::parse_str("let x = 5;\nwhile x < 10 then\n  x = x + 1;\nend\nprint x;\n").expect("program should parse");
+    let built = program(vec![
+        let_("x", num(5)),
+        while_(lt(ident("x"), num(10)), vec![
+            assign("x", add(ident("x"), num(1))),
+        ]),
+        print(ident("x")),
+    ]);
+
+    assert_eq!(parsed, built);
+}
+
+#[test]
+fn layout_does_not_count() {
+    let compact = parser::parse_str("let x = 1; if x == 1 then print \"one\"; end").expect("program should parse");
+    let spread = parser::parse_str("\n\nlet x = 1;\n\nif x == 1 then\n    print \"one\";\nend\n").expect("program should parse");
+
+    assert_eq!(compact, spread);
+}
+
+#[test]
+fn different_trees_are_not_equal() {
+    let parsed = parser::parse_str("print 1 + 2 * 3;").expect("program should parse");
+
+    assert_ne!(parsed, program(vec![print(mul(add(num(1), num(2)), num(3)))]));
+    assert_ne!(parsed, program(vec![print(add(num(1), mul(num(2), num(4))))]));
+    assert_eq!(parsed, program(vec![print(add(num(1), mul(num(2), num(3))))]));
+}
+
+#[test]
+fn clone_is_equal() {
+    let parsed = parser::parse_str("let a = [1, 2];\nlet m = {\"k\": a[0]};\nprint m;\n").expect("program should parse");
+
+    assert_eq!(parsed.clone(), parsed);
+    assert!(format!("{:?}", parsed).contains("Map"));
+}
+
+#[test]
+fn prog_matches_the_parser() {
+    let built = prog! {
+        let x = 1;
+        while x < 10 {
+            x = x + 1;
+        }
+        if x == 10 {
+            print "done";
+        } elseif x > 10 {
+            print x;
+        } else {
+            print 0;
+        }
+    };
+    let parsed = parser::parse_str(
+        "let x = 1;\nwhile x < 10 then\n  x = x + 1;\nend\nif x == 10 then\n  print \"done\";\nelseif x > 10 then\n  print x;\nelse\n  print 0;\nend\n"
+    ).expect("program should parse");
+
+    assert_eq!(built, parsed);
+}
+
+#[test]
+fn prog_keeps_precedence() {
+    let built = prog! {
+        let y = 1 + 2 * (3 - 4) / -x;
+        breakpoint;
+    };
+    let parsed = parser::parse_str("let y = 1 + 2 * (3 - 4) / -x;\nbreakpoint;\n").expect("program should parse");
+
+    assert_eq!(built, parsed);
+}
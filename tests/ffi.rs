@@ -0,0 +1,50 @@
+// Calls the C API the way an embedder would, from a thread of their own
+#![cfg(feature = "ffi")]
+
+use hello_rust::ffi::hello_rust_create;
+use hello_rust::ffi::hello_rust_destroy;
+use hello_rust::ffi::hello_rust_error;
+use hello_rust::ffi::hello_rust_error_output;
+use hello_rust::ffi::hello_rust_load;
+use hello_rust::ffi::hello_rust_output;
+use hello_rust::ffi::hello_rust_run;
+use hello_rust::ffi::HELLO_RUST_ERROR;
+use hello_rust::ffi::HELLO_RUST_OK;
+
+use std::ffi::CStr;
+use std::ffi::CString;
+use std::thread;
+
+fn run(source: &str) -> (i32, String, String, Option<String>) {
+    let source = CString::new(source).expect("source has no NULs");
+    thread::spawn(move || unsafe {
+        let engine = hello_rust_create();
+        assert_eq!(hello_rust_load(engine, source.as_ptr()), HELLO_RUST_OK);
+
+        let code = hello_rust_run(engine);
+        let output = CStr::from_ptr(hello_rust_output(engine)).to_string_lossy().into_owned();
+        let error_output = CStr::from_ptr(hello_rust_error_output(engine)).to_string_lossy().into_owned();
+        let error = hello_rust_error(engine);
+        let error = if error.is_null() { None } else { Some(CStr::from_ptr(error).to_string_lossy().into_owned()) };
+
+        hello_rust_destroy(engine);
+        (code, output, error_output, error)
+    }).join().expect("the call should not unwind into the caller")
+}
+
+#[test]
+fn run_captures_output() {
+    assert_eq!(run("let x = 2;\nprint x * 3;\n"), (HELLO_RUST_OK, String::from("6\n"), String::new(), None));
+}
+
+#[test]
+fn run_captures_error_output() {
+    assert_eq!(run("print 1;\neprint 2;\n"), (HELLO_RUST_OK, String::from("1\n"), String::from("2\n"), None));
+}
+
+#[test]
+fn deep_recursion_is_an_error() {
+    let (code, _, _, error) = run("function f(n)\n  return f(n + 1);\nend\nprint f(0);\n");
+    assert_eq!(code, HELLO_RUST_ERROR);
+    assert!(error.is_some());
+}
@@ -0,0 +1,16 @@
+// The golden scripts under tests/golden, run through the interpreter's own test-dir runner
+// so that cargo test checks them too
+use std::process::Command;
+
+#[test]
+fn golden_scripts() {
+    let output = Command::new(env!("CARGO_BIN_EXE_hello_rust"))
+        .args(["test-dir", concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden")])
+        .output()
+        .expect("the interpreter should start");
+
+    // Only what went wrong - a passing script is one line among dozens
+    let report = String::from_utf8_lossy(&output.stdout);
+    let failures: Vec<&str> = report.lines().filter(|line| !line.starts_with("PASS")).collect();
+    assert!(output.status.success(), "{}", failures.join("\n"));
+}
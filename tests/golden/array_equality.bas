@@ -0,0 +1,26 @@
+let a = [1, 2.5, "x"];
+let b = [1, 2.5, "x"];
+if a == b then
+    print "same";
+end
+let odd = [NAN];
+if odd == odd then
+    print "nan equal";
+else
+    print "nan not equal";
+end
+if odd != [NAN] then
+    print "nan differs";
+end
+if [1] == [1d] then
+    print "number equals decimal";
+end
+if [0.5, [2]] == [0.5d, [2d]] then
+    print "nested too";
+end
+if [1, 2] != [1] then
+    print "lengths differ";
+end
+if [1] != ["1"] then
+    print "string is not a number";
+end
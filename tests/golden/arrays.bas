@@ -0,0 +1,29 @@
+let xs = [1, 2, 3];
+print xs;
+print xs[0], xs[2];
+
+xs[1] = 5;
+print xs;
+
+let copy = xs;
+copy[0] = "one";
+print xs, copy;
+
+let grid = [[1, 2], [3, 4]];
+grid[1][0] = 30;
+print grid, grid[1][0];
+
+let empty = [];
+print empty;
+
+let i = 0;
+while i < 3 then
+  print [i, i * i][1];
+  i = i + 1;
+end
+
+if xs == [1, 5, 3] then
+  print "equal";
+end
+
+print xs[3];
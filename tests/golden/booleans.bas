@@ -0,0 +1,26 @@
+let done = false;
+let count = 0;
+while done == false then
+    count = count + 1;
+    if count == 3 then
+        done = true;
+    end
+end
+print count, done;
+
+if done then
+    print "finished";
+end
+
+let same = STARTSWITH("abc", "a");
+if same != false then
+    print "starts with a", same;
+end
+
+if TRUE then
+    print "literal";
+end
+
+if count then
+    print "never";
+end
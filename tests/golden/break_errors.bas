@@ -0,0 +1,6 @@
+break;
+let x = 1;
+while x < 2 then
+  break nope;
+  x = x + 1;
+end
@@ -0,0 +1,3 @@
+let x = 2;
+breakpoint;
+print x;
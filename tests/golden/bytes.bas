@@ -0,0 +1,10 @@
+let source = READBYTES("bytes.bas");
+print source[0], source[1], source[2];
+let data = UNHEX("00ff7F");
+print data, HEX(data);
+data[0] = 128;
+print data;
+if data == UNHEX("80FF7f") then
+    print "same";
+end
+data[1] = 256;
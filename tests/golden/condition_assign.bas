@@ -0,0 +1,8 @@
+let x = 2;
+if x = 2 then
+    print "two";
+end
+while x = 2 then
+    x = x + 1;
+end
+print x;
@@ -0,0 +1,9 @@
+let x = 5;
+if x > 2 then
+  print "Hello World";
+  print "This is my world";
+  let y = 11;
+  if y == 11 then
+    print 123;
+  end
+end
\ No newline at end of file
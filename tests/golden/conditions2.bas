@@ -0,0 +1,8 @@
+let x = 6;
+if x > 5 then
+  print "THIS IS IF";
+elseif x > 2 then
+  print "THIS IS ELSE IF";
+else
+  print "THIS IS ELSE";
+end
\ No newline at end of file
@@ -0,0 +1,11 @@
+let start = DATE(2024, 2, 28);
+let finish = DATE(2024, 3, 1, 12, 30, 0);
+print start;
+print finish;
+print DIFFDAYS(start, finish);
+print DIFFDAYS(DATE(2000, 1, 1), DATE(1970, 1, 1));
+print FORMATDATE(finish, "%d/%m/%Y at %H:%M:%S - 100%%");
+if start < finish then
+    print "in order";
+end
+print DATE(2023, 2, 29);
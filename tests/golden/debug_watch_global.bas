@@ -0,0 +1,6 @@
+let total = 0;
+function bump(a)
+  total = total + a;
+end
+bump(3);
+print total;
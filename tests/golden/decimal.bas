@@ -0,0 +1,12 @@
+let price = 0.1d;
+let total = price + 0.2d;
+print total;
+print 0.1 + 0.2;
+print total * 3;
+print -total;
+print 19.99d * 3;
+if total == 0.3d then
+print "exact";
+end
+print total + 1;
+print 10d / 3;
@@ -0,0 +1,2 @@
+let x = 10dollars;
+print x;
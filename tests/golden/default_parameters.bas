@@ -0,0 +1,30 @@
+function greet(name = "world", greeting = "hello")
+  print greeting, name;
+end
+
+function area(width, height = width)
+  return width * height;
+end
+
+let calls = 0;
+function counted()
+  calls = calls + 1;
+  return calls;
+end
+
+function stamp(at = counted())
+  return at;
+end
+
+memo function scaled(x, factor = 10)
+  print "scaling", x;
+  return x * factor;
+end
+
+greet();
+greet("there");
+greet("again", "goodbye");
+print area(3), area(3, 4);
+print stamp(), stamp(), stamp(100);
+print scaled(2), scaled(2, 10), scaled(2, 3);
+area();
@@ -0,0 +1,14 @@
+let s = "hello";
+s[0] = "j";
+print s;
+s[4] = "y";
+print s;
+let t = s;
+t[1] = "E";
+print s, t;
+function shout(word)
+  word[0] = "W";
+  return word;
+end
+print shout("word"), s;
+s[1] = "ab";
@@ -0,0 +1,19 @@
+let x = 3;
+if x > 5 then
+  print "big";
+else if x > 2 then
+  print "middle";
+ELSE IF x > 0 THEN
+  print "small";
+else
+  print "none";
+end
+
+let y = 0;
+if y > 0 then
+  print "positive";
+else
+  if y == 0 then
+    print "zero";
+  end
+end
@@ -0,0 +1,5 @@
+print "data";
+eprint "diagnostic";
+let x = 2;
+eprint x * 3;
+print x;
@@ -0,0 +1,11 @@
+let scores = {"ann": [1, 2], "bob": [3]};
+let same = {"bob": [3], "ann": [1, 2d]};
+print EQUALS(scores, same), EQUALS([1, 2], [2, 1]), EQUALS(nan, nan);
+
+let kept = COPY(scores);
+let alias = scores;
+scores["ann"][0] = 10;
+print scores;
+print kept, alias;
+print EQUALS(kept, alias);
+print EQUALS(1);
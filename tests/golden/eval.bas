@@ -0,0 +1,9 @@
+let x = 3;
+let y = 4;
+let formula = "x + 2 * y";
+print EVAL(formula);
+y = 10;
+print EVAL(formula), EVAL("-(x - 1) * 1.5");
+print EVAL("ISNAN(NAN)");
+let s = "EVAL(s)";
+print EVAL(s);
@@ -0,0 +1,4 @@
+let short = "1 + 2";
+print eval(short);
+let long = "(((1 + 2)))";
+print eval(long);
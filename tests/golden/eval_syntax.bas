@@ -0,0 +1 @@
+print EVAL("1 +");
@@ -0,0 +1,5 @@
+print 0.1, 1.5, 100, 123456.7, 1 / 3;
+print 0.000001, 0.0000001, 0.00000012345;
+print 0 * -1, -2.5, 16777216;
+let big = 1000000 * 1000000 * 1000000;
+print big, big * 10000, -big * 10000;
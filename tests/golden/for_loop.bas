@@ -0,0 +1,27 @@
+for i = 1 to 10 step 3
+    print i;
+end
+
+for i = 3 to 1 step -1
+    print "down", i;
+end
+
+for i = 5 to 1
+    print "never";
+end
+
+outer: for row = 1 to 3
+    for col = 1 to 3
+        if col == 2 then
+            continue outer;
+        end
+        if row == 3 then
+            break outer;
+        end
+        print row, col;
+    end
+end
+
+print "after", i;
+for i = 1 to 2 step 0
+end
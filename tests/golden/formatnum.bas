@@ -0,0 +1,8 @@
+print formatnum(3.14159, 2);
+print FORMATNUM(1234567.891d, 2, ",");
+print formatnum(-9876543, 0, ",");
+print formatnum(999, 1, " ");
+print formatnum(nan, 2);
+print formatnum(2.5d, 0);
+print formatnum("42", 3);
+print 1 / 3;
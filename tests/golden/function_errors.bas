@@ -0,0 +1,9 @@
+function inner(x)
+  return x / missing;
+end
+
+function outer(x)
+  return inner(x) + 1;
+end
+
+print outer(1);
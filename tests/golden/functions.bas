@@ -0,0 +1,30 @@
+print add(1, 2);
+
+function add(a, b)
+  return a + b;
+end
+
+function greet(name)
+  print "hello", name;
+end
+
+function fact(n)
+  if n <= 1 then
+    return 1;
+  end
+  return n * fact(n - 1);
+end
+
+let total = 0;
+function bump(amount)
+  total = total + amount;
+  let own = amount;
+end
+
+greet("world");
+print fact(5);
+bump(3);
+bump(4);
+print total;
+let a = 100;
+print add(a, 1), a;
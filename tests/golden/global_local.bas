@@ -0,0 +1,30 @@
+let count = 0;
+let name = "program";
+
+function tally()
+  global count;
+  count = count + 1;
+  local name;
+  name = "tally";
+  print name, count;
+end
+
+function rename()
+  local name;
+  name = "renamed";
+  return name;
+end
+
+function start()
+  global started;
+  started = true;
+end
+
+tally();
+tally();
+print name, count;
+print rename(), name;
+start();
+print started;
+let count = 10;
+tally();
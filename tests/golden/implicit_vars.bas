@@ -0,0 +1,12 @@
+let x = 1;
+print x;
+y = x + 1;
+print y;
+let x = 1;
+if x == 1 then
+    count = 0;
+    while count < 3 then
+        count = count + 1;
+    end
+end
+print count;
@@ -0,0 +1,7 @@
+inspect;
+let x = 5;
+let name = " hi ";
+let price = 1.50d;
+inspect;
+inspect name;
+INSPECT missing;
@@ -0,0 +1,4 @@
+let x = 1;
+print x +;
+let y = 2 # 3;
+print "unclosed;
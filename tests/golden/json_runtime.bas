@@ -0,0 +1,5 @@
+let x = 2;
+if x = 2 then
+    print "two";
+end
+y = 3;
@@ -0,0 +1,14 @@
+let scores = { "zoe": 3, "adam": 1, "mia": 2 };
+print KEYS(scores);
+scores["bea"] = 4;
+scores["adam"] = 5;
+print KEYS(scores);
+print scores;
+print KEYS({});
+
+let zeta = 1;
+let alpha = "two";
+let mid = [3];
+inspect;
+
+print KEYS("zoe");
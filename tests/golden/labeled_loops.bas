@@ -0,0 +1,28 @@
+let i = 0;
+outer: while i < 3 then
+    i = i + 1;
+    let j = 0;
+    inner: while j < 3 then
+        j = j + 1;
+        if j == 2 then
+            continue inner;
+        end
+        if i == 2 then
+            continue outer;
+        end
+        if i == 3 then
+            break outer;
+        end
+        print i, j;
+    end
+end
+print "after", i;
+
+let n = 0;
+while 1 == 1 then
+    n = n + 1;
+    if n == 5 then
+        break;
+    end
+end
+print n;
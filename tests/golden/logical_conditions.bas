@@ -0,0 +1,30 @@
+let x = 0;
+let y = 5;
+while x < 10 and y > 0 then
+    x = x + 1;
+    y = y - 1;
+end
+print x, y;
+
+if x == 0 or y == 0 then
+    print "one is zero";
+end
+
+if not x == 5 then
+    print "never";
+else
+    print "x is 5";
+end
+
+let ready = true;
+if not ready or x > 100 and y > 100 then
+    print "never";
+end
+
+if y == 0 or "text" > 1 then
+    print "short circuit";
+end
+
+if x > 0 and "text" > 1 then
+    print "never";
+end
@@ -0,0 +1,5 @@
+let x = 1;
+while x < 4 then
+  print x;
+  x = x + 1;
+end
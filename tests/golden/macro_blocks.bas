@@ -0,0 +1,16 @@
+MACRO countdown(n)
+    for i = n to 1 step -1
+        print i;
+    end
+    print "liftoff";
+END
+
+MACRO twice(name)
+    function name(x)
+        return x * 2;
+    end
+END
+
+countdown(3);
+twice(double);
+print double(21);
@@ -0,0 +1,14 @@
+MACRO sign(x)
+    if x > 0 then
+        print "positive";
+    else if x < 0 then
+        print "negative";
+    else
+        print "zero";
+    end
+END
+
+sign(5);
+sign(-2);
+sign(0);
+print "done";
@@ -0,0 +1,32 @@
+MACRO swap(a, b)
+    LET tmp = a;
+    a = b;
+    b = tmp;
+END
+
+MACRO repeat(n, body)
+    LET i = 0;
+    WHILE i < n THEN
+        body;
+        i = i + 1;
+    END
+END
+
+MACRO square(x)
+    print x * x;
+END
+
+let x = 1;
+let y = 2;
+let tmp = "mine";
+swap(x, y);
+print x, y, tmp;
+
+let i = 10;
+repeat(2) THEN
+    repeat(2) THEN
+        print "i is still", i;
+    END
+END
+
+square(y + 1);
@@ -0,0 +1,20 @@
+let a = {"x": 1, "y": 2};
+let b = {"y": 2, "x": 1};
+if a == b then
+    print "same, in any order";
+end
+let odd = {"a": NAN};
+if odd == odd then
+    print "nan equal";
+else
+    print "nan not equal";
+end
+if {"a": 1} == {"a": 1d} then
+    print "number equals decimal";
+end
+if {"a": [0.5]} == {"a": [0.5d]} then
+    print "nested too";
+end
+if {"a": 1} != {"b": 1} then
+    print "keys differ";
+end
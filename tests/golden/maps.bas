@@ -0,0 +1,24 @@
+let ages = { "ann": 31, "bob": 27 };
+print ages;
+print ages["bob"];
+
+ages["ann"] = 32;
+ages["cy"] = 40;
+print ages;
+
+let copy = ages;
+copy["bob"] = "unknown";
+print ages["bob"], copy["bob"];
+
+let key = "nested";
+let config = { key: { "depth": [1, 2] }, "x": 1, "x": 2 };
+config["nested"]["depth"][1] = 20;
+print config;
+
+print {};
+
+if { "a": 1, "b": 2 } == { "b": 2, "a": 1 } then
+  print "equal";
+end
+
+print ages["dan"];
@@ -0,0 +1,9 @@
+function countdown(n)
+    if n > 0 then
+        return countdown(n - 1) + 1;
+    end
+    return 0;
+end
+
+print countdown(5);
+print countdown(50);
@@ -0,0 +1,8 @@
+let x = (1 + 2) * 3;
+print x;
+if x > 1 then
+  if x > 2 then
+    print "two deep";
+  end
+end
+let y = ((((1 + 2) * 3) - 4) * 2);
@@ -0,0 +1,8 @@
+let total = 0;
+let i = 0;
+while i < 10 then
+  total = total + i;
+  i = i + 1;
+end
+print total;
+print "never parsed";
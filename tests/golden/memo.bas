@@ -0,0 +1,17 @@
+memo function fib(n)
+  if n < 2 then
+    return n;
+  end
+  return fib(n - 1) + fib(n - 2);
+end
+
+print fib(35);
+
+memo function square(x)
+  print "working out", x;
+  return x * x;
+end
+
+print square(4);
+print square(4);
+print square(5);
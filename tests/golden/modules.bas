@@ -0,0 +1,2 @@
+print ISNAN(1);
+print CHR(65);
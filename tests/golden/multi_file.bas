@@ -0,0 +1,2 @@
+print half(4);
+print broken();
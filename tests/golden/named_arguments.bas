@@ -0,0 +1,11 @@
+function plot(x, y = 0, label = "point")
+  print label, x, y;
+end
+
+plot(1, 2);
+plot(x = 1, y = 2);
+plot(y = 5, x = 3);
+plot(4, label = "corner");
+plot(label = "origin", x = 0);
+print CHR(65);
+print ISNAN(value = 1);
@@ -0,0 +1,5 @@
+function plot(x, y = 0)
+  print x, y;
+end
+
+plot(1, x = 2);
@@ -0,0 +1,5 @@
+function plot(x, y)
+  print x, y;
+end
+
+plot(x = 1, 2);
@@ -0,0 +1,27 @@
+let a = nan;
+let b = INF;
+let c = -inf;
+print a;
+print b;
+print c;
+print isnan(a);
+print isnan(b);
+print ISINF(c);
+print isinf(1.5d);
+if a == a then
+print "nan equals itself";
+else
+print "nan is unequal to itself";
+end
+if a != a then
+print "nan != nan";
+end
+if a < 1 then
+print "bad";
+elseif a >= 1 then
+print "bad";
+else
+print "nan is unordered";
+end
+print 1 / 0;
+print 0 / 0;
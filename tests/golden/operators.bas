@@ -0,0 +1,19 @@
+function pair(left, right)
+  return [left, right];
+end
+
+function average(left, right)
+  return (left + right) / 2;
+end
+
+operator ".." pair 5;
+operator "<>" average 20;
+
+let name = "world";
+print "hello" .. name;
+print 2 <> 4 + 1;
+print 1 + 2 .. 3;
+print 10 <> 20 <> 40;
+if 3 < 4 then
+  print "still less than";
+end
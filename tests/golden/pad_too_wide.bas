@@ -0,0 +1 @@
+print PAD("a", 100000000000);
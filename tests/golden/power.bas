@@ -0,0 +1,9 @@
+print 2 ^ 10;
+print 2 ^ 3 ^ 2;
+print -2 ^ 2;
+print 2 * 3 ^ 2;
+print 9 ^ 0.5;
+print 2 ^ -1;
+print 1.5d ^ 3;
+print 2d ^ -2;
+print 10d ^ 100;
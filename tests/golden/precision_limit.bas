@@ -0,0 +1,3 @@
+let x = 2;
+print x / 3;
+print 1.5;
@@ -0,0 +1,15 @@
+#DEFINE LIMIT 3
+let x = 0;
+while x < LIMIT then
+    x = x + 1;
+#IF DEBUG
+    eprint "debug: x is", x;
+#ELSE
+    print "x is", x;
+#END
+end
+print "LIMIT stays in strings", LIMIT;
+#IF VERBOSE
+print "verbose";
+#END
+print 1.5d * 2, "done";
@@ -0,0 +1,4 @@
+print "hello world";
+print "Hello Compiler";
+print "Wat";
+print "I'm going to Disney World";
\ No newline at end of file
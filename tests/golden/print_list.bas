@@ -0,0 +1,5 @@
+let name = "widget";
+let qty = 3;
+print name, qty, 2.5d;
+print "single";
+eprint "to", "stderr";
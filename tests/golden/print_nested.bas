@@ -0,0 +1,9 @@
+let inner = [1, 2.5d, "a, b"];
+let outer = [inner, [inner, []], {"list": inner, "empty": {}}];
+print outer;
+inner[0] = 9;
+print inner;
+print outer[0];
+let m = {"b": {"c": {"d": [TRUE, FALSE]}}, "a": 1};
+print m;
+print m["b"];
@@ -0,0 +1,5 @@
+function forever(n)
+  return forever(n + 1);
+end
+
+print forever(0);
@@ -0,0 +1,9 @@
+let i = 0;
+while i < 10 then
+  i = i + 1;
+  if i == 3 then
+    print "stopping at", i;
+    return 3;
+  end
+end
+print "never printed";
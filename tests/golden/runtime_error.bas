@@ -0,0 +1,5 @@
+let x = 4;
+let y = "abc";
+print x;
+print x + y;
+print y;
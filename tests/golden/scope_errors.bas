@@ -0,0 +1,3 @@
+function f(x)
+  global x;
+end
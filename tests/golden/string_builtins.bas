@@ -0,0 +1,9 @@
+print PAD("name", 8), "|";
+print PAD(42, 6, "right"), "|";
+print PAD("mid", 8, "CENTER"), "|";
+print PAD("too long", 3), "|";
+print STARTSWITH("report.txt", "report"), STARTSWITH("report.txt", "txt");
+print ENDSWITH("report.txt", ".txt"), ENDSWITH("report.txt", "report");
+print REPLACE("a.b.c", ".", " + ");
+print REPLACE("no match", "x", "y");
+print PAD("x", 4, "middle");
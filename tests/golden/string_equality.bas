@@ -0,0 +1,13 @@
+let a = "ab";
+let b = "ab";
+let c = "cd";
+let d = REPLACE("xd", "x", "c");
+if a == b then
+print 1;
+end
+if a != c then
+print 2;
+end
+if d == c then
+print 3;
+end
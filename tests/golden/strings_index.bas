@@ -0,0 +1,13 @@
+let s = "héllo wörld";
+print s[0];
+print s[1];
+print s[7];
+let i = 4;
+print s[i];
+print s[i - 4];
+print ord(s[1]);
+print chr(72);
+print chr(ord("a") + 1);
+print chr(128512);
+print ord("€");
+INSPECT s;
@@ -0,0 +1,10 @@
+let x = 1;
+print x +;
+let y = 2 # 3;
+while x < 3 then
+    print "unclosed;
+    x = x + 1;
+end
+print x !;
+let z = 1.;
+print z;
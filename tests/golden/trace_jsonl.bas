@@ -0,0 +1,7 @@
+let x = 0;
+while x < 2 then
+    x = x + 1;
+    if x == 2 then
+        print "done";
+    end
+end
@@ -0,0 +1,21 @@
+let total = 0;
+let spare = 1;
+let n = 5;
+let one = "1";
+
+function scale(n)
+  let total = n * 2;
+  return total;
+end
+
+let i = 0;
+while i < 3 then
+  total = total + scale(i);
+  i = i + one;
+end
+
+if total = 6 then
+  print "six";
+end
+print total;
+print i * one;
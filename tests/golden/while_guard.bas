@@ -0,0 +1,35 @@
+let i = 0;
+while i < 3 then
+  print i;
+  i = i + 1;
+end
+
+let n = 10;
+while n >= 2.5 then
+  n = n / 2;
+end
+print n;
+
+let d = 0.1d;
+while d < 0.4 then
+  d = d + 0.1d;
+end
+print d;
+
+function countdown(i)
+  while i > 0 then
+    print i;
+    i = i - 1;
+  end
+  return i;
+end
+print countdown(2);
+print i;
+
+let x = 0;
+while x != 2 then
+  x = x + 1;
+  if x == 2 then
+    x = "two";
+  end
+end
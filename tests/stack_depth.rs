@@ -0,0 +1,40 @@
+// The default limits have to hold on any thread an embedder runs a program on, not just the
+// command line's own - these run on a plain spawned thread, which gets 2MB of stack
+use hello_rust::interpreter::error::RuntimeErrorKind;
+use hello_rust::interpreter::intr::Interpreter;
+use hello_rust::interpreter::parser;
+
+use std::thread;
+
+fn run_on_spawned_thread(source: String) -> Result<(), RuntimeErrorKind> {
+    thread::spawn(move || {
+        let ast = parser::parse_str(&source).expect("program should parse");
+        Interpreter::standalone().run(&ast).map(|_| ()).map_err(|err| err.get_kind())
+    }).join().expect("interpreter thread should not overflow its stack")
+}
+
+#[test]
+fn deep_recursion_is_an_error() {
+    let source = String::from("function f(n)\n  if n > 0 then\n    return f(n - 1);\n  end\n  return 0;\nend\nprint f(199);\n");
+    assert_eq!(run_on_spawned_thread(source), Err(RuntimeErrorKind::BudgetExceeded));
+}
+
+#[test]
+fn deepest_parsed_nesting_runs() {
+    // As deep as the parser takes, both in blocks and within an expression
+    let source = format!("let x = 1;\n{}let y = {}1;\n{}", "while x < 2 then\n".repeat(31), "-".repeat(31), "x = 2;\nend\n".repeat(31));
+    assert_eq!(run_on_spawned_thread(source), Ok(()));
+}
+
+#[test]
+fn longest_parsed_chain_runs() {
+    // Chained operators don't count as nesting, so this goes well past it, inside the deepest blocks
+    let source = format!("let x = 1;\n{}let y = x{};\n{}", "while x < 2 then\n".repeat(31), " + 1".repeat(500), "x = 2;\nend\n".repeat(31));
+    assert_eq!(run_on_spawned_thread(source), Ok(()));
+}
+
+#[test]
+fn recursion_through_nested_blocks_is_an_error() {
+    let source = format!("function f(n)\n  let x = 1;\n{}  if n > 0 then\n    return f(n - 1);\n  end\n{}  return 0;\nend\nprint f(1000);\n", "while x < 2 then\n".repeat(28), "x = 2;\nend\n".repeat(28));
+    assert_eq!(run_on_spawned_thread(source), Err(RuntimeErrorKind::BudgetExceeded));
+}